@@ -0,0 +1,30 @@
+//! Deserializes Engine API payload attributes from JSON and decodes them into payload builder
+//! attributes, as `engine_forkchoiceUpdatedV3` does with the attributes ev-node sends.
+
+#![no_main]
+
+use alloy_primitives::B256;
+use ev_reth::attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes};
+use libfuzzer_sys::fuzz_target;
+use reth_payload_primitives::PayloadBuilderAttributes;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(attributes) = serde_json::from_slice::<RollkitEnginePayloadAttributes>(data) else {
+        return;
+    };
+
+    // Deserialized attributes serialize back to the same attributes
+    let json = serde_json::to_vec(&attributes).expect("attributes should serialize");
+    let decoded = serde_json::from_slice::<RollkitEnginePayloadAttributes>(&json)
+        .expect("serialized attributes should deserialize");
+    assert_eq!(decoded, attributes);
+
+    let transactions = attributes.transactions.as_ref().map_or(0, Vec::len);
+    let Ok(builder_attributes) =
+        RollkitEnginePayloadBuilderAttributes::try_new(B256::ZERO, attributes, 3)
+    else {
+        return;
+    };
+    assert_eq!(builder_attributes.transactions.len(), transactions);
+    let _ = builder_attributes.rollkit_attributes(1).validate();
+});
@@ -0,0 +1,47 @@
+//! Decodes arbitrary transaction bytes sent in Engine API payload attributes.
+//!
+//! Attributes decode if and only if every transaction does on its own, and decoded
+//! transactions can have their signer recovered without panicking.
+
+#![no_main]
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_eips::Decodable2718;
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_rpc_types_engine::PayloadAttributes;
+use ev_reth::attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes};
+use libfuzzer_sys::fuzz_target;
+use reth_ethereum_primitives::TransactionSigned;
+use reth_payload_primitives::PayloadBuilderAttributes;
+
+fuzz_target!(|transactions: Vec<Vec<u8>>| {
+    let decodable = transactions
+        .iter()
+        .all(|tx| TransactionSigned::network_decode(&mut tx.as_slice()).is_ok());
+    let attributes = RollkitEnginePayloadAttributes {
+        inner: PayloadAttributes {
+            timestamp: 1,
+            prev_randao: B256::ZERO,
+            suggested_fee_recipient: Address::ZERO,
+            withdrawals: Some(Vec::new()),
+            parent_beacon_block_root: Some(B256::ZERO),
+        },
+        transactions: Some(transactions.into_iter().map(Bytes::from).collect()),
+        gas_limit: Some(30_000_000),
+        da_info: None,
+        timestamp_millis: None,
+    };
+
+    match RollkitEnginePayloadBuilderAttributes::try_new(B256::ZERO, attributes, 3) {
+        Ok(attributes) => {
+            assert!(
+                decodable,
+                "attributes decoded with an undecodable transaction"
+            );
+            for tx in &attributes.transactions {
+                let _ = tx.recover_signer();
+            }
+        }
+        Err(_) => assert!(!decodable, "attributes rejected decodable transactions"),
+    }
+});
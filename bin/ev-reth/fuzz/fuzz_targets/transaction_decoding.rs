@@ -0,0 +1,37 @@
+//! Decodes arbitrary bytes as a transaction, in the network encoding payload attributes are
+//! decoded with and in the EIP-2718 encoding of payloads and `eth_sendRawTransaction`.
+//!
+//! Decoded transactions re-encode to bytes decoding to the same transaction, and their
+//! signer can be recovered without panicking.
+
+#![no_main]
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_eips::{Decodable2718, Encodable2718};
+use libfuzzer_sys::fuzz_target;
+use reth_ethereum_primitives::TransactionSigned;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tx) = TransactionSigned::network_decode(&mut &data[..]) {
+        check_roundtrip(&tx);
+    }
+    if let Ok(tx) = TransactionSigned::decode_2718(&mut &data[..]) {
+        check_roundtrip(&tx);
+    }
+});
+
+/// Checks that `tx` survives re-encoding and that its signer can be recovered
+fn check_roundtrip(tx: &TransactionSigned) {
+    let encoded = tx.encoded_2718();
+    let decoded = TransactionSigned::decode_2718(&mut encoded.as_slice())
+        .expect("an encoded transaction should decode");
+    assert_eq!(&decoded, tx);
+
+    let mut network = Vec::new();
+    tx.network_encode(&mut network);
+    let decoded = TransactionSigned::network_decode(&mut network.as_slice())
+        .expect("a network encoded transaction should decode");
+    assert_eq!(&decoded, tx);
+
+    let _ = tx.recover_signer();
+}
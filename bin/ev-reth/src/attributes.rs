@@ -4,12 +4,14 @@ use alloy_rpc_types::{
     engine::{PayloadAttributes as EthPayloadAttributes, PayloadId},
     Withdrawal,
 };
+use evolve_ev_reth::{DaBlockInfo, RollkitPayloadAttributes};
 use reth_ethereum::{
     node::api::payload::{PayloadAttributes, PayloadBuilderAttributes},
     TransactionSigned,
 };
 use reth_payload_builder::EthPayloadBuilderAttributes;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::error::RollkitEngineError;
 
@@ -24,6 +26,12 @@ pub struct RollkitEnginePayloadAttributes {
     /// Optional gas limit for the payload
     #[serde(rename = "gasLimit")]
     pub gas_limit: Option<u64>,
+    /// Optional DA layer block info to write into the DA info predeploy
+    #[serde(rename = "daInfo")]
+    pub da_info: Option<DaBlockInfo>,
+    /// Optional block timestamp in milliseconds, within the second of `timestamp`
+    #[serde(rename = "timestampMillis", default)]
+    pub timestamp_millis: Option<u64>,
 }
 
 impl PayloadAttributes for RollkitEnginePayloadAttributes {
@@ -49,6 +57,28 @@ pub struct RollkitEnginePayloadBuilderAttributes {
     pub transactions: Vec<TransactionSigned>,
     /// Gas limit for the payload
     pub gas_limit: Option<u64>,
+    /// DA layer block info for the payload
+    pub da_info: Option<DaBlockInfo>,
+    /// Block timestamp in milliseconds for the payload
+    pub timestamp_millis: Option<u64>,
+}
+
+impl RollkitEnginePayloadBuilderAttributes {
+    /// Returns the attributes for the Rollkit payload builder of the block at `block_number`
+    pub fn rollkit_attributes(&self, block_number: u64) -> RollkitPayloadAttributes {
+        let mut attributes = RollkitPayloadAttributes::new(
+            self.transactions.clone(),
+            self.gas_limit,
+            self.timestamp(),
+            self.prev_randao(),
+            self.suggested_fee_recipient(),
+            self.parent(),
+            block_number,
+        );
+        attributes.da_info = self.da_info;
+        attributes.timestamp_millis = self.timestamp_millis;
+        attributes
+    }
 }
 
 impl PayloadBuilderAttributes for RollkitEnginePayloadBuilderAttributes {
@@ -77,11 +107,30 @@ impl PayloadBuilderAttributes for RollkitEnginePayloadBuilderAttributes {
             ethereum_attributes,
             transactions,
             gas_limit: attributes.gas_limit,
+            da_info: attributes.da_info,
+            timestamp_millis: attributes.timestamp_millis,
         })
     }
 
+    /// The id of the Ethereum attributes, extended with the DA info and the millisecond
+    /// timestamp so that attributes differing only in those start separate payload jobs
     fn payload_id(&self) -> PayloadId {
-        self.ethereum_attributes.id
+        if self.da_info.is_none() && self.timestamp_millis.is_none() {
+            return self.ethereum_attributes.id;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.ethereum_attributes.id.0);
+        // Each field is tagged, so that one cannot pass for the other
+        if let Some(da_info) = self.da_info {
+            hasher.update([0x01]);
+            hasher.update(da_info.encode_extra_data());
+        }
+        if let Some(timestamp_millis) = self.timestamp_millis {
+            hasher.update([0x02]);
+            hasher.update(timestamp_millis.to_be_bytes());
+        }
+        let out = hasher.finalize();
+        PayloadId::new(out[..8].try_into().expect("sufficient length"))
     }
 
     fn parent(&self) -> B256 {
@@ -1,7 +1,11 @@
-use alloy_primitives::U256;
+use alloy_primitives::{Address, U256};
+use alloy_signer_local::PrivateKeySigner;
 use clap::Parser;
-use ev_node::{RollkitPayloadBuilder, RollkitPayloadBuilderConfig};
-use evolve_ev_reth::RollkitPayloadAttributes;
+use ev_node::{ConfigError, RollkitEvmConfig, RollkitPayloadBuilder, RollkitPayloadBuilderConfig};
+use evolve_ev_reth::{
+    sequencer::load_keystore, BuildAttributesRecorder, EvolveChainConfig, Preconfirmations,
+    RollkitPayloadAttributes,
+};
 use reth_basic_payload_builder::{
     BuildArguments, BuildOutcome, HeaderForPayload, MissingPayloadBehaviour, PayloadBuilder,
     PayloadConfig,
@@ -11,7 +15,6 @@ use reth_ethereum::{
     node::{
         api::{payload::PayloadBuilderAttributes, FullNodeTypes, NodeTypes},
         builder::{components::PayloadBuilderBuilder, BuilderContext},
-        EthEvmConfig,
     },
     pool::{PoolTransaction, TransactionPool},
     primitives::Header,
@@ -21,10 +24,13 @@ use reth_payload_builder::{EthBuiltPayload, PayloadBuilderError};
 use reth_provider::HeaderProvider;
 use reth_revm::cached::CachedReads;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tracing::info;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use tracing::{info, warn};
 
-use crate::{attributes::RollkitEnginePayloadBuilderAttributes, RollkitEngineTypes};
+use crate::{
+    attributes::RollkitEnginePayloadBuilderAttributes, commands::batches::BatchFormat,
+    RollkitEngineTypes,
+};
 
 /// Rollkit-specific command line arguments
 #[derive(Debug, Clone, Parser, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -36,21 +42,179 @@ pub struct RollkitArgs {
         help = "Enable Evolve integration for transaction processing via Engine API"
     )]
     pub enable_rollkit: bool,
+    /// Address to serve the Evolve execution API on, disabled when unset
+    #[arg(
+        long = "ev-reth.execution-api.addr",
+        help = "Serve ev-node's execution interface (InitChain, GetTxs, ExecuteTxs, SetFinal) on this address"
+    )]
+    pub execution_api_addr: Option<SocketAddr>,
+    /// Allow serving the unauthenticated Evolve execution API on a non-loopback address
+    #[arg(
+        long = "ev-reth.execution-api.allow-remote",
+        help = "Allow serving the unauthenticated Evolve execution API on a non-loopback address"
+    )]
+    pub execution_api_allow_remote: bool,
+    /// Fee recipient of blocks produced through the Evolve execution API
+    #[arg(
+        long = "ev-reth.execution-api.fee-recipient",
+        default_value_t = Address::ZERO,
+        help = "Fee recipient of blocks produced through the Evolve execution API"
+    )]
+    pub execution_api_fee_recipient: Address,
+    /// Keystore holding the sequencer key that signs produced blocks
+    #[arg(
+        long = "ev-reth.sequencer.keystore",
+        requires = "sequencer_password_file",
+        help = "Sign produced blocks with the sequencer key in this keystore file"
+    )]
+    pub sequencer_keystore: Option<PathBuf>,
+    /// File holding the password of the sequencer keystore
+    #[arg(
+        long = "ev-reth.sequencer.password-file",
+        help = "File holding the password of the sequencer keystore"
+    )]
+    pub sequencer_password_file: Option<PathBuf>,
+    /// Maximum number of transactions per block
+    #[arg(
+        long = "ev-reth.max-transactions",
+        help = "Maximum number of transactions per block (default: 1000)"
+    )]
+    pub max_transactions: Option<usize>,
+    /// Minimum effective gas price of included transactions, in wei
+    #[arg(
+        long = "ev-reth.min-gas-price",
+        help = "Minimum effective gas price in wei of included transactions, also the txpool's minimum priority fee (default: 1 gwei, 0 on zero-fee chains)"
+    )]
+    pub min_gas_price: Option<u128>,
+    /// Maximum size of the RLP-encoded block
+    #[arg(
+        long = "ev-reth.max-block-bytes",
+        help = "Maximum size in bytes of produced RLP-encoded blocks, also bounding GetTxs and txpoolExt_getTxs (default: the chain's maxBlockBytes)"
+    )]
+    pub max_block_bytes: Option<u64>,
+    /// Record the payload attributes of every built block that becomes canonical
+    #[arg(
+        long = "ev-reth.record-build-attributes",
+        help = "Record the payload attributes of every built block that becomes canonical, including the transactions left out, for evolve_getBuildAttributes"
+    )]
+    pub record_build_attributes: bool,
+    /// Batch file to import through the engine before exiting
+    #[arg(
+        long = "ev-reth.import-batches",
+        value_name = "FILE",
+        requires = "import_batches_headers",
+        help = "Import the batches of this file, in Evolve's DA format, through the engine and exit"
+    )]
+    pub import_batches: Option<PathBuf>,
+    /// Headers of the blocks of the imported batch file
+    #[arg(
+        long = "ev-reth.import-batches-headers",
+        value_name = "FILE",
+        requires = "import_batches",
+        help = "Headers of the blocks of the imported batches, as written by export-batches --headers"
+    )]
+    pub import_batches_headers: Option<PathBuf>,
+    /// Message type of the entries of the imported batch file
+    #[arg(
+        long = "ev-reth.import-batches-format",
+        value_enum,
+        default_value_t = BatchFormat::Data,
+        help = "Message type of the entries of the imported batch file"
+    )]
+    pub import_batches_format: BatchFormat,
+}
+
+impl RollkitArgs {
+    /// Returns the address to serve the Evolve execution API on, if any.
+    ///
+    /// The API is not authenticated and anyone reaching it can produce and finalize blocks,
+    /// so only loopback addresses are accepted unless remote access is explicitly allowed.
+    pub fn execution_api_listen_addr(&self) -> eyre::Result<Option<SocketAddr>> {
+        match self.execution_api_addr {
+            Some(addr) if !addr.ip().is_loopback() && !self.execution_api_allow_remote => {
+                eyre::bail!(
+                    "the Evolve execution API is unauthenticated, pass \
+                     --ev-reth.execution-api.allow-remote to serve it on {addr}"
+                )
+            }
+            addr => Ok(addr),
+        }
+    }
+
+    /// Decrypts the sequencer key, if a keystore is configured
+    pub fn sequencer_signer(&self) -> eyre::Result<Option<PrivateKeySigner>> {
+        let Some(keystore) = &self.sequencer_keystore else {
+            return Ok(None);
+        };
+        let password_file = self
+            .sequencer_password_file
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("a sequencer keystore requires a password file"))?;
+        let password = std::fs::read_to_string(password_file)?;
+        Ok(Some(load_keystore(
+            keystore,
+            password.trim_end_matches(['\r', '\n']),
+        )?))
+    }
+
+    /// Returns the payload builder configuration for the given chain
+    pub fn payload_builder_config(
+        &self,
+        chain_config: &EvolveChainConfig,
+    ) -> Result<RollkitPayloadBuilderConfig, ConfigError> {
+        let mut config = RollkitPayloadBuilderConfig::for_chain(chain_config);
+        if let Some(max_transactions) = self.max_transactions {
+            config = config.with_max_transactions(max_transactions);
+        }
+        if let Some(min_gas_price) = self.min_gas_price {
+            config = config.with_min_gas_price(min_gas_price);
+        }
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            // Followers reject blocks above the chain's maximum
+            if let Some(chain_max) = chain_config.max_block_bytes {
+                if max_block_bytes > chain_max {
+                    return Err(ConfigError::MaxBlockBytesAboveChain {
+                        max_block_bytes,
+                        chain_max,
+                    });
+                }
+            }
+            config = config.with_max_block_bytes(Some(max_block_bytes));
+        }
+        config.validate()?;
+        Ok(config)
+    }
 }
 
 /// Rollkit payload service builder that integrates with the rollkit payload builder
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct RollkitPayloadBuilderBuilder {
-    config: RollkitPayloadBuilderConfig,
+    args: RollkitArgs,
+    preconfirmations: Option<Preconfirmations>,
+    build_attributes: Option<BuildAttributesRecorder>,
 }
 
 impl RollkitPayloadBuilderBuilder {
     /// Create a new builder with rollkit args
-    pub fn new(_args: &RollkitArgs) -> Self {
-        let config = RollkitPayloadBuilderConfig::new();
-        info!("Created Rollkit payload builder with config: {:?}", config);
-        Self { config }
+    pub fn new(args: &RollkitArgs) -> Self {
+        Self {
+            args: args.clone(),
+            preconfirmations: None,
+            build_attributes: None,
+        }
+    }
+
+    /// Publishes the results of executed transactions before blocks are sealed
+    pub fn with_preconfirmations(mut self, preconfirmations: Preconfirmations) -> Self {
+        self.preconfirmations = Some(preconfirmations);
+        self
+    }
+
+    /// Records the attributes of every built block
+    pub fn with_build_attributes(mut self, recorder: Option<BuildAttributesRecorder>) -> Self {
+        self.build_attributes = recorder;
+        self
     }
 }
 
@@ -74,7 +238,8 @@ where
     pub(crate) config: RollkitPayloadBuilderConfig,
 }
 
-impl<Node, Pool> PayloadBuilderBuilder<Node, Pool, EthEvmConfig> for RollkitPayloadBuilderBuilder
+impl<Node, Pool> PayloadBuilderBuilder<Node, Pool, RollkitEvmConfig>
+    for RollkitPayloadBuilderBuilder
 where
     Node: FullNodeTypes<
         Types: NodeTypes<
@@ -93,17 +258,39 @@ where
         self,
         ctx: &BuilderContext<Node>,
         pool: Pool,
-        evm_config: EthEvmConfig,
+        evm_config: RollkitEvmConfig,
     ) -> eyre::Result<Self::PayloadBuilder> {
-        let rollkit_builder = Arc::new(RollkitPayloadBuilder::new(
-            Arc::new(ctx.provider().clone()),
-            evm_config,
-        ));
+        let chain_config = EvolveChainConfig::from_chain_spec(&ctx.chain_spec())?;
+        let config = self.args.payload_builder_config(&chain_config)?;
+        info!("Created Rollkit payload builder with config: {:?}", config);
+        let mut rollkit_builder =
+            RollkitPayloadBuilder::new(Arc::new(ctx.provider().clone()), evm_config)
+                .with_config(config);
+        if let Some(preconfirmations) = self.preconfirmations {
+            rollkit_builder = rollkit_builder.with_preconfirmations(preconfirmations);
+        }
+        if let Some(recorder) = self.build_attributes {
+            info!("Recording the payload attributes of built blocks");
+            rollkit_builder = rollkit_builder.with_build_attributes(recorder);
+        }
+        if let Some(signer) = self.args.sequencer_signer()? {
+            if chain_config.is_scheduled(signer.address()) || !chain_config.has_proposer_schedule()
+            {
+                info!(signer = %signer.address(), "Signing blocks with sequencer key");
+            } else {
+                warn!(
+                    signer = %signer.address(),
+                    "Sequencer key is not part of the proposer schedule, its blocks will be rejected"
+                );
+            }
+            rollkit_builder = rollkit_builder.with_sequencer_signer(signer);
+        }
+        let rollkit_builder = Arc::new(rollkit_builder);
 
         Ok(RollkitEnginePayloadBuilder {
             rollkit_builder,
             pool,
-            config: self.config,
+            config,
         })
     }
 }
@@ -143,22 +330,22 @@ where
         );
 
         // Convert Engine API attributes to Rollkit payload attributes
-        let rollkit_attrs = RollkitPayloadAttributes::new(
-            attributes.transactions.clone(),
-            attributes.gas_limit,
-            attributes.timestamp(),
-            attributes.prev_randao(),
-            attributes.suggested_fee_recipient(),
-            attributes.parent(),
-            parent_header.number + 1,
-        );
+        let rollkit_attrs = attributes.rollkit_attributes(parent_header.number + 1);
 
         // Build the payload using the rollkit payload builder - use spawn_blocking for async work
         let rollkit_builder = self.rollkit_builder.clone();
-        let sealed_block = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(rollkit_builder.build_payload(rollkit_attrs))
+        let built = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(rollkit_builder.build_payload_with_report(rollkit_attrs))
         })
         .map_err(PayloadBuilderError::other)?;
+        if !built.skipped.is_empty() {
+            warn!(
+                skipped = built.skipped.len(),
+                "Rollkit engine payload builder: left transactions out of the block"
+            );
+        }
+        let sealed_block = built.block;
 
         info!(
             "Rollkit engine payload builder: built block with {} transactions, gas used: {}",
@@ -193,7 +380,7 @@ where
         info!("Rollkit engine payload builder: building empty payload");
 
         // Create empty rollkit attributes (no transactions)
-        let rollkit_attrs = RollkitPayloadAttributes::new(
+        let mut rollkit_attrs = RollkitPayloadAttributes::new(
             vec![],
             attributes.gas_limit,
             attributes.timestamp(),
@@ -202,6 +389,12 @@ where
             attributes.parent(),
             parent_header.number + 1,
         );
+        if let Some(da_info) = attributes.da_info {
+            rollkit_attrs = rollkit_attrs.with_da_info(da_info);
+        }
+        if let Some(timestamp_millis) = attributes.timestamp_millis {
+            rollkit_attrs = rollkit_attrs.with_timestamp_millis(timestamp_millis);
+        }
 
         // Build empty payload - use spawn_blocking for async work
         let rollkit_builder = self.rollkit_builder.clone();
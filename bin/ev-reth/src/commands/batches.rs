@@ -0,0 +1,378 @@
+//! `ev-reth export-batches` and `ev-reth import-batches` commands.
+//!
+//! Batches are written in the format Evolve posts to DA: one `evnode.v1.Data` protobuf
+//! message per block, holding the block's metadata and its EIP-2718 encoded transactions.
+//! A batch file is a stream of length-delimited messages, as produced by `protodelim` on the
+//! Go side.
+//!
+//! Batches only carry transactions, so the export also writes the headers of the blocks to a
+//! headers file, the RLP-encoded headers one after the other. The import puts each block
+//! back together from its header and batch and imports it as is, sequencer signature and
+//! fee recipient included.
+
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::B256;
+use alloy_rlp::{Decodable, Encodable};
+use clap::{Parser, ValueEnum};
+use evolve_ev_reth::EvolveExtraData;
+use prost::Message;
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_ethereum::{
+    chainspec::ChainSpecProvider,
+    pool::TransactionPool,
+    primitives::{Header, SealedBlock},
+    TransactionSigned,
+};
+use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use reth_ethereum_primitives::{Block, BlockBody};
+use reth_primitives_traits::BlockBody as _;
+use reth_provider::{
+    BlockIdReader, BlockNumReader, BlockReader, CanonChainTracker, ChainStateBlockWriter,
+    DatabaseProviderFactory, HeaderProvider, TransactionsProvider,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+use crate::{
+    execution::{service::INITIAL_HEIGHT, EvolveExecutionService},
+    RollkitNode,
+};
+
+/// Name of the import command, which runs the node
+pub const IMPORT_BATCHES: &str = "import-batches";
+
+const NANOS_PER_MILLI: u64 = 1_000_000;
+
+/// Metadata of a batch, mirroring `evnode.v1.Metadata`
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Metadata {
+    /// Chain ID of ev-node
+    #[prost(string, tag = "1")]
+    pub chain_id: String,
+    /// Height of the block
+    #[prost(uint64, tag = "2")]
+    pub height: u64,
+    /// Timestamp of the block in nanoseconds since the Unix epoch
+    #[prost(uint64, tag = "3")]
+    pub time: u64,
+    /// Hash of the previous batch, empty for the first block
+    #[prost(bytes = "vec", tag = "4")]
+    pub last_data_hash: Vec<u8>,
+}
+
+/// Transactions of a block as posted to DA, mirroring `evnode.v1.Data`
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct Data {
+    /// Metadata of the batch
+    #[prost(message, optional, tag = "1")]
+    pub metadata: Option<Metadata>,
+    /// EIP-2718 encoded transactions
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub txs: Vec<Vec<u8>>,
+}
+
+/// Batch signed by the sequencer, mirroring `evnode.v1.SignedData`.
+///
+/// The signer (field 3) is not decoded.
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct SignedData {
+    /// Signed batch
+    #[prost(message, optional, tag = "1")]
+    pub data: Option<Data>,
+    /// Signature of the sequencer over the batch
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: Vec<u8>,
+}
+
+/// Message type of the entries of a batch file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BatchFormat {
+    /// `evnode.v1.Data` messages, as written by `ev-reth export-batches`
+    #[default]
+    Data,
+    /// `evnode.v1.SignedData` messages, as posted to DA by ev-node
+    SignedData,
+}
+
+/// Returns the hash of a batch, as linked to by the next batch's `last_data_hash`.
+///
+/// This is ev-node's `Data.Hash`, the SHA-256 Merkle leaf hash of the encoded message.
+pub fn data_hash(data: &Data) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(data.encode_to_vec());
+    B256::from_slice(&hasher.finalize())
+}
+
+/// Writes batches to a file as length-delimited messages
+pub fn write_batches(path: &Path, batches: &[Data]) -> eyre::Result<()> {
+    let mut buf = Vec::new();
+    for data in batches {
+        data.encode_length_delimited(&mut buf)?;
+    }
+    fs::write(path, buf)?;
+    Ok(())
+}
+
+/// Reads the batches of a file of length-delimited messages
+pub fn read_batches(path: &Path, format: BatchFormat) -> eyre::Result<Vec<Data>> {
+    let bytes = fs::read(path)?;
+    let mut buf = bytes.as_slice();
+    let mut batches = Vec::new();
+    while !buf.is_empty() {
+        let data = match format {
+            BatchFormat::Data => Data::decode_length_delimited(&mut buf)?,
+            BatchFormat::SignedData => SignedData::decode_length_delimited(&mut buf)?
+                .data
+                .ok_or_else(|| eyre::eyre!("{}: signed batch without data", path.display()))?,
+        };
+        batches.push(data);
+    }
+    Ok(batches)
+}
+
+/// Writes headers to a file, RLP-encoded one after the other
+pub fn write_headers(path: &Path, headers: &[Header]) -> eyre::Result<()> {
+    let mut buf = Vec::new();
+    for header in headers {
+        header.encode(&mut buf);
+    }
+    fs::write(path, buf)?;
+    Ok(())
+}
+
+/// Reads the headers of a file of RLP-encoded headers
+pub fn read_headers(path: &Path) -> eyre::Result<Vec<Header>> {
+    let bytes = fs::read(path)?;
+    let mut buf = bytes.as_slice();
+    let mut headers = Vec::new();
+    while !buf.is_empty() {
+        headers.push(Header::decode(&mut buf)?);
+    }
+    Ok(headers)
+}
+
+/// Puts the block of a batch back together from its exported header.
+///
+/// Fails if the batch does not hold the header's transactions or was not made from the
+/// block, so the block is imported exactly as exported.
+pub fn block_from_batch(header: Header, data: &Data) -> eyre::Result<SealedBlock> {
+    let metadata = data
+        .metadata
+        .as_ref()
+        .ok_or_else(|| eyre::eyre!("batch without metadata"))?;
+    let height = metadata.height;
+    if header.number != height {
+        eyre::bail!(
+            "batch at height {height} is paired with the header of block {}",
+            header.number
+        );
+    }
+    let millis = EvolveExtraData::timestamp_millis_of(&header);
+    if metadata.time / NANOS_PER_MILLI != millis {
+        eyre::bail!(
+            "batch at height {height} has time {}ns, its block was produced at {millis}ms",
+            metadata.time
+        );
+    }
+
+    let transactions = data
+        .txs
+        .iter()
+        .map(|tx| TransactionSigned::decode_2718(&mut tx.as_slice()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let body = BlockBody {
+        transactions,
+        ommers: Vec::new(),
+        withdrawals: header.withdrawals_root.map(|_| Default::default()),
+    };
+    if body.calculate_tx_root() != header.transactions_root {
+        eyre::bail!("transactions of the batch at height {height} do not match its header");
+    }
+    Ok(SealedBlock::seal_slow(Block { header, body }))
+}
+
+/// Rewrites `ev-reth import-batches --batches FILE --headers FILE [--format FORMAT] ...` into
+/// the node command importing the batches. Other arguments are returned unchanged.
+pub fn node_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut args: Vec<String> = args.into_iter().collect();
+    if args.get(1).map(String::as_str) != Some(IMPORT_BATCHES) {
+        return args;
+    }
+
+    args[1] = "node".to_string();
+    for arg in &mut args[2..] {
+        for (flag, node_flag) in [
+            ("--batches", "--ev-reth.import-batches"),
+            ("--headers", "--ev-reth.import-batches-headers"),
+            ("--format", "--ev-reth.import-batches-format"),
+        ] {
+            if arg == flag {
+                *arg = node_flag.to_string();
+            } else if let Some(value) = arg
+                .strip_prefix(flag)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                *arg = format!("{node_flag}={value}");
+            }
+        }
+    }
+    args
+}
+
+/// Export blocks of a datadir as batches in Evolve's DA format
+#[derive(Debug, Parser)]
+pub struct ExportBatchesCommand {
+    #[command(flatten)]
+    env: EnvironmentArgs<EthereumChainSpecParser>,
+
+    /// First block to export
+    #[arg(long)]
+    from: u64,
+
+    /// Last block to export
+    #[arg(long)]
+    to: u64,
+
+    /// File to write the batches to
+    #[arg(long, value_name = "FILE")]
+    output: PathBuf,
+
+    /// File to write the headers of the exported blocks to, which `import-batches` needs
+    #[arg(long, value_name = "FILE")]
+    headers: Option<PathBuf>,
+
+    /// Chain ID of ev-node written into the metadata of each batch, the EVM chain ID by
+    /// default
+    #[arg(long)]
+    chain_id: Option<String>,
+}
+
+impl ExportBatchesCommand {
+    /// Exports the blocks `from..=to`
+    pub fn execute(self) -> eyre::Result<()> {
+        if self.from < INITIAL_HEIGHT || self.to < self.from {
+            eyre::bail!(
+                "invalid range {}..={}, blocks start at height {INITIAL_HEIGHT}",
+                self.from,
+                self.to
+            );
+        }
+        let Environment {
+            provider_factory, ..
+        } = self.env.init::<RollkitNode>(AccessRights::RO)?;
+        let chain_id = self
+            .chain_id
+            .unwrap_or_else(|| provider_factory.chain_spec().chain().id().to_string());
+        let provider = provider_factory.provider()?;
+
+        // Every batch links to the hash of the one before it, so the chain of hashes is
+        // followed from the first block even when exporting a later range
+        let mut last_data_hash = Vec::new();
+        let mut batches = Vec::new();
+        let mut headers = Vec::new();
+        for number in INITIAL_HEIGHT..=self.to {
+            let block = provider
+                .block_by_number(number)?
+                .ok_or_else(|| eyre::eyre!("block {number} is not in the datadir"))?;
+            let data = Data {
+                metadata: Some(Metadata {
+                    chain_id: chain_id.clone(),
+                    height: number,
+                    time: EvolveExtraData::timestamp_millis_of(&block.header)
+                        .saturating_mul(NANOS_PER_MILLI),
+                    last_data_hash,
+                }),
+                txs: block
+                    .body
+                    .transactions
+                    .iter()
+                    .map(|tx| tx.encoded_2718())
+                    .collect(),
+            };
+            last_data_hash = data_hash(&data).to_vec();
+            if number >= self.from {
+                batches.push(data);
+                headers.push(block.header);
+            }
+        }
+
+        write_batches(&self.output, &batches)?;
+        if let Some(path) = &self.headers {
+            write_headers(path, &headers)?;
+        }
+        info!(
+            from = self.from,
+            to = self.to,
+            path = %self.output.display(),
+            "Exported batches"
+        );
+        Ok(())
+    }
+}
+
+/// Imports the batches of a file through the engine.
+///
+/// Each block is put back together from its batch and its header in `headers` and imported
+/// as is, the way a follower receiving it over the Engine API would, so it is validated by
+/// `RollkitConsensus` and keeps its hash. Batches must follow each other from the datadir's
+/// head, and heights the datadir already holds must hold the same blocks.
+pub async fn import_batches<Provider, Pool>(
+    service: &EvolveExecutionService<Provider, Pool>,
+    path: &Path,
+    headers: &Path,
+    format: BatchFormat,
+) -> eyre::Result<()>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    let batches = read_batches(path, format)?;
+    let headers = read_headers(headers)?;
+    if headers.len() != batches.len() {
+        eyre::bail!(
+            "{} batches but {} headers, export both from the same range",
+            batches.len(),
+            headers.len()
+        );
+    }
+
+    let mut previous: Option<(u64, B256)> = None;
+    for (data, header) in batches.into_iter().zip(headers) {
+        let hash = data_hash(&data);
+        let block = block_from_batch(header, &data)?;
+        let height = block.number;
+        if let Some((previous_height, previous_hash)) = previous {
+            if height != previous_height + 1 {
+                eyre::bail!("batch at height {height} does not follow height {previous_height}");
+            }
+            let last_data_hash = data
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.last_data_hash.as_slice());
+            if last_data_hash != Some(previous_hash.as_slice()) {
+                eyre::bail!("batch at height {height} does not link to the batch before it");
+            }
+        }
+
+        let imported = service.import_block(block).await?;
+        info!(height, hash = %imported, "Imported batch");
+        previous = Some((height, hash));
+    }
+
+    info!(path = %path.display(), "Imported all batches");
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! `ev-reth genesis` command.
+
+use alloy_primitives::{Address, U256};
+use clap::Parser;
+use evolve_ev_reth::genesis::{
+    GenesisConfig, MnemonicAccounts, PrefundedAccount, DEFAULT_MNEMONIC_ACCOUNTS,
+    DEFAULT_MNEMONIC_BALANCE,
+};
+use reth_cli::chainspec::ChainSpecParser;
+use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+/// Generate an Evolve genesis file from a TOML description and/or flags
+#[derive(Debug, Parser)]
+pub struct GenesisCommand {
+    /// TOML file describing the chain, flags override its values
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Chain ID
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    /// Block gas limit of the genesis block
+    #[arg(long)]
+    gas_limit: Option<u64>,
+
+    /// Timestamp of the genesis block
+    #[arg(long)]
+    timestamp: Option<u64>,
+
+    /// Base fee of the genesis block
+    #[arg(long)]
+    base_fee: Option<u64>,
+
+    /// Shanghai activation timestamp
+    #[arg(long)]
+    shanghai_time: Option<u64>,
+
+    /// Cancun activation timestamp
+    #[arg(long)]
+    cancun_time: Option<u64>,
+
+    /// Prague activation timestamp
+    #[arg(long)]
+    prague_time: Option<u64>,
+
+    /// Prefunded account, can be repeated
+    #[arg(long = "alloc", value_name = "ADDRESS=BALANCE", value_parser = parse_prefunded_account)]
+    alloc: Vec<PrefundedAccount>,
+
+    /// Mnemonic to derive prefunded accounts from
+    #[arg(long)]
+    mnemonic: Option<String>,
+
+    /// Number of accounts derived from the mnemonic
+    #[arg(long, requires = "mnemonic")]
+    mnemonic_accounts: Option<u32>,
+
+    /// Balance in wei of every account derived from the mnemonic
+    #[arg(long, requires = "mnemonic")]
+    mnemonic_balance: Option<U256>,
+
+    /// Do not include the ev-reth predeploys
+    #[arg(long)]
+    no_predeploys: bool,
+
+    /// Output file, the genesis is printed to stdout if omitted
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl GenesisCommand {
+    /// Generates the genesis and writes it out
+    pub fn execute(self) -> eyre::Result<()> {
+        let mut config = match &self.config {
+            Some(path) => toml::from_str(&fs::read_to_string(path)?)?,
+            None => GenesisConfig::default(),
+        };
+        self.apply_overrides(&mut config);
+
+        let genesis = config.build()?;
+        let json = serde_json::to_string_pretty(&genesis)?;
+
+        // Make sure the node accepts the file exactly as written
+        EthereumChainSpecParser::parse(&json)?;
+
+        match self.output {
+            Some(path) => {
+                fs::write(&path, json)?;
+                info!(path = %path.display(), chain_id = config.chain_id, "Wrote genesis");
+            }
+            None => println!("{json}"),
+        }
+
+        Ok(())
+    }
+
+    /// Applies the flags on top of the TOML configuration
+    fn apply_overrides(&self, config: &mut GenesisConfig) {
+        if let Some(chain_id) = self.chain_id {
+            config.chain_id = chain_id;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            config.gas_limit = gas_limit;
+        }
+        if let Some(timestamp) = self.timestamp {
+            config.timestamp = timestamp;
+        }
+        if let Some(base_fee) = self.base_fee {
+            config.base_fee_per_gas = Some(base_fee);
+        }
+        if let Some(shanghai_time) = self.shanghai_time {
+            config.forks.shanghai_time = Some(shanghai_time);
+        }
+        if let Some(cancun_time) = self.cancun_time {
+            config.forks.cancun_time = Some(cancun_time);
+        }
+        if let Some(prague_time) = self.prague_time {
+            config.forks.prague_time = Some(prague_time);
+        }
+        config.accounts.extend(self.alloc.iter().cloned());
+        if let Some(phrase) = &self.mnemonic {
+            let previous = config.mnemonic.take();
+            config.mnemonic = Some(MnemonicAccounts {
+                phrase: phrase.clone(),
+                count: self
+                    .mnemonic_accounts
+                    .or_else(|| previous.as_ref().map(|m| m.count))
+                    .unwrap_or(DEFAULT_MNEMONIC_ACCOUNTS),
+                balance: self
+                    .mnemonic_balance
+                    .or_else(|| previous.as_ref().map(|m| m.balance))
+                    .unwrap_or(DEFAULT_MNEMONIC_BALANCE),
+            });
+        }
+        if self.no_predeploys {
+            config.predeploys = false;
+        }
+    }
+}
+
+/// Parses a prefunded account given as `ADDRESS=BALANCE`
+fn parse_prefunded_account(value: &str) -> eyre::Result<PrefundedAccount> {
+    let (address, balance) = value
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected ADDRESS=BALANCE, got {value}"))?;
+    Ok(PrefundedAccount {
+        address: address.parse::<Address>()?,
+        balance: balance.parse::<U256>()?,
+    })
+}
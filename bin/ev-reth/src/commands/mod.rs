@@ -0,0 +1,58 @@
+//! ev-reth specific subcommands.
+//!
+//! These commands are not part of the reth CLI. They are parsed and executed before the
+//! process arguments are handed to reth, except for `import-batches`, which runs the node
+//! and is rewritten into the `node` command by [`batches::node_args`].
+
+use clap::{Parser, Subcommand};
+
+pub mod batches;
+pub mod genesis;
+pub mod replay;
+pub mod rollback;
+
+/// ev-reth specific subcommands
+#[derive(Debug, Subcommand)]
+pub enum EvRethCommand {
+    /// Generate an Evolve genesis file
+    Genesis(genesis::GenesisCommand),
+    /// Export blocks as batches in the format Evolve posts to DA
+    ExportBatches(Box<batches::ExportBatchesCommand>),
+    /// Re-execute blocks from a batch file and report the first divergence
+    Replay(Box<replay::ReplayCommand>),
+    /// Unwind the chain to a height, along with its state, receipts and DA inclusions
+    Rollback(Box<rollback::RollbackCommand>),
+}
+
+/// Parser for the ev-reth specific subcommands
+#[derive(Debug, Parser)]
+#[command(name = "ev-reth")]
+struct EvRethCli {
+    #[command(subcommand)]
+    command: EvRethCommand,
+}
+
+impl EvRethCommand {
+    /// Names of the ev-reth specific subcommands
+    pub const NAMES: &[&str] = &["genesis", "export-batches", "replay", "rollback"];
+
+    /// Parses an ev-reth specific subcommand from the process arguments.
+    ///
+    /// Returns `None` if the arguments name a reth command instead.
+    pub fn parse_from_env() -> Option<Self> {
+        let name = std::env::args().nth(1)?;
+        Self::NAMES
+            .contains(&name.as_str())
+            .then(|| EvRethCli::parse().command)
+    }
+
+    /// Executes the command
+    pub fn execute(self) -> eyre::Result<()> {
+        match self {
+            Self::Genesis(command) => command.execute(),
+            Self::ExportBatches(command) => command.execute(),
+            Self::Replay(command) => command.execute(),
+            Self::Rollback(command) => command.execute(),
+        }
+    }
+}
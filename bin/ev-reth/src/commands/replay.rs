@@ -0,0 +1,260 @@
+//! `ev-reth replay` command.
+
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_rlp::Decodable;
+use clap::Parser;
+use ev_node::{
+    attributes_from_block, compare_headers, diff_state, AccountDiff, RollkitEvmConfig,
+    RollkitPayloadBuilder, RollkitPayloadBuilderConfig,
+};
+use evolve_ev_reth::{BuildAttributes, EvolveChainConfig};
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_ethereum::{
+    chainspec::ChainSpecProvider, node::api::payload::PayloadBuilderAttributes,
+    primitives::SealedBlock,
+};
+use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use reth_ethereum_primitives::Block;
+use reth_primitives_traits::SealedHeader;
+use reth_provider::{
+    providers::BlockchainProvider, BlockHashReader, ChangeSetReader, HeaderProvider,
+    StateProviderFactory, StorageChangeSetReader,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+use crate::{
+    attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes},
+    RollkitNode,
+};
+
+/// Entry of a batch file, which holds one JSON object per line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchEntry {
+    /// Block as produced by the sequencer, replayed from the attributes it implies
+    Block {
+        /// RLP-encoded block
+        block: Bytes,
+    },
+    /// Payload attributes as sent by Evolve with `engine_forkchoiceUpdated`
+    #[serde(rename_all = "camelCase")]
+    Attributes {
+        /// Hash of the block the payload is built on
+        parent_hash: B256,
+        /// Payload attributes, including the transactions the builder may still skip
+        attributes: RollkitEnginePayloadAttributes,
+    },
+    /// Build attributes recorded by a node with `--ev-reth.record-build-attributes`
+    Recorded(BuildAttributes),
+}
+
+/// Re-execute blocks from a batch file against a datadir and report the first divergence
+#[derive(Debug, Parser)]
+pub struct ReplayCommand {
+    #[command(flatten)]
+    env: EnvironmentArgs<EthereumChainSpecParser>,
+
+    /// Batch file holding one block or set of payload attributes per line
+    #[arg(long, value_name = "FILE")]
+    batches: PathBuf,
+
+    /// Maximum number of transactions per block built from payload attributes
+    #[arg(long)]
+    max_transactions: Option<usize>,
+
+    /// Minimum effective gas price in wei of transactions built from payload attributes
+    #[arg(long)]
+    min_gas_price: Option<u128>,
+
+    /// Maximum size in bytes of RLP-encoded blocks built from payload attributes
+    #[arg(long)]
+    max_block_bytes: Option<u64>,
+}
+
+impl ReplayCommand {
+    /// Replays the batches, failing at the first block that diverges
+    pub fn execute(self) -> eyre::Result<()> {
+        let entries = read_batch_file(&self.batches)?;
+        let Environment {
+            provider_factory, ..
+        } = self.env.init::<RollkitNode>(AccessRights::RO)?;
+
+        let chain_spec = provider_factory.chain_spec();
+        let chain_config = EvolveChainConfig::from_chain_spec(&chain_spec)?;
+        let mut config = RollkitPayloadBuilderConfig::for_chain(&chain_config);
+        if let Some(max_transactions) = self.max_transactions {
+            config = config.with_max_transactions(max_transactions);
+        }
+        if let Some(min_gas_price) = self.min_gas_price {
+            config = config.with_min_gas_price(min_gas_price);
+        }
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            config = config.with_max_block_bytes(Some(max_block_bytes));
+        }
+        config.validate()?;
+
+        let client = Arc::new(BlockchainProvider::new(provider_factory.clone())?);
+        let evm_config = RollkitEvmConfig::try_new(chain_spec)?;
+        let attributes_builder =
+            RollkitPayloadBuilder::new(client.clone(), evm_config.clone()).with_config(config);
+        // Blocks only hold the transactions the sequencer included, whatever its limits were
+        let block_builder = RollkitPayloadBuilder::new(client.clone(), evm_config).with_config(
+            RollkitPayloadBuilderConfig::new()
+                .with_max_transactions(usize::MAX)
+                .with_min_gas_price(0),
+        );
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        for (line, entry) in entries {
+            let (attributes, expected, builder) = match entry {
+                BatchEntry::Block { block } => {
+                    let block = SealedBlock::seal_slow(Block::decode(&mut block.as_ref())?);
+                    let expected = SealedHeader::new(block.header().clone(), block.hash());
+                    (attributes_from_block(&block), expected, &block_builder)
+                }
+                BatchEntry::Attributes {
+                    parent_hash,
+                    attributes,
+                } => {
+                    let parent = client.header(&parent_hash)?.ok_or_else(|| {
+                        eyre::eyre!("line {line}: parent {parent_hash} is not in the datadir")
+                    })?;
+                    let number = parent.number + 1;
+                    let expected = client.sealed_header(number)?.ok_or_else(|| {
+                        eyre::eyre!(
+                            "line {line}: the datadir holds no block {number} to compare with"
+                        )
+                    })?;
+                    let attributes =
+                        RollkitEnginePayloadBuilderAttributes::try_new(parent_hash, attributes, 3)?
+                            .rollkit_attributes(number);
+                    (attributes, expected, &attributes_builder)
+                }
+                BatchEntry::Recorded(recorded) => {
+                    let header = client.header(&recorded.block_hash)?.ok_or_else(|| {
+                        eyre::eyre!(
+                            "line {line}: block {} is not in the datadir",
+                            recorded.block_hash
+                        )
+                    })?;
+                    let expected = SealedHeader::new(header, recorded.block_hash);
+                    (
+                        recorded.payload_attributes()?,
+                        expected,
+                        &attributes_builder,
+                    )
+                }
+            };
+            let number = attributes.block_number;
+            let parent_hash = attributes.parent_hash;
+            if client.header(&parent_hash)?.is_none() {
+                eyre::bail!(
+                    "line {line}: parent {parent_hash} of block {number} is not in the datadir"
+                );
+            }
+
+            let built = runtime.block_on(builder.build_payload_with_report(attributes))?;
+            let mismatches = compare_headers(expected.header(), built.block.header());
+            if mismatches.is_empty() {
+                info!(number, hash = %expected.hash(), "Replayed block matches");
+                continue;
+            }
+
+            println!("Block {number} ({}) diverges:", expected.hash());
+            for mismatch in &mismatches {
+                println!("  {mismatch}");
+            }
+
+            // Diff against the datadir's own version of the block, if it has one
+            match client.block_hash(number)? {
+                Some(hash) => {
+                    let provider = provider_factory.provider()?;
+                    let mut expected_changes = BTreeMap::<Address, BTreeSet<B256>>::new();
+                    for change in provider.account_block_changeset(number)? {
+                        expected_changes.entry(change.address).or_default();
+                    }
+                    for (key, entry) in provider.storage_changeset(number)? {
+                        expected_changes
+                            .entry(key.address())
+                            .or_default()
+                            .insert(entry.key);
+                    }
+                    let parent_state = client.state_by_block_hash(parent_hash)?;
+                    let expected_state = client.history_by_block_number(number)?;
+                    let diffs = diff_state(
+                        &built.bundle_state,
+                        &*parent_state,
+                        &*expected_state,
+                        &expected_changes,
+                    )?;
+                    println!("State diff against block {number} ({hash}) of the datadir:");
+                    if diffs.is_empty() {
+                        println!("  none");
+                    }
+                    for diff in &diffs {
+                        print_account_diff(diff);
+                    }
+                }
+                None => println!("The datadir holds no block {number} to diff the state against"),
+            }
+            eyre::bail!("block {number} diverges from its replay");
+        }
+
+        info!(path = %self.batches.display(), "Replayed all blocks without divergence");
+        Ok(())
+    }
+}
+
+/// Reads a batch file, returning its entries along with their line numbers
+fn read_batch_file(path: &Path) -> eyre::Result<Vec<(usize, BatchEntry)>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map(|entry| (index + 1, entry))
+                .map_err(|err| eyre::eyre!("{}:{}: {err}", path.display(), index + 1))
+        })
+        .collect()
+}
+
+/// Prints how an account differs after the replayed block, expected values in brackets
+fn print_account_diff(diff: &AccountDiff) {
+    match (&diff.expected, &diff.actual) {
+        (None, Some(_)) => println!("  {}: exists [no account]", diff.address),
+        (Some(_), None) => println!("  {}: no account [exists]", diff.address),
+        (expected, actual) => {
+            println!("  {}:", diff.address);
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                if expected.nonce != actual.nonce {
+                    println!("    nonce {} [{}]", actual.nonce, expected.nonce);
+                }
+                if expected.balance != actual.balance {
+                    println!("    balance {} [{}]", actual.balance, expected.balance);
+                }
+                if expected.bytecode_hash != actual.bytecode_hash {
+                    println!(
+                        "    code hash {:?} [{:?}]",
+                        actual.bytecode_hash, expected.bytecode_hash
+                    );
+                }
+            }
+        }
+    }
+    for slot in &diff.storage {
+        println!(
+            "    slot {}: {} [{}]",
+            slot.slot, slot.actual, slot.expected
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! `ev-reth rollback` command.
+
+use clap::Parser;
+use evolve_ev_reth::{
+    da_inclusion::{DaInclusionStore, EvolveTables},
+    BuildAttributesStore,
+};
+use reth_cli_commands::common::{AccessRights, Environment, EnvironmentArgs};
+use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use reth_provider::{
+    writer::UnifiedStorageWriter, BlockExecutionWriter, BlockNumReader, ChainStateBlockReader,
+    ChainStateBlockWriter, StorageLocation,
+};
+use tracing::info;
+
+use crate::RollkitNode;
+
+/// Unwind the chain of a stopped node to a height, for Evolve to re-drive it from there
+#[derive(Debug, Parser)]
+pub struct RollbackCommand {
+    #[command(flatten)]
+    env: EnvironmentArgs<EthereumChainSpecParser>,
+
+    /// Height of the block to keep as the new head
+    #[arg(long, value_name = "HEIGHT")]
+    to: u64,
+
+    /// Unwind below the finalized block
+    #[arg(long)]
+    force: bool,
+}
+
+impl RollbackCommand {
+    /// Unwinds the blocks above the target along with their state, receipts, DA inclusions
+    /// and build attributes
+    pub fn execute(self) -> eyre::Result<()> {
+        let Environment {
+            provider_factory, ..
+        } = self.env.init::<RollkitNode>(AccessRights::RW)?;
+
+        let provider = provider_factory.database_provider_rw()?;
+        let head = provider.best_block_number()?;
+        if self.to >= head {
+            info!(
+                head,
+                "The chain is not above the target, nothing to roll back"
+            );
+            return Ok(());
+        }
+        let finalized = provider.last_finalized_block_number()?;
+        if let Some(finalized) = finalized.filter(|finalized| *finalized > self.to) {
+            if !self.force {
+                eyre::bail!(
+                    "target {} is below the finalized block {finalized}, pass --force to roll back anyway",
+                    self.to
+                );
+            }
+        }
+
+        provider.remove_block_and_execution_above(self.to, StorageLocation::Both)?;
+        // Safe and finalized blocks above the target are moved down to it
+        if finalized.is_some_and(|finalized| finalized > self.to) {
+            provider.save_finalized_block_number(self.to)?;
+        }
+        if provider
+            .last_safe_block_number()?
+            .is_some_and(|safe| safe > self.to)
+        {
+            provider.save_safe_block_number(self.to)?;
+        }
+        // The blocks were removed from the static files too, which are committed after the
+        // database so that they never lag behind it
+        UnifiedStorageWriter::commit_unwind(provider)?;
+
+        // DA inclusions and build attributes live in the Evolve tables of the same database
+        let db = provider_factory.db_ref().clone();
+        db.create_tables_for::<EvolveTables>()?;
+        let removed = DaInclusionStore::new(db.clone()).unwind_above(self.to)?;
+        let removed_build_attributes = BuildAttributesStore::new(db).unwind_above(self.to)?;
+
+        info!(
+            head = self.to,
+            previous_head = head,
+            removed_da_inclusions = removed,
+            removed_build_attributes,
+            "Rolled back the chain"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Evolve execution API.
+//!
+//! Serves ev-node's execution interface (`InitChain`, `GetTxs`, `ExecuteTxs`, `SetFinal`) as
+//! a Connect/gRPC-compatible service, so ev-node can drive ev-reth without an Engine API
+//! adapter.
+
+pub mod proto;
+pub mod server;
+pub mod service;
+
+pub use server::{router, serve};
+pub use service::{EvolveExecutionService, ExecutedBlock, ExecutionApiError};
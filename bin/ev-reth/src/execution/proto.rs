@@ -0,0 +1,228 @@
+//! Messages of ev-node's `evnode.v1.ExecutorService`.
+//!
+//! These mirror `proto/evnode/v1/execution.proto` from ev-node. They are encoded as binary
+//! protobuf with `prost`, or as protobuf JSON (base64 bytes, string `uint64`s, RFC 3339
+//! timestamps) with `serde`.
+
+use prost_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Fully qualified name of the executor service
+pub const EXECUTOR_SERVICE: &str = "evnode.v1.ExecutorService";
+
+/// Request of `InitChain`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InitChainRequest {
+    /// Genesis time of the chain
+    #[prost(message, optional, tag = "1")]
+    #[serde(with = "json::timestamp", alias = "genesis_time")]
+    pub genesis_time: Option<Timestamp>,
+    /// Height of the first block
+    #[prost(uint64, tag = "2")]
+    #[serde(with = "json::uint64", alias = "initial_height")]
+    pub initial_height: u64,
+    /// Chain identifier
+    #[prost(string, tag = "3")]
+    #[serde(alias = "chain_id")]
+    pub chain_id: String,
+}
+
+/// Response of `InitChain`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InitChainResponse {
+    /// State root of the genesis block
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "json::bytes", alias = "state_root")]
+    pub state_root: Vec<u8>,
+    /// Maximum size of the next block, as reported to ev-node
+    #[prost(uint64, tag = "2")]
+    #[serde(with = "json::uint64", alias = "max_bytes")]
+    pub max_bytes: u64,
+}
+
+/// Request of `GetTxs`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+pub struct GetTxsRequest {}
+
+/// Response of `GetTxs`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GetTxsResponse {
+    /// RLP-encoded transactions from the pool
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    #[serde(with = "json::repeated_bytes")]
+    pub txs: Vec<Vec<u8>>,
+}
+
+/// Request of `ExecuteTxs`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExecuteTxsRequest {
+    /// RLP-encoded transactions to execute
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    #[serde(with = "json::repeated_bytes")]
+    pub txs: Vec<Vec<u8>>,
+    /// Height of the block to produce
+    #[prost(uint64, tag = "2")]
+    #[serde(with = "json::uint64", alias = "block_height")]
+    pub block_height: u64,
+    /// Timestamp of the block to produce
+    #[prost(message, optional, tag = "3")]
+    #[serde(with = "json::timestamp")]
+    pub timestamp: Option<Timestamp>,
+    /// State root of the parent block
+    #[prost(bytes = "vec", tag = "4")]
+    #[serde(with = "json::bytes", alias = "prev_state_root")]
+    pub prev_state_root: Vec<u8>,
+}
+
+/// Response of `ExecuteTxs`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExecuteTxsResponse {
+    /// State root after executing the block
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(with = "json::bytes", alias = "updated_state_root")]
+    pub updated_state_root: Vec<u8>,
+    /// Maximum size of the next block, as reported to ev-node
+    #[prost(uint64, tag = "2")]
+    #[serde(with = "json::uint64", alias = "max_bytes")]
+    pub max_bytes: u64,
+}
+
+/// Request of `SetFinal`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SetFinalRequest {
+    /// Height of the block to finalize
+    #[prost(uint64, tag = "1")]
+    #[serde(with = "json::uint64", alias = "block_height")]
+    pub block_height: u64,
+}
+
+/// Response of `SetFinal`
+#[derive(Clone, PartialEq, Eq, prost::Message, Serialize, Deserialize)]
+pub struct SetFinalResponse {}
+
+/// Returns the seconds of a protobuf timestamp, rejecting pre-epoch times
+pub(crate) fn timestamp_secs(timestamp: Option<&Timestamp>) -> Option<u64> {
+    timestamp.and_then(|timestamp| u64::try_from(timestamp.seconds).ok())
+}
+
+/// Returns the milliseconds of a protobuf timestamp, rejecting pre-epoch times
+pub(crate) fn timestamp_millis(timestamp: Option<&Timestamp>) -> Option<u64> {
+    let timestamp = timestamp?;
+    let seconds = u64::try_from(timestamp.seconds).ok()?;
+    let millis = u64::try_from(timestamp.nanos).ok()? / 1_000_000;
+    seconds.checked_mul(1000)?.checked_add(millis)
+}
+
+/// Protobuf JSON encodings of scalar types
+mod json {
+    pub(super) mod uint64 {
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrNumber {
+            String(String),
+            Number(u64),
+        }
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &u64,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&value.to_string())
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<u64, D::Error> {
+            match StringOrNumber::deserialize(deserializer)? {
+                StringOrNumber::String(value) => value.parse().map_err(D::Error::custom),
+                StringOrNumber::Number(value) => Ok(value),
+            }
+        }
+    }
+
+    pub(super) mod bytes {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &[u8],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&STANDARD.encode(value))
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            STANDARD
+                .decode(String::deserialize(deserializer)?)
+                .map_err(D::Error::custom)
+        }
+    }
+
+    pub(super) mod repeated_bytes {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &[Vec<u8>],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(value.iter().map(|bytes| STANDARD.encode(bytes)))
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<Vec<u8>>, D::Error> {
+            Vec::<String>::deserialize(deserializer)?
+                .into_iter()
+                .map(|value| STANDARD.decode(value).map_err(D::Error::custom))
+                .collect()
+        }
+    }
+
+    pub(super) mod timestamp {
+        use chrono::{DateTime, SecondsFormat, Utc};
+        use prost_types::Timestamp;
+        use serde::{de::Error, ser::Error as _, Deserialize, Deserializer, Serializer};
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &Option<Timestamp>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(timestamp) => {
+                    let time = DateTime::<Utc>::from_timestamp(
+                        timestamp.seconds,
+                        timestamp.nanos.max(0) as u32,
+                    )
+                    .ok_or_else(|| S::Error::custom("timestamp out of range"))?;
+                    serializer.serialize_str(&time.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+                }
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Timestamp>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| {
+                    let time = DateTime::parse_from_rfc3339(&value).map_err(D::Error::custom)?;
+                    Ok(Timestamp {
+                        seconds: time.timestamp(),
+                        nanos: time.timestamp_subsec_nanos() as i32,
+                    })
+                })
+                .transpose()
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Connect protocol server for the Evolve execution API.
+//!
+//! Serves the unary methods of `evnode.v1.ExecutorService` over HTTP/1.1 and HTTP/2 (h2c)
+//! with both the binary (`application/proto`) and JSON (`application/json`) codecs, which
+//! is what ev-node's Connect client speaks.
+
+use alloy_primitives::{Bytes, B256};
+use axum::{
+    body::Bytes as Body,
+    extract::State,
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use prost::Message;
+use reth_ethereum::{pool::TransactionPool, primitives::Header, TransactionSigned};
+use reth_provider::{
+    BlockIdReader, BlockNumReader, CanonChainTracker, ChainSpecProvider, ChainStateBlockWriter,
+    DatabaseProviderFactory, HeaderProvider, TransactionsProvider,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use super::{
+    proto::{
+        timestamp_millis, timestamp_secs, ExecuteTxsRequest, ExecuteTxsResponse, GetTxsRequest,
+        GetTxsResponse, InitChainRequest, InitChainResponse, SetFinalRequest, SetFinalResponse,
+        EXECUTOR_SERVICE,
+    },
+    service::{EvolveExecutionService, ExecutionApiError},
+};
+
+const PROTO_CONTENT_TYPE: &str = "application/proto";
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Codec negotiated from the request content type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Proto,
+    Json,
+}
+
+impl Codec {
+    fn from_headers(headers: &HeaderMap) -> Result<Self, ConnectError> {
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        match media_type {
+            PROTO_CONTENT_TYPE | "application/protobuf" => Ok(Self::Proto),
+            JSON_CONTENT_TYPE => Ok(Self::Json),
+            _ => Err(ConnectError::unsupported_media_type(content_type)),
+        }
+    }
+
+    const fn content_type(self) -> &'static str {
+        match self {
+            Self::Proto => PROTO_CONTENT_TYPE,
+            Self::Json => JSON_CONTENT_TYPE,
+        }
+    }
+
+    fn decode<T: Message + DeserializeOwned + Default>(
+        self,
+        body: &[u8],
+    ) -> Result<T, ConnectError> {
+        match self {
+            Self::Proto => {
+                T::decode(body).map_err(|err| ConnectError::invalid_argument(err.to_string()))
+            }
+            // An empty JSON body is the empty message
+            Self::Json if body.is_empty() => Ok(T::default()),
+            Self::Json => serde_json::from_slice(body)
+                .map_err(|err| ConnectError::invalid_argument(err.to_string())),
+        }
+    }
+
+    fn encode<T: Message + Serialize>(self, message: &T) -> Response {
+        let body = match self {
+            Self::Proto => message.encode_to_vec(),
+            Self::Json => match serde_json::to_vec(message) {
+                Ok(body) => body,
+                Err(err) => return ConnectError::internal(err.to_string()).into_response(),
+            },
+        };
+        (
+            [(CONTENT_TYPE, HeaderValue::from_static(self.content_type()))],
+            body,
+        )
+            .into_response()
+    }
+}
+
+/// Error in the Connect protocol JSON error format
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConnectError {
+    code: &'static str,
+    status: StatusCode,
+    message: String,
+}
+
+impl ConnectError {
+    const fn new(code: &'static str, status: StatusCode, message: String) -> Self {
+        Self {
+            code,
+            status,
+            message,
+        }
+    }
+
+    const fn invalid_argument(message: String) -> Self {
+        Self::new("invalid_argument", StatusCode::BAD_REQUEST, message)
+    }
+
+    const fn internal(message: String) -> Self {
+        Self::new("internal", StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    fn unsupported_media_type(content_type: &str) -> Self {
+        Self::new(
+            "unknown",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("unsupported content type {content_type:?}"),
+        )
+    }
+}
+
+impl From<ExecutionApiError> for ConnectError {
+    fn from(err: ExecutionApiError) -> Self {
+        let message = err.to_string();
+        match err {
+            ExecutionApiError::InvalidArgument(_) => Self::invalid_argument(message),
+            ExecutionApiError::FailedPrecondition(_) => {
+                Self::new("failed_precondition", StatusCode::BAD_REQUEST, message)
+            }
+            ExecutionApiError::BlockNotFound(_) => {
+                Self::new("not_found", StatusCode::NOT_FOUND, message)
+            }
+            ExecutionApiError::Engine(_) | ExecutionApiError::Provider(_) => {
+                Self::internal(message)
+            }
+        }
+    }
+}
+
+impl IntoResponse for ConnectError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "code": self.code, "message": self.message });
+        (
+            self.status,
+            [(CONTENT_TYPE, HeaderValue::from_static(JSON_CONTENT_TYPE))],
+            body.to_string(),
+        )
+            .into_response()
+    }
+}
+
+/// Returns the router serving the Evolve execution API
+pub fn router<Provider, Pool>(service: EvolveExecutionService<Provider, Pool>) -> Router
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone
+        + 'static,
+    Pool: TransactionPool + 'static,
+{
+    Router::new()
+        .route(
+            &format!("/{EXECUTOR_SERVICE}/InitChain"),
+            post(init_chain::<Provider, Pool>),
+        )
+        .route(
+            &format!("/{EXECUTOR_SERVICE}/GetTxs"),
+            post(get_txs::<Provider, Pool>),
+        )
+        .route(
+            &format!("/{EXECUTOR_SERVICE}/ExecuteTxs"),
+            post(execute_txs::<Provider, Pool>),
+        )
+        .route(
+            &format!("/{EXECUTOR_SERVICE}/SetFinal"),
+            post(set_final::<Provider, Pool>),
+        )
+        .with_state(service)
+}
+
+/// Serves the Evolve execution API on `addr` until the listener fails
+pub async fn serve<Provider, Pool>(
+    addr: SocketAddr,
+    service: EvolveExecutionService<Provider, Pool>,
+) -> std::io::Result<()>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone
+        + 'static,
+    Pool: TransactionPool + 'static,
+{
+    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "Evolve execution API listening on {}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, router(service)).await
+}
+
+async fn init_chain<Provider, Pool>(
+    State(service): State<EvolveExecutionService<Provider, Pool>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ConnectError>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    let codec = Codec::from_headers(&headers)?;
+    let request: InitChainRequest = codec.decode(&body)?;
+
+    let genesis_time = request
+        .genesis_time
+        .as_ref()
+        .map(|genesis_time| {
+            timestamp_secs(Some(genesis_time))
+                .ok_or_else(|| ConnectError::invalid_argument("invalid genesis time".into()))
+        })
+        .transpose()?;
+    let (state_root, max_bytes) =
+        service.init_chain(request.initial_height, &request.chain_id, genesis_time)?;
+    Ok(codec.encode(&InitChainResponse {
+        state_root: state_root.to_vec(),
+        max_bytes,
+    }))
+}
+
+async fn get_txs<Provider, Pool>(
+    State(service): State<EvolveExecutionService<Provider, Pool>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ConnectError>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    let codec = Codec::from_headers(&headers)?;
+    let GetTxsRequest {} = codec.decode(&body)?;
+
+    let txs = service.get_txs().into_iter().map(Vec::from).collect();
+    Ok(codec.encode(&GetTxsResponse { txs }))
+}
+
+async fn execute_txs<Provider, Pool>(
+    State(service): State<EvolveExecutionService<Provider, Pool>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ConnectError>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    let codec = Codec::from_headers(&headers)?;
+    let request: ExecuteTxsRequest = codec.decode(&body)?;
+
+    let timestamp_millis = timestamp_millis(request.timestamp.as_ref())
+        .ok_or_else(|| ConnectError::invalid_argument("missing or invalid timestamp".into()))?;
+    if request.prev_state_root.len() != B256::len_bytes() {
+        return Err(ConnectError::invalid_argument(format!(
+            "prev_state_root must be {} bytes, got {}",
+            B256::len_bytes(),
+            request.prev_state_root.len()
+        )));
+    }
+    let prev_state_root = B256::from_slice(&request.prev_state_root);
+    let transactions = request.txs.into_iter().map(Bytes::from).collect();
+
+    let executed = service
+        .execute_txs(
+            transactions,
+            request.block_height,
+            timestamp_millis,
+            prev_state_root,
+        )
+        .await
+        .inspect_err(|err| warn!(block_height = request.block_height, %err, "ExecuteTxs failed"))?;
+    Ok(codec.encode(&ExecuteTxsResponse {
+        updated_state_root: executed.state_root.to_vec(),
+        max_bytes: service.max_bytes(),
+    }))
+}
+
+async fn set_final<Provider, Pool>(
+    State(service): State<EvolveExecutionService<Provider, Pool>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ConnectError>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    let codec = Codec::from_headers(&headers)?;
+    let request: SetFinalRequest = codec.decode(&body)?;
+
+    service.set_final(request.block_height).await?;
+    Ok(codec.encode(&SetFinalResponse {}))
+}
@@ -0,0 +1,472 @@
+//! Execution service backing the Evolve execution API.
+//!
+//! Every call is translated into Engine API messages sent directly to the engine tree, so
+//! blocks produced here go through the same payload builder, validation and persistence as
+//! blocks produced over the authenticated Engine API.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rpc_types::engine::{ForkchoiceState, PayloadAttributes as EthPayloadAttributes};
+use evolve_ev_reth::{
+    chain_config::validate_timestamp_millis,
+    rpc::{select_transactions, EvolveFinalityApiImpl, FinalityError},
+    EvolveExtraData,
+};
+use reth_chainspec::EthChainSpec;
+use reth_ethereum::{
+    pool::TransactionPool,
+    primitives::{Header, SealedBlock},
+    TransactionSigned,
+};
+use reth_node_api::{
+    BeaconConsensusEngineHandle, EngineApiMessageVersion, PayloadKind, PayloadTypes,
+};
+use reth_payload_builder::PayloadBuilderHandle;
+use reth_primitives_traits::{SealedHeader, SignedTransaction};
+use reth_provider::{
+    BlockIdReader, BlockNumReader, CanonChainTracker, ChainSpecProvider, ChainStateBlockWriter,
+    DatabaseProviderFactory, HeaderProvider, TransactionsProvider,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::{attributes::RollkitEnginePayloadAttributes, RollkitEngineTypes};
+
+/// Height of the first block produced on top of genesis, the only height ev-node may start at
+pub const INITIAL_HEIGHT: u64 = 1;
+
+/// Evolve execution service driving the engine tree
+#[derive(Debug)]
+pub struct EvolveExecutionService<Provider, Pool> {
+    inner: Arc<EvolveExecutionServiceInner<Provider, Pool>>,
+}
+
+impl<Provider, Pool> Clone for EvolveExecutionService<Provider, Pool> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EvolveExecutionServiceInner<Provider, Pool> {
+    provider: Provider,
+    pool: Pool,
+    engine: BeaconConsensusEngineHandle<RollkitEngineTypes>,
+    payload_builder: PayloadBuilderHandle<RollkitEngineTypes>,
+    finality: EvolveFinalityApiImpl<Provider>,
+    fee_recipient: Address,
+    max_txpool_bytes: u64,
+    /// Base fee selected transactions must pay, the pool's pending base fee when unset
+    base_fee: Option<u64>,
+    /// Serializes calls that move the fork choice
+    lock: Mutex<()>,
+}
+
+/// Result of executing a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutedBlock {
+    /// Hash of the block
+    pub hash: B256,
+    /// State root after executing the block
+    pub state_root: B256,
+    /// Gas used by the block
+    pub gas_used: u64,
+}
+
+impl<Provider, Pool> EvolveExecutionService<Provider, Pool>
+where
+    Provider: HeaderProvider<Header = Header>
+        + TransactionsProvider<Transaction = TransactionSigned>
+        + BlockIdReader
+        + BlockNumReader
+        + ChainSpecProvider
+        + CanonChainTracker<Header = Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + Clone,
+    Pool: TransactionPool,
+{
+    /// Creates a new execution service
+    pub fn new(
+        provider: Provider,
+        pool: Pool,
+        engine: BeaconConsensusEngineHandle<RollkitEngineTypes>,
+        payload_builder: PayloadBuilderHandle<RollkitEngineTypes>,
+        fee_recipient: Address,
+        max_txpool_bytes: u64,
+        base_fee: Option<u64>,
+    ) -> Self {
+        let finality = EvolveFinalityApiImpl::new(provider.clone());
+        Self {
+            inner: Arc::new(EvolveExecutionServiceInner {
+                provider,
+                pool,
+                engine,
+                payload_builder,
+                finality,
+                fee_recipient,
+                max_txpool_bytes,
+                base_fee,
+                lock: Mutex::new(()),
+            }),
+        }
+    }
+
+    /// Returns the maximum encoded size of the transactions ev-node may pass to a block,
+    /// reported to it as `max_bytes`
+    pub fn max_bytes(&self) -> u64 {
+        self.inner.max_txpool_bytes
+    }
+
+    /// Returns the genesis state root and the maximum encoded size of a block's transactions.
+    ///
+    /// `chain_id` must be the chain ID of the chain spec, in decimal, and `genesis_time`, in
+    /// seconds, the timestamp of the genesis block when given.
+    pub fn init_chain(
+        &self,
+        initial_height: u64,
+        chain_id: &str,
+        genesis_time: Option<u64>,
+    ) -> Result<(B256, u64), ExecutionApiError> {
+        if initial_height != INITIAL_HEIGHT {
+            return Err(ExecutionApiError::InvalidArgument(format!(
+                "initial height must be {INITIAL_HEIGHT}, got {initial_height}"
+            )));
+        }
+        let expected_chain_id = self.inner.provider.chain_spec().chain_id();
+        if chain_id.parse::<u64>().ok() != Some(expected_chain_id) {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "chain ID mismatch: expected {expected_chain_id}, got {chain_id:?}"
+            )));
+        }
+
+        let genesis = self.sealed_header(0)?;
+        if let Some(genesis_time) = genesis_time.filter(|time| *time != genesis.timestamp) {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "genesis time mismatch: expected {}, got {genesis_time}",
+                genesis.timestamp
+            )));
+        }
+        info!(
+            genesis_hash = %genesis.hash(),
+            state_root = %genesis.state_root,
+            "Evolve execution API: chain initialized"
+        );
+        Ok((genesis.state_root, self.max_bytes()))
+    }
+
+    /// Returns the best pending transactions, RLP-encoded, up to the txpool byte limit
+    pub fn get_txs(&self) -> Vec<Bytes> {
+        let txs = select_transactions(
+            &self.inner.pool,
+            self.inner.max_txpool_bytes,
+            self.inner.base_fee,
+        );
+        debug!("Evolve execution API: returning {} transactions", txs.len());
+        txs
+    }
+
+    /// Builds, imports and makes canonical the block at `block_height`.
+    ///
+    /// If the block already exists it is returned unchanged, so ev-node can safely retry, as
+    /// long as the retry carries the block's timestamp and every transaction the block
+    /// included, in order. Transactions the builder skipped may be repeated.
+    pub async fn execute_txs(
+        &self,
+        transactions: Vec<Bytes>,
+        block_height: u64,
+        timestamp_millis: u64,
+        prev_state_root: B256,
+    ) -> Result<ExecutedBlock, ExecutionApiError> {
+        if block_height < INITIAL_HEIGHT {
+            return Err(ExecutionApiError::InvalidArgument(format!(
+                "block height must be at least {INITIAL_HEIGHT}"
+            )));
+        }
+
+        let _guard = self.inner.lock.lock().await;
+
+        if let Some(existing) = self.inner.provider.sealed_header(block_height)? {
+            debug!(block_height, hash = %existing.hash(), "Evolve execution API: block already executed");
+            self.ensure_replay_matches(&existing, &transactions, timestamp_millis)?;
+            return Ok(ExecutedBlock {
+                hash: existing.hash(),
+                state_root: existing.state_root,
+                gas_used: existing.gas_used,
+            });
+        }
+
+        let parent = self.sealed_header(block_height - 1)?;
+        if parent.state_root != prev_state_root {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "previous state root mismatch at height {}: expected {}, got {prev_state_root}",
+                block_height - 1,
+                parent.state_root
+            )));
+        }
+        // Several blocks may be produced per second, so order them by millisecond timestamp
+        let timestamp = timestamp_millis / 1000;
+        validate_timestamp_millis(timestamp, Some(timestamp_millis), parent.header())
+            .map_err(|err| ExecutionApiError::InvalidArgument(err.to_string()))?;
+
+        let attributes = RollkitEnginePayloadAttributes {
+            inner: EthPayloadAttributes {
+                timestamp,
+                prev_randao: B256::from(U256::from(block_height)),
+                suggested_fee_recipient: self.inner.fee_recipient,
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: Some(B256::ZERO),
+            },
+            transactions: Some(transactions),
+            gas_limit: Some(parent.gas_limit),
+            da_info: None,
+            timestamp_millis: Some(timestamp_millis),
+        };
+
+        let state = self.forkchoice_state(parent.hash())?;
+        let updated = self
+            .inner
+            .engine
+            .fork_choice_updated(state, Some(attributes), EngineApiMessageVersion::default())
+            .await
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        if !updated.payload_status.is_valid() {
+            return Err(ExecutionApiError::Engine(format!(
+                "forkchoice update to {} rejected: {:?}",
+                parent.hash(),
+                updated.payload_status.status
+            )));
+        }
+        let payload_id = updated.payload_id.ok_or_else(|| {
+            ExecutionApiError::Engine("forkchoice update did not start a payload job".to_string())
+        })?;
+
+        let built = self
+            .inner
+            .payload_builder
+            .resolve_kind(payload_id, PayloadKind::WaitForPending)
+            .await
+            .ok_or_else(|| ExecutionApiError::Engine(format!("unknown payload {payload_id}")))?
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        let block = SealedBlock::clone(built.block());
+        let executed = ExecutedBlock {
+            hash: block.hash(),
+            state_root: block.state_root,
+            gas_used: block.gas_used,
+        };
+
+        let status = self
+            .inner
+            .engine
+            .new_payload(RollkitEngineTypes::block_to_payload(block))
+            .await
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        if !status.is_valid() {
+            return Err(ExecutionApiError::Engine(format!(
+                "payload {} rejected: {:?}",
+                executed.hash, status.status
+            )));
+        }
+
+        let state = self.forkchoice_state(executed.hash)?;
+        let updated = self
+            .inner
+            .engine
+            .fork_choice_updated(state, None, EngineApiMessageVersion::default())
+            .await
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        if !updated.payload_status.is_valid() {
+            return Err(ExecutionApiError::Engine(format!(
+                "forkchoice update to {} rejected: {:?}",
+                executed.hash, updated.payload_status.status
+            )));
+        }
+
+        info!(
+            block_height,
+            hash = %executed.hash,
+            gas_used = executed.gas_used,
+            "Evolve execution API: block executed"
+        );
+        Ok(executed)
+    }
+
+    /// Imports a block produced elsewhere and makes it canonical, as a follower receiving it
+    /// over the Engine API would.
+    ///
+    /// The block goes through `RollkitConsensus` and execution unchanged, signature and fee
+    /// recipient included. If a block already exists at its height it must be the same block.
+    pub async fn import_block(&self, block: SealedBlock) -> Result<B256, ExecutionApiError> {
+        let _guard = self.inner.lock.lock().await;
+
+        let (number, hash) = (block.number, block.hash());
+        if let Some(existing) = self.inner.provider.sealed_header(number)? {
+            if existing.hash() != hash {
+                return Err(ExecutionApiError::FailedPrecondition(format!(
+                    "block {number} is {}, not the imported block {hash}",
+                    existing.hash()
+                )));
+            }
+            debug!(number, %hash, "Evolve execution API: block already imported");
+            return Ok(hash);
+        }
+        let parent = self.sealed_header(number.saturating_sub(1))?;
+        if parent.hash() != block.parent_hash {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "block {number} builds on {}, not on the canonical block {}",
+                block.parent_hash,
+                parent.hash()
+            )));
+        }
+
+        let status = self
+            .inner
+            .engine
+            .new_payload(RollkitEngineTypes::block_to_payload(block))
+            .await
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        if !status.is_valid() {
+            return Err(ExecutionApiError::Engine(format!(
+                "payload {hash} rejected: {:?}",
+                status.status
+            )));
+        }
+
+        let state = self.forkchoice_state(hash)?;
+        let updated = self
+            .inner
+            .engine
+            .fork_choice_updated(state, None, EngineApiMessageVersion::default())
+            .await
+            .map_err(|err| ExecutionApiError::Engine(err.to_string()))?;
+        if !updated.payload_status.is_valid() {
+            return Err(ExecutionApiError::Engine(format!(
+                "forkchoice update to {hash} rejected: {:?}",
+                updated.payload_status.status
+            )));
+        }
+
+        info!(number, %hash, "Evolve execution API: block imported");
+        Ok(hash)
+    }
+
+    /// Marks the block at `block_height` as safe and finalized.
+    ///
+    /// Heights at or below the current finalized block are accepted as no-ops, so ev-node can
+    /// replay `SetFinal` after a restart.
+    pub async fn set_final(&self, block_height: u64) -> Result<(), ExecutionApiError> {
+        let _guard = self.inner.lock.lock().await;
+
+        let status = self.inner.finality.status()?;
+        if status
+            .finalized
+            .is_some_and(|finalized| block_height <= finalized.number)
+        {
+            return Ok(());
+        }
+
+        // Only move the safe block forward, it may already be ahead of the finalized block
+        let safe = status
+            .safe
+            .is_none_or(|safe| safe.number < block_height)
+            .then_some(block_height);
+        self.inner.finality.update(safe, Some(block_height)).await?;
+        debug!(block_height, "Evolve execution API: block finalized");
+        Ok(())
+    }
+
+    /// Checks that re-executing the existing block `existing` with `transactions` at
+    /// `timestamp_millis` would build it again
+    fn ensure_replay_matches(
+        &self,
+        existing: &SealedHeader,
+        transactions: &[Bytes],
+        timestamp_millis: u64,
+    ) -> Result<(), ExecutionApiError> {
+        let existing_millis = EvolveExtraData::timestamp_millis_of(existing.header());
+        if existing_millis != timestamp_millis {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "block {} was executed at timestamp {existing_millis}ms, got {timestamp_millis}ms",
+                existing.number
+            )));
+        }
+
+        let included = self
+            .inner
+            .provider
+            .transactions_by_block(existing.number.into())?
+            .ok_or(ExecutionApiError::BlockNotFound(existing.number))?;
+        // The block holds the requested transactions in order, minus the ones skipped
+        let mut requested = transactions.iter().map(keccak256);
+        if let Some(missing) = included
+            .iter()
+            .map(|tx| *tx.tx_hash())
+            .find(|hash| !requested.any(|requested| requested == *hash))
+        {
+            return Err(ExecutionApiError::FailedPrecondition(format!(
+                "block {} includes transaction {missing}, which is missing from the request or \
+                 out of order",
+                existing.number
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns a fork choice state with the given head, keeping the current safe and
+    /// finalized blocks
+    fn forkchoice_state(&self, head: B256) -> Result<ForkchoiceState, ExecutionApiError> {
+        Ok(ForkchoiceState {
+            head_block_hash: head,
+            safe_block_hash: self.inner.provider.safe_block_hash()?.unwrap_or_default(),
+            finalized_block_hash: self
+                .inner
+                .provider
+                .finalized_block_hash()?
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Returns the block header at the given height
+    fn sealed_header(&self, number: u64) -> Result<SealedHeader, ExecutionApiError> {
+        self.inner
+            .provider
+            .sealed_header(number)?
+            .ok_or(ExecutionApiError::BlockNotFound(number))
+    }
+}
+
+/// Errors returned by the Evolve execution API
+#[derive(Debug, thiserror::Error)]
+pub enum ExecutionApiError {
+    /// The request is malformed
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    /// The request does not match the current chain
+    #[error("Failed precondition: {0}")]
+    FailedPrecondition(String),
+    /// No block exists at the requested height
+    #[error("Block {0} not found")]
+    BlockNotFound(u64),
+    /// The engine rejected or failed to process a message
+    #[error("Engine error: {0}")]
+    Engine(String),
+    /// Reading from the database failed
+    #[error("Provider error: {0}")]
+    Provider(#[from] reth_provider::ProviderError),
+}
+
+impl From<FinalityError> for ExecutionApiError {
+    fn from(err: FinalityError) -> Self {
+        match err {
+            FinalityError::UnknownBlock(number) => Self::BlockNotFound(number),
+            FinalityError::Provider(err) => Self::Provider(err),
+            err @ (FinalityError::AheadOfHead { .. }
+            | FinalityError::NotCanonical(_)
+            | FinalityError::Backwards { .. }
+            | FinalityError::FinalizedAfterSafe { .. }) => {
+                Self::FailedPrecondition(err.to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! ev-reth node library.
+//!
+//! Holds the Rollkit node type, its engine types and components, and the ev-reth specific
+//! subcommands, so that the node can be launched from the `ev-reth` binary as well as
+//! in-process by tests.
+
+#![allow(missing_docs, rustdoc::missing_crate_level_docs)]
+
+pub mod attributes;
+pub mod builder;
+pub mod commands;
+pub mod error;
+pub mod execution;
+pub mod validator;
+
+use alloy_rpc_types::engine::{
+    ExecutionData, ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3,
+    ExecutionPayloadEnvelopeV4, ExecutionPayloadEnvelopeV5, ExecutionPayloadV1,
+};
+use ev_node::{RollkitExecutorBuilder, RollkitPoolBuilder};
+use evolve_ev_reth::{
+    consensus::RollkitConsensusBuilder, BuildAttributesRecorder, Preconfirmations,
+};
+use reth_ethereum::{
+    chainspec::ChainSpec,
+    node::{
+        api::{EngineTypes, FullNodeTypes, NodeTypes, PayloadTypes},
+        builder::{
+            components::{BasicPayloadServiceBuilder, ComponentsBuilder},
+            rpc::RpcAddOns,
+            Node, NodeAdapter, NodeComponentsBuilder,
+        },
+        node::EthereumNetworkBuilder,
+        EthereumEthApiBuilder,
+    },
+    primitives::SealedBlock,
+};
+use reth_node_core::args::EngineArgs;
+use reth_payload_builder::EthBuiltPayload;
+use reth_trie_db::MerklePatriciaTrie;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes},
+    builder::{RollkitArgs, RollkitPayloadBuilderBuilder},
+    validator::RollkitEngineValidatorBuilder,
+};
+
+/// Rollkit engine types - uses custom payload attributes that support transactions
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RollkitEngineTypes;
+
+impl PayloadTypes for RollkitEngineTypes {
+    type ExecutionData = ExecutionData;
+    type BuiltPayload = EthBuiltPayload;
+    type PayloadAttributes = RollkitEnginePayloadAttributes;
+    type PayloadBuilderAttributes = RollkitEnginePayloadBuilderAttributes;
+
+    fn block_to_payload(
+        block: SealedBlock<
+            <<Self::BuiltPayload as reth_ethereum::node::api::BuiltPayload>::Primitives as reth_ethereum::node::api::NodePrimitives>::Block,
+        >,
+    ) -> ExecutionData {
+        let (payload, sidecar) =
+            reth_ethereum::rpc::types::engine::ExecutionPayload::from_block_unchecked(
+                block.hash(),
+                &block.into_block(),
+            );
+        ExecutionData { payload, sidecar }
+    }
+}
+
+impl EngineTypes for RollkitEngineTypes {
+    type ExecutionPayloadEnvelopeV1 = ExecutionPayloadV1;
+    type ExecutionPayloadEnvelopeV2 = ExecutionPayloadEnvelopeV2;
+    type ExecutionPayloadEnvelopeV3 = ExecutionPayloadEnvelopeV3;
+    type ExecutionPayloadEnvelopeV4 = ExecutionPayloadEnvelopeV4;
+    type ExecutionPayloadEnvelopeV5 = ExecutionPayloadEnvelopeV5;
+}
+
+/// Rollkit node type
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct RollkitNode {
+    /// Rollkit-specific arguments
+    pub args: RollkitArgs,
+    /// Preconfirmations published by the payload builder
+    pub preconfirmations: Preconfirmations,
+    /// Recorder of the attributes of built blocks, if enabled
+    pub build_attributes: Option<BuildAttributesRecorder>,
+}
+
+impl RollkitNode {
+    /// Create a new rollkit node with the given arguments
+    pub fn new(args: RollkitArgs) -> Self {
+        Self {
+            args,
+            preconfirmations: Preconfirmations::default(),
+            build_attributes: None,
+        }
+    }
+
+    /// Records the attributes of every block the payload builder builds
+    pub fn with_build_attributes(mut self, recorder: BuildAttributesRecorder) -> Self {
+        self.build_attributes = Some(recorder);
+        self
+    }
+}
+
+impl NodeTypes for RollkitNode {
+    type Primitives = reth_ethereum::EthPrimitives;
+    type ChainSpec = ChainSpec;
+    type StateCommitment = MerklePatriciaTrie;
+    type Storage = reth_ethereum::provider::EthStorage;
+    type Payload = RollkitEngineTypes;
+}
+
+/// Rollkit node addons configuring RPC types with custom engine validator
+pub type RollkitNodeAddOns<N> = RpcAddOns<N, EthereumEthApiBuilder, RollkitEngineValidatorBuilder>;
+
+impl<N> Node<N> for RollkitNode
+where
+    N: FullNodeTypes<
+        Types: NodeTypes<
+            Payload = RollkitEngineTypes,
+            ChainSpec = ChainSpec,
+            Primitives = reth_ethereum::EthPrimitives,
+            Storage = reth_ethereum::provider::EthStorage,
+        >,
+    >,
+{
+    type ComponentsBuilder = ComponentsBuilder<
+        N,
+        RollkitPoolBuilder,
+        BasicPayloadServiceBuilder<RollkitPayloadBuilderBuilder>,
+        EthereumNetworkBuilder,
+        RollkitExecutorBuilder,
+        RollkitConsensusBuilder,
+    >;
+    type AddOns = RollkitNodeAddOns<
+        NodeAdapter<N, <Self::ComponentsBuilder as NodeComponentsBuilder<N>>::Components>,
+    >;
+
+    fn components_builder(&self) -> Self::ComponentsBuilder {
+        ComponentsBuilder::default()
+            .node_types::<N>()
+            .pool(RollkitPoolBuilder::default().with_min_gas_price(self.args.min_gas_price))
+            .executor(RollkitExecutorBuilder::default())
+            .payload(BasicPayloadServiceBuilder::new(
+                RollkitPayloadBuilderBuilder::new(&self.args)
+                    .with_preconfirmations(self.preconfirmations.clone())
+                    .with_build_attributes(self.build_attributes.clone()),
+            ))
+            .network(EthereumNetworkBuilder::default())
+            .consensus(RollkitConsensusBuilder::default())
+    }
+
+    fn add_ons(&self) -> Self::AddOns {
+        RollkitNodeAddOns::default()
+    }
+}
+
+/// Configures the engine the way Evolve drives it.
+///
+/// A forkchoice update to an ancestor of the canonical head makes the ancestor the head,
+/// discarding the blocks above it, and payload attributes sent with it are built on top of
+/// the ancestor. Evolve relies on this to rewind the chain after a failed DA submission and
+/// to roll it back.
+pub fn configure_engine(engine: &mut EngineArgs) {
+    engine.always_process_payload_attributes_on_canonical_head = true;
+    engine.allow_unwind_canonical_header = true;
+}
@@ -5,149 +5,43 @@
 
 #![allow(missing_docs, rustdoc::missing_crate_level_docs)]
 
-pub mod attributes;
-pub mod builder;
-pub mod error;
-pub mod validator;
-
-use alloy_rpc_types::engine::{
-    ExecutionData, ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3,
-    ExecutionPayloadEnvelopeV4, ExecutionPayloadEnvelopeV5, ExecutionPayloadV1,
-};
 use clap::Parser;
+use ev_node::{
+    rollkit_cli_components, rpc::EvolveSimulateApiServer, EvolveSimulateApiImpl, RollkitEvmConfig,
+    RollkitPayloadBuilder,
+};
+use ev_reth::{
+    builder::RollkitArgs,
+    commands::{self, EvRethCommand},
+    configure_engine,
+    execution::{self, EvolveExecutionService},
+    RollkitNode,
+};
 use evolve_ev_reth::{
     config::RollkitConfig,
-    consensus::RollkitConsensusBuilder,
-    rpc::txpool::{RollkitTxpoolApiImpl, RollkitTxpoolApiServer},
-};
-use reth_ethereum::{
-    chainspec::ChainSpec,
-    node::{
-        api::{EngineTypes, FullNodeTypes, NodeTypes, PayloadTypes},
-        builder::{
-            components::{BasicPayloadServiceBuilder, ComponentsBuilder},
-            rpc::RpcAddOns,
-            Node, NodeAdapter, NodeComponentsBuilder,
+    da_inclusion::{DaInclusionStore, EvolveTables},
+    rpc::{
+        block_time::{EvolveBlockTimeApiImpl, EvolveBlockTimeApiServer},
+        build_attributes::{EvolveBuildAttributesApiImpl, EvolveBuildAttributesApiServer},
+        da_inclusion::{
+            EvolveDaInclusionAdminApiServer, EvolveDaInclusionApiImpl, EvolveDaInclusionApiServer,
         },
-        node::{EthereumExecutorBuilder, EthereumNetworkBuilder, EthereumPoolBuilder},
-        EthereumEthApiBuilder,
+        finality::{EvolveFinalityApiImpl, EvolveFinalityApiServer, EvolveFinalityStatusApiServer},
+        preconfirmation::{EvolvePreconfirmationApiImpl, EvolvePreconfirmationApiServer},
+        rollback::{EvolveRollbackApiImpl, EvolveRollbackApiServer},
+        txpool::{RollkitTxpoolApiImpl, RollkitTxpoolApiServer},
     },
-    primitives::SealedBlock,
+    BuildAttributesRecorder, BuildAttributesStore, EvolveChainConfig, RollkitConsensus,
 };
+use reth_chainspec::ChainSpec;
 use reth_ethereum_cli::{chainspec::EthereumChainSpecParser, Cli};
-use reth_payload_builder::EthBuiltPayload;
-use reth_trie_db::MerklePatriciaTrie;
-use serde::{Deserialize, Serialize};
-use tracing::info;
-
-use crate::{
-    attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes},
-    builder::{RollkitArgs, RollkitPayloadBuilderBuilder},
-    validator::RollkitEngineValidatorBuilder,
-};
+use reth_provider::CanonStateSubscriptions;
+use std::sync::Arc;
+use tracing::{error, info};
 
 #[global_allocator]
 static ALLOC: reth_cli_util::allocator::Allocator = reth_cli_util::allocator::new_allocator();
 
-/// Rollkit engine types - uses custom payload attributes that support transactions
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
-#[non_exhaustive]
-pub struct RollkitEngineTypes;
-
-impl PayloadTypes for RollkitEngineTypes {
-    type ExecutionData = ExecutionData;
-    type BuiltPayload = EthBuiltPayload;
-    type PayloadAttributes = RollkitEnginePayloadAttributes;
-    type PayloadBuilderAttributes = RollkitEnginePayloadBuilderAttributes;
-
-    fn block_to_payload(
-        block: SealedBlock<
-            <<Self::BuiltPayload as reth_ethereum::node::api::BuiltPayload>::Primitives as reth_ethereum::node::api::NodePrimitives>::Block,
-        >,
-    ) -> ExecutionData {
-        let (payload, sidecar) =
-            reth_ethereum::rpc::types::engine::ExecutionPayload::from_block_unchecked(
-                block.hash(),
-                &block.into_block(),
-            );
-        ExecutionData { payload, sidecar }
-    }
-}
-
-impl EngineTypes for RollkitEngineTypes {
-    type ExecutionPayloadEnvelopeV1 = ExecutionPayloadV1;
-    type ExecutionPayloadEnvelopeV2 = ExecutionPayloadEnvelopeV2;
-    type ExecutionPayloadEnvelopeV3 = ExecutionPayloadEnvelopeV3;
-    type ExecutionPayloadEnvelopeV4 = ExecutionPayloadEnvelopeV4;
-    type ExecutionPayloadEnvelopeV5 = ExecutionPayloadEnvelopeV5;
-}
-
-/// Rollkit node type
-#[derive(Debug, Clone, Default)]
-#[non_exhaustive]
-pub struct RollkitNode {
-    /// Rollkit-specific arguments
-    pub args: RollkitArgs,
-}
-
-impl RollkitNode {
-    /// Create a new rollkit node with the given arguments
-    pub const fn new(args: RollkitArgs) -> Self {
-        Self { args }
-    }
-}
-
-impl NodeTypes for RollkitNode {
-    type Primitives = reth_ethereum::EthPrimitives;
-    type ChainSpec = ChainSpec;
-    type StateCommitment = MerklePatriciaTrie;
-    type Storage = reth_ethereum::provider::EthStorage;
-    type Payload = RollkitEngineTypes;
-}
-
-/// Rollkit node addons configuring RPC types with custom engine validator
-pub type RollkitNodeAddOns<N> = RpcAddOns<N, EthereumEthApiBuilder, RollkitEngineValidatorBuilder>;
-
-impl<N> Node<N> for RollkitNode
-where
-    N: FullNodeTypes<
-        Types: NodeTypes<
-            Payload = RollkitEngineTypes,
-            ChainSpec = ChainSpec,
-            Primitives = reth_ethereum::EthPrimitives,
-            Storage = reth_ethereum::provider::EthStorage,
-        >,
-    >,
-{
-    type ComponentsBuilder = ComponentsBuilder<
-        N,
-        EthereumPoolBuilder,
-        BasicPayloadServiceBuilder<RollkitPayloadBuilderBuilder>,
-        EthereumNetworkBuilder,
-        EthereumExecutorBuilder,
-        RollkitConsensusBuilder,
-    >;
-    type AddOns = RollkitNodeAddOns<
-        NodeAdapter<N, <Self::ComponentsBuilder as NodeComponentsBuilder<N>>::Components>,
-    >;
-
-    fn components_builder(&self) -> Self::ComponentsBuilder {
-        ComponentsBuilder::default()
-            .node_types::<N>()
-            .pool(EthereumPoolBuilder::default())
-            .executor(EthereumExecutorBuilder::default())
-            .payload(BasicPayloadServiceBuilder::new(
-                RollkitPayloadBuilderBuilder::new(&self.args),
-            ))
-            .network(EthereumNetworkBuilder::default())
-            .consensus(RollkitConsensusBuilder::default())
-    }
-
-    fn add_ons(&self) -> Self::AddOns {
-        RollkitNodeAddOns::default()
-    }
-}
-
 fn main() {
     info!("=== EV-RETH NODE STARTING ===");
 
@@ -158,29 +52,202 @@ fn main() {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 
-    if let Err(err) = Cli::<EthereumChainSpecParser, RollkitArgs>::parse().run(
-        async move |builder, rollkit_args| {
+    // ev-reth specific subcommands are not known to the reth CLI
+    if let Some(command) = EvRethCommand::parse_from_env() {
+        if let Err(err) = command.execute() {
+            eprintln!("Error: {err:?}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // `ev-reth import-batches` runs the node, importing the batches before exiting
+    let args = commands::batches::node_args(std::env::args());
+    // Commands other than `node` that validate blocks, such as `import` and `stage run`, use
+    // the Rollkit EVM and consensus too
+    let cli = Cli::<EthereumChainSpecParser, RollkitArgs>::parse_from(args);
+    if let Err(err) = cli.run_with_components::<RollkitNode>(
+        cli_components,
+        async move |mut builder, rollkit_args| {
             info!("=== EV-RETH: Starting with args: {:?} ===", rollkit_args);
             info!("=== EV-RETH: EV-node mode enabled ===");
             info!("=== EV-RETH: Using custom payload builder with transaction support ===");
 
+            // Evolve rewinds and rolls the chain back with forkchoice updates to an ancestor
+            // of the head
+            configure_engine(&mut builder.config_mut().engine);
+
+            let execution_api_addr = rollkit_args.execution_api_listen_addr()?;
+            let execution_api_fee_recipient = rollkit_args.execution_api_fee_recipient;
+            let import_batches = rollkit_args
+                .import_batches
+                .clone()
+                .zip(rollkit_args.import_batches_headers.clone());
+            let import_batches_format = rollkit_args.import_batches_format;
+
+            // The pool follows EIP-1559 for its pending base fee, zero-fee chains select
+            // transactions against their pinned base fee instead
+            let chain_config = EvolveChainConfig::from_chain_spec(&builder.config().chain)?;
+            let selection_base_fee = chain_config.zero_fee.then_some(0);
+
+            // Select no more transactions than fit in a block
+            let payload_builder_config = rollkit_args.payload_builder_config(&chain_config)?;
+            let max_txpool_bytes =
+                RollkitConfig::for_max_block_bytes(payload_builder_config.max_block_bytes)
+                    .max_txpool_bytes;
+            let simulation_evm_config = RollkitEvmConfig::try_new(builder.config().chain.clone())?;
+
+            // Evolve tables live in the node's database next to the reth tables
+            builder.db().create_tables_for::<EvolveTables>()?;
+            let da_inclusions = DaInclusionStore::new(builder.db().clone());
+            let rpc_da_inclusions = da_inclusions.clone();
+            let rollback_da_inclusions = da_inclusions.clone();
+            let build_attributes = BuildAttributesStore::new(builder.db().clone());
+            let rpc_build_attributes = build_attributes.clone();
+            let rollback_build_attributes = build_attributes.clone();
+
+            // Recording is opt-in, the RPC serves whatever was recorded either way
+            let record_build_attributes = rollkit_args.record_build_attributes;
+            let mut node = RollkitNode::new(rollkit_args);
+            let build_attribute_records = if record_build_attributes {
+                let (recorder, records) = BuildAttributesRecorder::channel();
+                node = node.with_build_attributes(recorder);
+                Some(records)
+            } else {
+                None
+            };
+            let preconfirmations = node.preconfirmations.clone();
+            let sealed_preconfirmations = node.preconfirmations.clone();
+
             let handle = builder
-                .node(RollkitNode::new(rollkit_args))
+                .node(node)
                 .extend_rpc_modules(move |ctx| {
                     // Build custom txpool RPC
-                    let rollkit_txpool = RollkitTxpoolApiImpl::new(
-                        ctx.pool().clone(),
-                        RollkitConfig::default().max_txpool_bytes,
-                    );
+                    let rollkit_txpool =
+                        RollkitTxpoolApiImpl::new(ctx.pool().clone(), max_txpool_bytes)
+                            .with_base_fee(selection_base_fee);
 
                     // Merge into all enabled transports (HTTP / WS)
                     ctx.modules.merge_configured(rollkit_txpool.into_rpc())?;
+
+                    // Build the finality RPC moving the safe and finalized heads, only served
+                    // on the authenticated Engine API endpoint
+                    let evolve_finality = EvolveFinalityApiImpl::new(ctx.provider().clone());
+                    ctx.auth_module
+                        .merge_auth_methods(EvolveFinalityApiServer::into_rpc(evolve_finality))?;
+
+                    // Build the finality status RPC, served on every endpoint
+                    let evolve_finality_status = EvolveFinalityApiImpl::new(ctx.provider().clone());
+                    let evolve_finality_status =
+                        EvolveFinalityStatusApiServer::into_rpc(evolve_finality_status);
+                    ctx.auth_module
+                        .merge_auth_methods(evolve_finality_status.clone())?;
+                    ctx.modules.merge_configured(evolve_finality_status)?;
+
+                    // Build the rollback RPC, only served on the authenticated Engine API
+                    // endpoint
+                    let evolve_rollback = EvolveRollbackApiImpl::new(
+                        ctx.provider().clone(),
+                        ctx.beacon_engine_handle.clone(),
+                        rollback_da_inclusions,
+                        rollback_build_attributes,
+                    );
+                    ctx.auth_module
+                        .merge_auth_methods(evolve_rollback.into_rpc())?;
+
+                    // Build the DA inclusion RPC, recording inclusions only on the
+                    // authenticated Engine API endpoint
+                    let evolve_da_inclusion_admin = EvolveDaInclusionApiImpl::new(
+                        ctx.provider().clone(),
+                        rpc_da_inclusions.clone(),
+                    );
+                    ctx.auth_module.merge_auth_methods(
+                        EvolveDaInclusionAdminApiServer::into_rpc(evolve_da_inclusion_admin),
+                    )?;
+                    let evolve_da_inclusion =
+                        EvolveDaInclusionApiImpl::new(ctx.provider().clone(), rpc_da_inclusions);
+                    ctx.modules
+                        .merge_configured(EvolveDaInclusionApiServer::into_rpc(
+                            evolve_da_inclusion,
+                        ))?;
+
+                    // Build the block time RPC
+                    let evolve_block_time = EvolveBlockTimeApiImpl::new(ctx.provider().clone());
+                    ctx.modules.merge_configured(evolve_block_time.into_rpc())?;
+
+                    // Build the preconfirmation RPC, its subscription is served over WS
+                    let evolve_preconfirmation =
+                        EvolvePreconfirmationApiImpl::new(preconfirmations);
+                    ctx.modules
+                        .merge_configured(evolve_preconfirmation.into_rpc())?;
+
+                    // Build the build attributes RPC
+                    let evolve_build_attributes =
+                        EvolveBuildAttributesApiImpl::new(rpc_build_attributes);
+                    ctx.modules
+                        .merge_configured(evolve_build_attributes.into_rpc())?;
+
+                    // Build the batch simulation RPC on a payload builder of its own, which
+                    // neither signs blocks nor publishes preconfirmations
+                    let simulation_builder = RollkitPayloadBuilder::new(
+                        Arc::new(ctx.provider().clone()),
+                        simulation_evm_config,
+                    )
+                    .with_config(payload_builder_config);
+                    let evolve_simulate = EvolveSimulateApiImpl::new(Arc::new(simulation_builder));
+                    ctx.modules.merge_configured(evolve_simulate.into_rpc())?;
                     Ok(())
                 })
                 .launch()
                 .await?;
 
             info!("=== EV-RETH: Node launched successfully with ev-reth payload builder ===");
+
+            // Drop DA inclusions of blocks that get reorged out
+            handle.node.task_executor.spawn(
+                da_inclusions.unwind_on_reorgs(handle.node.provider.subscribe_to_canonical_state()),
+            );
+
+            // Drop preconfirmations once their blocks are sealed
+            handle.node.task_executor.spawn(
+                sealed_preconfirmations
+                    .drop_sealed(handle.node.provider.subscribe_to_canonical_state()),
+            );
+
+            if let Some(records) = build_attribute_records {
+                handle.node.task_executor.spawn(
+                    build_attributes
+                        .record(records, handle.node.provider.subscribe_to_canonical_state()),
+                );
+            }
+
+            let node = &handle.node;
+            let service = EvolveExecutionService::new(
+                node.provider.clone(),
+                node.pool.clone(),
+                node.add_ons_handle.beacon_engine_handle.clone(),
+                node.payload_builder_handle.clone(),
+                execution_api_fee_recipient,
+                max_txpool_bytes,
+                selection_base_fee,
+            );
+
+            // Batches are imported the way a follower would receive them, then the node exits
+            if let Some((path, headers)) = import_batches {
+                commands::batches::import_batches(&service, &path, &headers, import_batches_format)
+                    .await?;
+                return Ok(());
+            }
+
+            if let Some(addr) = execution_api_addr {
+                node.task_executor
+                    .spawn_critical("evolve execution api", async move {
+                        if let Err(err) = execution::serve(addr, service).await {
+                            error!(%err, "Evolve execution API server failed");
+                        }
+                    });
+            }
+
             handle.node_exit_future.await
         },
     ) {
@@ -188,3 +255,14 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+/// Components of the reth commands other than `node`.
+///
+/// reth takes them from an infallible function, so an invalid Evolve config in the chain spec
+/// ends the command with its error instead.
+fn cli_components(chain_spec: Arc<ChainSpec>) -> (RollkitEvmConfig, RollkitConsensus) {
+    rollkit_cli_components(chain_spec).unwrap_or_else(|err| {
+        eprintln!("Error: invalid Evolve chain config: {err}");
+        std::process::exit(1);
+    })
+}
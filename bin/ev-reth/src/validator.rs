@@ -1,6 +1,10 @@
 #![allow(missing_docs, rustdoc::missing_crate_level_docs)]
 
 use alloy_rpc_types::engine::ExecutionData;
+use evolve_ev_reth::{
+    chain_config::validate_timestamp_millis, EvolveChainConfig, EvolveChainConfigError,
+    EvolveExtraData,
+};
 
 use reth_ethereum::{
     chainspec::ChainSpec,
@@ -25,14 +29,17 @@ use crate::{attributes::RollkitEnginePayloadAttributes, RollkitEngineTypes};
 #[derive(Debug, Clone)]
 pub struct RollkitEngineValidator {
     inner: EthereumExecutionPayloadValidator<ChainSpec>,
+    config: EvolveChainConfig,
 }
 
 impl RollkitEngineValidator {
-    /// Instantiates a new validator.
-    pub const fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        Self {
+    /// Instantiates a new validator, reading the Evolve parameters of the chain spec.
+    pub fn try_new(chain_spec: Arc<ChainSpec>) -> Result<Self, EvolveChainConfigError> {
+        let config = EvolveChainConfig::from_chain_spec(&chain_spec)?;
+        Ok(Self {
             inner: EthereumExecutionPayloadValidator::new(chain_spec),
-        }
+            config,
+        })
     }
 
     /// Returns the chain spec used by the validator.
@@ -74,6 +81,25 @@ impl PayloadValidator for RollkitEngineValidator {
                     sealed_block
                         .try_recover()
                         .map_err(|e| NewPayloadError::Other(e.into()))
+                } else if matches!(err, alloy_rpc_types::engine::PayloadError::ExtraData(_))
+                    && EvolveExtraData::decode(&payload.payload.as_v1().extra_data).is_some()
+                {
+                    info!("Rollkit engine validator: accepting Evolve header extension");
+                    // The Evolve header extension exceeds Ethereum's extra data cap, so decode the
+                    // block without it and restore it afterwards
+                    use reth_primitives_traits::Block;
+                    let ExecutionData {
+                        mut payload,
+                        sidecar,
+                    } = payload;
+                    let extra_data = std::mem::take(&mut payload.as_v1_mut().extra_data);
+                    let mut block: reth_ethereum::Block =
+                        payload.try_into_block_with_sidecar(&sidecar)?;
+                    block.header.extra_data = extra_data;
+                    block
+                        .seal_slow()
+                        .try_recover()
+                        .map_err(|e| NewPayloadError::Other(e.into()))
                 } else {
                     // For other errors, re-throw them
                     Err(NewPayloadError::Eth(err))
@@ -124,11 +150,22 @@ where
 
     fn validate_payload_attributes_against_header(
         &self,
-        _attr: &<T as PayloadTypes>::PayloadAttributes,
-        _header: &<Self::Block as reth_ethereum::primitives::Block>::Header,
+        attr: &<T as PayloadTypes>::PayloadAttributes,
+        header: &<Self::Block as reth_ethereum::primitives::Block>::Header,
     ) -> Result<(), InvalidPayloadAttributesError> {
-        // Skip default timestamp validation for rollkit
-        Ok(())
+        // Unlike the default validation, rollkit allows the parent's timestamp to be reused,
+        // within the bounds of the chain spec's timestamp rules
+        let timestamp = attr.inner.timestamp;
+        if timestamp < header.timestamp {
+            return Err(InvalidPayloadAttributesError::InvalidTimestamp);
+        }
+
+        let rules = &self.config.timestamp_rules;
+        rules
+            .validate_drift_now(timestamp)
+            .and_then(|()| rules.validate_against_parent(timestamp, header, None))
+            .and_then(|()| validate_timestamp_millis(timestamp, attr.timestamp_millis, header))
+            .map_err(|err| InvalidPayloadAttributesError::InvalidParams(Box::new(err)))
     }
 }
 
@@ -150,6 +187,6 @@ where
     type Validator = RollkitEngineValidator;
 
     async fn build(self, ctx: &AddOnsContext<'_, N>) -> eyre::Result<Self::Validator> {
-        Ok(RollkitEngineValidator::new(ctx.config.chain.clone()))
+        Ok(RollkitEngineValidator::try_new(ctx.config.chain.clone())?)
     }
 }
@@ -0,0 +1,322 @@
+//! Payload attributes that produced each built block.
+//!
+//! Once a block is built, the attributes it came from are gone: transactions the payload
+//! builder left out do not show up anywhere. When enabled, the payload builder hands a
+//! [`BuildAttributes`] record for every block it builds to a [`BuildAttributesRecorder`],
+//! and [`BuildAttributesStore`] keeps them in the [`BuildAttributesTable`] of the node's
+//! database, keyed by block hash. Records are held in memory until their block becomes
+//! canonical, and only then stored: records of payloads that never make it to the canonical
+//! chain are dropped, and records of blocks reorged or rolled back out of it are removed.
+
+use crate::types::{DaBlockInfo, RollkitPayloadAttributes};
+use alloy_eips::{
+    eip2718::{Decodable2718, Eip2718Error, Encodable2718},
+    BlockNumHash,
+};
+use alloy_primitives::{bytes::BufMut, Address, BlockNumber, Bytes, TxHash, B256};
+use reth_db::{
+    cursor::DbCursorRO,
+    table::{Compress, Decompress, Table, TableInfo},
+    transaction::{DbTx, DbTxMut},
+    Database, DatabaseError,
+};
+use reth_primitives::TransactionSigned;
+use reth_primitives_traits::NodePrimitives;
+use reth_provider::CanonStateNotifications;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tracing::{debug, warn};
+
+/// Payload attributes a block was built from, along with the transactions left out of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAttributes {
+    /// Number of the built block
+    pub block_number: BlockNumber,
+    /// Hash of the built block
+    pub block_hash: B256,
+    /// Hash of the parent block
+    pub parent_hash: B256,
+    /// EIP-2718 encoded transactions of the attributes, including those left out
+    pub transactions: Vec<Bytes>,
+    /// Gas limit of the attributes
+    pub gas_limit: Option<u64>,
+    /// Timestamp of the attributes
+    pub timestamp: u64,
+    /// Millisecond timestamp of the attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_millis: Option<u64>,
+    /// Prev randao of the attributes
+    pub prev_randao: B256,
+    /// Fee recipient of the attributes
+    pub fee_recipient: Address,
+    /// DA block info of the attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub da_info: Option<DaBlockInfo>,
+    /// Transactions of the attributes left out of the block
+    pub excluded: Vec<ExcludedTransaction>,
+}
+
+/// Transaction of the payload attributes left out of the built block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedTransaction {
+    /// Transaction hash
+    pub hash: TxHash,
+    /// Why the payload builder left the transaction out
+    pub reason: String,
+}
+
+impl BuildAttributes {
+    /// Creates the record of the block `block_hash` built from `attributes`
+    pub fn new(
+        attributes: &RollkitPayloadAttributes,
+        block_hash: B256,
+        excluded: Vec<ExcludedTransaction>,
+    ) -> Self {
+        Self {
+            block_number: attributes.block_number,
+            block_hash,
+            parent_hash: attributes.parent_hash,
+            transactions: attributes
+                .transactions
+                .iter()
+                .map(|tx| tx.encoded_2718().into())
+                .collect(),
+            gas_limit: attributes.gas_limit,
+            timestamp: attributes.timestamp,
+            timestamp_millis: attributes.timestamp_millis,
+            prev_randao: attributes.prev_randao,
+            fee_recipient: attributes.suggested_fee_recipient,
+            da_info: attributes.da_info,
+            excluded,
+        }
+    }
+
+    /// Returns the payload attributes the block was built from
+    pub fn payload_attributes(&self) -> Result<RollkitPayloadAttributes, Eip2718Error> {
+        let transactions = self
+            .transactions
+            .iter()
+            .map(|raw| TransactionSigned::decode_2718(&mut raw.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut attributes = RollkitPayloadAttributes::new(
+            transactions,
+            self.gas_limit,
+            self.timestamp,
+            self.prev_randao,
+            self.fee_recipient,
+            self.parent_hash,
+            self.block_number,
+        );
+        attributes.da_info = self.da_info;
+        attributes.timestamp_millis = self.timestamp_millis;
+        Ok(attributes)
+    }
+}
+
+impl Compress for BuildAttributes {
+    type Compressed = Vec<u8>;
+
+    /// Records are stored as JSON, they are written once and only read back for audits
+    fn compress_to_buf<B: BufMut + AsMut<[u8]>>(&self, buf: &mut B) {
+        buf.put_slice(&serde_json::to_vec(self).expect("build attributes serialize to JSON"));
+    }
+}
+
+impl Decompress for BuildAttributes {
+    fn decompress(value: &[u8]) -> Result<Self, DatabaseError> {
+        serde_json::from_slice(value).map_err(|_| DatabaseError::Decode)
+    }
+}
+
+/// Table mapping block hashes to the payload attributes they were built from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BuildAttributesTable;
+
+impl Table for BuildAttributesTable {
+    const NAME: &'static str = "EvolveBuildAttributes";
+    const DUPSORT: bool = false;
+    type Key = B256;
+    type Value = BuildAttributes;
+}
+
+impl TableInfo for BuildAttributesTable {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn is_dupsort(&self) -> bool {
+        Self::DUPSORT
+    }
+}
+
+/// Handle through which the payload builder records the attributes of built blocks
+#[derive(Debug, Clone)]
+pub struct BuildAttributesRecorder {
+    sender: mpsc::UnboundedSender<BuildAttributes>,
+}
+
+impl BuildAttributesRecorder {
+    /// Creates a recorder along with the receiving end to hand to
+    /// [`BuildAttributesStore::record`]
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<BuildAttributes>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    /// Records the attributes of a built block
+    pub fn record(&self, attributes: BuildAttributes) {
+        // The store may have shut down with the node, which is fine
+        let _ = self.sender.send(attributes);
+    }
+}
+
+/// Store of build attributes backed by the node's database
+#[derive(Debug, Clone)]
+pub struct BuildAttributesStore<DB> {
+    db: DB,
+}
+
+impl<DB: Database> BuildAttributesStore<DB> {
+    /// Creates a new store on a database in which [`EvolveTables`](crate::EvolveTables)
+    /// have been created
+    pub const fn new(db: DB) -> Self {
+        Self { db }
+    }
+
+    /// Stores the attributes of a built block, replacing any previous record of the block
+    pub fn insert(&self, attributes: &BuildAttributes) -> Result<(), DatabaseError> {
+        let tx = self.db.tx_mut()?;
+        tx.put::<BuildAttributesTable>(attributes.block_hash, attributes.clone())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the attributes the block `block_hash` was built from, if recorded
+    pub fn get(&self, block_hash: B256) -> Result<Option<BuildAttributes>, DatabaseError> {
+        let tx = self.db.tx()?;
+        let attributes = tx.get::<BuildAttributesTable>(block_hash)?;
+        tx.commit()?;
+        Ok(attributes)
+    }
+
+    /// Stores the pending records of the blocks that became canonical, and drops the pending
+    /// records of other blocks at or below their heights, which can no longer become
+    /// canonical. Returns how many records were stored.
+    pub fn store_canonical(
+        &self,
+        pending: &mut HashMap<B256, BuildAttributes>,
+        blocks: impl IntoIterator<Item = BlockNumHash>,
+    ) -> Result<usize, DatabaseError> {
+        let tx = self.db.tx_mut()?;
+        let mut stored = 0;
+        let mut tip = None;
+        for block in blocks {
+            if let Some(attributes) = pending.remove(&block.hash) {
+                tx.put::<BuildAttributesTable>(block.hash, attributes)?;
+                stored += 1;
+            }
+            tip = tip.max(Some(block.number));
+        }
+        tx.commit()?;
+
+        if let Some(tip) = tip {
+            pending.retain(|_, attributes| attributes.block_number > tip);
+        }
+        Ok(stored)
+    }
+
+    /// Removes the records of blocks that are no longer canonical
+    pub fn unwind_blocks(
+        &self,
+        blocks: impl IntoIterator<Item = BlockNumHash>,
+    ) -> Result<usize, DatabaseError> {
+        let tx = self.db.tx_mut()?;
+        let mut removed = 0;
+        for block in blocks {
+            if tx.delete::<BuildAttributesTable>(block.hash, None)? {
+                removed += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Removes the records of all blocks above `number`, returning how many were removed.
+    ///
+    /// Records are keyed by block hash, so this walks all of them.
+    pub fn unwind_above(&self, number: BlockNumber) -> Result<usize, DatabaseError> {
+        let tx = self.db.tx_mut()?;
+        let hashes = tx
+            .cursor_read::<BuildAttributesTable>()?
+            .walk(None)?
+            .filter_map(|entry| match entry {
+                Ok((hash, attributes)) => (attributes.block_number > number).then_some(Ok(hash)),
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for hash in &hashes {
+            tx.delete::<BuildAttributesTable>(*hash, None)?;
+        }
+        tx.commit()?;
+        Ok(hashes.len())
+    }
+
+    /// Keeps the records sent through a [`BuildAttributesRecorder`] until their block becomes
+    /// canonical, storing them then, and removes the records of blocks reorged out of the
+    /// canonical chain. Runs until all recorders are dropped or the notification stream
+    /// closes.
+    pub async fn record<N: NodePrimitives>(
+        self,
+        mut records: mpsc::UnboundedReceiver<BuildAttributes>,
+        mut notifications: CanonStateNotifications<N>,
+    ) {
+        let mut pending = HashMap::new();
+        loop {
+            tokio::select! {
+                // A block is built before it becomes canonical, so take its record first
+                biased;
+                record = records.recv() => match record {
+                    Some(attributes) => {
+                        pending.insert(attributes.block_hash, attributes);
+                    }
+                    None => return,
+                },
+                notification = notifications.recv() => {
+                    let notification = match notification {
+                        Ok(notification) => notification,
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!(
+                                skipped,
+                                "Build attributes store lagged behind canonical state notifications"
+                            );
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return,
+                    };
+
+                    if let Some(old) = notification.reverted() {
+                        let blocks = old.blocks().values().map(|block| block.num_hash());
+                        match self.unwind_blocks(blocks) {
+                            Ok(removed) if removed > 0 => {
+                                debug!(removed, "Removed build attributes of reorged blocks")
+                            }
+                            Ok(_) => {}
+                            Err(err) => {
+                                warn!(%err, "Failed to remove build attributes of reorged blocks")
+                            }
+                        }
+                    }
+                    let new = notification.committed();
+                    let blocks = new.blocks().values().map(|block| block.num_hash());
+                    if let Err(err) = self.store_canonical(&mut pending, blocks) {
+                        warn!(%err, "Failed to store build attributes");
+                    }
+                }
+            }
+        }
+    }
+}
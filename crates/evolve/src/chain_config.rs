@@ -0,0 +1,444 @@
+//! Evolve parameters of the chain spec.
+//!
+//! They are stored in the genesis `config` object under [`EVOLVE_CONFIG_KEY`], next to the
+//! Ethereum fork schedule, and are read by consensus, the payload builder and the pool.
+
+use crate::{config::BLOCK_OVERHEAD_BYTES, genesis::EVOLVE_CONFIG_KEY, types::EvolveExtraData};
+use alloy_consensus::Header;
+use alloy_genesis::Genesis;
+use alloy_primitives::{keccak256, Address, BlockNumber, B256, U256};
+use reth_chainspec::ChainSpec;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Evolve parameters of a chain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EvolveChainConfig {
+    /// Sequencer keys by activation height.
+    ///
+    /// When non-empty, every block from the first activation height on must be signed by
+    /// the sequencer active at its height.
+    pub sequencers: Vec<SequencerKey>,
+    /// Round-robin proposer set, taking over from the sequencer keys at its activation height
+    pub proposer_rotation: Option<ProposerRotation>,
+    /// Bounds on block timestamps
+    pub timestamp_rules: TimestampRules,
+    /// Origins allowed to deploy contracts, unrestricted when unset
+    pub deploy_allowlist: Option<DeployAllowlist>,
+    /// Pins the base fee of every block at zero instead of following EIP-1559, so
+    /// transactions may be priced at zero
+    pub zero_fee: bool,
+    /// Maximum size of an RLP-encoded block in bytes, unbounded when unset
+    pub max_block_bytes: Option<u64>,
+}
+
+/// Sequencer key active from a block height on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencerKey {
+    /// First block signed with this key
+    pub activation_height: BlockNumber,
+    /// Address of the sequencer key
+    pub address: Address,
+}
+
+/// Round-robin set of proposers taking turns by block height
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposerRotation {
+    /// First block proposed by the set
+    pub activation_height: BlockNumber,
+    /// Proposers in turn order
+    pub proposers: Vec<Address>,
+    /// Number of consecutive blocks proposed in a turn
+    #[serde(default = "default_blocks_per_proposer")]
+    pub blocks_per_proposer: u64,
+}
+
+const fn default_blocks_per_proposer() -> u64 {
+    1
+}
+
+impl ProposerRotation {
+    /// Returns the proposer whose turn includes block `number`, if the set is active.
+    ///
+    /// A set without proposers or giving them no block, which validation rejects, schedules
+    /// no one.
+    pub fn proposer_at(&self, number: BlockNumber) -> Option<Address> {
+        let offset = number.checked_sub(self.activation_height)?;
+        let turn = offset.checked_div(self.blocks_per_proposer)?;
+        let index = turn.checked_rem(self.proposers.len() as u64)?;
+        self.proposers.get(index as usize).copied()
+    }
+}
+
+/// Contract deployment allow-list.
+///
+/// From its activation height on, contract creations (`CREATE`, `CREATE2` and creation
+/// transactions) are only allowed in transactions sent by an allow-listed origin. An origin
+/// is allow-listed if it is one of the `deployers` or if the governance contract's
+/// `mapping(address => bool)` at storage slot 0 holds `true` for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployAllowlist {
+    /// First block enforcing the allow-list
+    #[serde(default)]
+    pub activation_height: BlockNumber,
+    /// Origins allowed to deploy contracts
+    #[serde(default)]
+    pub deployers: Vec<Address>,
+    /// Governance contract listing further allowed origins
+    #[serde(default)]
+    pub governance_contract: Option<Address>,
+}
+
+impl DeployAllowlist {
+    /// Returns `true` if the allow-list is enforced at block `number`
+    pub const fn is_active(&self, number: BlockNumber) -> bool {
+        number >= self.activation_height
+    }
+
+    /// Returns the governance contract's storage slot holding the flag of `deployer`
+    pub fn governance_slot(deployer: Address) -> B256 {
+        let mut key = [0u8; 64];
+        key[..32].copy_from_slice(deployer.into_word().as_slice());
+        keccak256(key)
+    }
+
+    /// Returns `true` if `deployer` may create contracts in block `number`.
+    ///
+    /// `storage` reads a storage slot of the governance contract, it is only called for
+    /// origins missing from the static list.
+    pub fn allows<E>(
+        &self,
+        number: BlockNumber,
+        deployer: Address,
+        storage: impl FnOnce(Address, B256) -> Result<U256, E>,
+    ) -> Result<bool, E> {
+        if !self.is_active(number) || self.deployers.contains(&deployer) {
+            return Ok(true);
+        }
+        match self.governance_contract {
+            Some(contract) => Ok(!storage(contract, Self::governance_slot(deployer))?.is_zero()),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Bounds on block timestamps, each unbounded when unset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimestampRules {
+    /// First block the bounds against the parent (equal-timestamp runs and timestamp jumps)
+    /// apply to, so that a chain adopting them later still validates its history
+    pub activation_height: BlockNumber,
+    /// Maximum number of seconds a block's timestamp may be ahead of the local clock
+    pub max_future_drift: Option<u64>,
+    /// Maximum number of consecutive blocks sharing a timestamp
+    pub max_equal_timestamp_run: Option<u64>,
+    /// Maximum number of seconds between a block's timestamp and its parent's
+    pub max_timestamp_jump: Option<u64>,
+}
+
+impl TimestampRules {
+    /// Returns `true` if the bounds against the parent apply to block `number`
+    pub const fn is_active(&self, number: BlockNumber) -> bool {
+        number >= self.activation_height
+    }
+
+    /// Returns the equal-timestamp run recorded by a block with the given timestamp on top of
+    /// `parent`, zero unless runs are bounded at the block's height
+    pub fn timestamp_run(&self, parent: &Header, timestamp: u64) -> u64 {
+        if self.max_equal_timestamp_run.is_some() && self.is_active(parent.number + 1) {
+            EvolveExtraData::next_timestamp_run(parent, timestamp)
+        } else {
+            0
+        }
+    }
+
+    /// Checks that `timestamp` is not too far ahead of `now`
+    pub const fn validate_drift(&self, timestamp: u64, now: u64) -> Result<(), TimestampRuleError> {
+        if let Some(max) = self.max_future_drift {
+            if timestamp > now.saturating_add(max) {
+                return Err(TimestampRuleError::FutureDrift {
+                    timestamp,
+                    now,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `timestamp` is not too far ahead of the local clock
+    pub fn validate_drift_now(&self, timestamp: u64) -> Result<(), TimestampRuleError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.validate_drift(timestamp, now)
+    }
+
+    /// Checks that a block with the given timestamp may follow `parent`.
+    ///
+    /// `recorded_run` is the equal-timestamp run recorded by the block, or `None` when
+    /// checking payload attributes for a block that is yet to be built. Blocks below the
+    /// activation height are not checked.
+    pub fn validate_against_parent(
+        &self,
+        timestamp: u64,
+        parent: &Header,
+        recorded_run: Option<u64>,
+    ) -> Result<(), TimestampRuleError> {
+        if !self.is_active(parent.number + 1) {
+            return Ok(());
+        }
+
+        if let Some(max) = self.max_timestamp_jump {
+            if timestamp > parent.timestamp.saturating_add(max) {
+                return Err(TimestampRuleError::TimestampJump {
+                    parent_timestamp: parent.timestamp,
+                    timestamp,
+                    max,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_equal_timestamp_run {
+            let run = EvolveExtraData::next_timestamp_run(parent, timestamp);
+            if let Some(recorded) = recorded_run {
+                if recorded != run {
+                    return Err(TimestampRuleError::TimestampRunMismatch {
+                        expected: run,
+                        recorded,
+                    });
+                }
+            }
+            // The run counts the ancestors sharing the timestamp, the block itself is one more
+            if run >= max {
+                return Err(TimestampRuleError::EqualTimestampRun {
+                    timestamp,
+                    blocks: run.saturating_add(1),
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a block's millisecond timestamp lies within its seconds timestamp and
+/// increases over its parent's.
+///
+/// Parents without a recorded millisecond timestamp count as the start of their second, so
+/// the first block recording one may share it. Once a parent records one, so must the block.
+pub fn validate_timestamp_millis(
+    timestamp: u64,
+    timestamp_millis: Option<u64>,
+    parent: &Header,
+) -> Result<(), TimestampRuleError> {
+    let parent_millis = EvolveExtraData::decode(&parent.extra_data)
+        .and_then(|extension| extension.timestamp_millis);
+    let Some(timestamp_millis) = timestamp_millis else {
+        return match parent_millis {
+            Some(parent_millis) => Err(TimestampRuleError::MissingMillis { parent_millis }),
+            None => Ok(()),
+        };
+    };
+
+    if timestamp_millis / 1000 != timestamp {
+        return Err(TimestampRuleError::InconsistentMillis {
+            timestamp,
+            timestamp_millis,
+        });
+    }
+
+    let increasing = match parent_millis {
+        Some(parent_millis) => timestamp_millis > parent_millis,
+        None => timestamp_millis >= parent.timestamp.saturating_mul(1000),
+    };
+    if !increasing {
+        return Err(TimestampRuleError::MillisNotIncreasing {
+            parent_millis: EvolveExtraData::timestamp_millis_of(parent),
+            timestamp_millis,
+        });
+    }
+    Ok(())
+}
+
+impl EvolveChainConfig {
+    /// Reads the Evolve parameters of a chain spec, defaulting to no parameters
+    pub fn from_chain_spec(chain_spec: &ChainSpec) -> Result<Self, EvolveChainConfigError> {
+        Self::from_genesis(chain_spec.genesis())
+    }
+
+    /// Reads the Evolve parameters of a genesis, defaulting to no parameters
+    pub fn from_genesis(genesis: &Genesis) -> Result<Self, EvolveChainConfigError> {
+        genesis
+            .config
+            .extra_fields
+            .get(EVOLVE_CONFIG_KEY)
+            .map_or_else(|| Ok(Self::default()), Self::from_value)
+    }
+
+    /// Parses and validates Evolve parameters from their JSON value
+    pub fn from_value(value: &serde_json::Value) -> Result<Self, EvolveChainConfigError> {
+        let config: Self = serde_json::from_value(value.clone())
+            .map_err(|err| EvolveChainConfigError::Invalid(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Validates the parameters
+    pub fn validate(&self) -> Result<(), EvolveChainConfigError> {
+        for pair in self.sequencers.windows(2) {
+            if pair[1].activation_height <= pair[0].activation_height {
+                return Err(EvolveChainConfigError::Invalid(format!(
+                    "sequencer activation heights must be strictly increasing, got {} after {}",
+                    pair[1].activation_height, pair[0].activation_height
+                )));
+            }
+        }
+
+        if let Some(rotation) = &self.proposer_rotation {
+            if rotation.proposers.is_empty() {
+                return Err(EvolveChainConfigError::Invalid(
+                    "proposer rotation has no proposers".to_string(),
+                ));
+            }
+            if rotation.blocks_per_proposer == 0 {
+                return Err(EvolveChainConfigError::Invalid(
+                    "proposer rotation must give each proposer at least one block".to_string(),
+                ));
+            }
+            if let Some(last) = self.sequencers.last() {
+                if rotation.activation_height <= last.activation_height {
+                    return Err(EvolveChainConfigError::Invalid(format!(
+                        "proposer rotation activates at {}, before sequencer key activating at {}",
+                        rotation.activation_height, last.activation_height
+                    )));
+                }
+            }
+        }
+
+        if self.timestamp_rules.max_equal_timestamp_run == Some(0) {
+            return Err(EvolveChainConfigError::Invalid(
+                "the equal-timestamp run must allow at least one block per timestamp".to_string(),
+            ));
+        }
+
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            if max_block_bytes <= BLOCK_OVERHEAD_BYTES {
+                return Err(EvolveChainConfigError::Invalid(format!(
+                    "blocks of {max_block_bytes} bytes leave no room for transactions next to the \
+                     {BLOCK_OVERHEAD_BYTES}-byte block overhead"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the proposer that must sign the block at `number`, if any
+    pub fn proposer_at(&self, number: BlockNumber) -> Option<Address> {
+        if let Some(proposer) = self
+            .proposer_rotation
+            .as_ref()
+            .and_then(|rotation| rotation.proposer_at(number))
+        {
+            return Some(proposer);
+        }
+
+        self.sequencers
+            .iter()
+            .rev()
+            .find(|key| key.activation_height <= number)
+            .map(|key| key.address)
+    }
+
+    /// Returns `true` if blocks must be signed by scheduled proposers from some height on
+    pub const fn has_proposer_schedule(&self) -> bool {
+        !self.sequencers.is_empty() || self.proposer_rotation.is_some()
+    }
+
+    /// Returns `true` if `address` proposes blocks at some height
+    pub fn is_scheduled(&self, address: Address) -> bool {
+        self.sequencers.iter().any(|key| key.address == address)
+            || self
+                .proposer_rotation
+                .as_ref()
+                .is_some_and(|rotation| rotation.proposers.contains(&address))
+    }
+}
+
+/// Violations of the [`TimestampRules`] and of millisecond timestamp ordering
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TimestampRuleError {
+    /// The timestamp is too far ahead of the local clock
+    #[error("timestamp {timestamp} is more than {max}s ahead of the local clock {now}")]
+    FutureDrift {
+        /// Block timestamp
+        timestamp: u64,
+        /// Local clock
+        now: u64,
+        /// Maximum drift
+        max: u64,
+    },
+    /// Too many consecutive blocks share the timestamp
+    #[error("{blocks} consecutive blocks with timestamp {timestamp}, at most {max} allowed")]
+    EqualTimestampRun {
+        /// Shared timestamp
+        timestamp: u64,
+        /// Number of consecutive blocks sharing it, including this one
+        blocks: u64,
+        /// Maximum run
+        max: u64,
+    },
+    /// The recorded equal-timestamp run does not match the parent's
+    #[error("recorded equal-timestamp run {recorded}, expected {expected}")]
+    TimestampRunMismatch {
+        /// Run derived from the parent
+        expected: u64,
+        /// Run recorded in the header
+        recorded: u64,
+    },
+    /// The millisecond timestamp is outside the second of the timestamp
+    #[error("millisecond timestamp {timestamp_millis} is not within timestamp {timestamp}")]
+    InconsistentMillis {
+        /// Block timestamp
+        timestamp: u64,
+        /// Block timestamp in milliseconds
+        timestamp_millis: u64,
+    },
+    /// The millisecond timestamp does not increase over the parent's
+    #[error("millisecond timestamp {timestamp_millis} is not after parent's {parent_millis}")]
+    MillisNotIncreasing {
+        /// Parent timestamp in milliseconds
+        parent_millis: u64,
+        /// Block timestamp in milliseconds
+        timestamp_millis: u64,
+    },
+    /// The parent records a millisecond timestamp but the block does not
+    #[error("missing millisecond timestamp after parent's {parent_millis}")]
+    MissingMillis {
+        /// Parent timestamp in milliseconds
+        parent_millis: u64,
+    },
+    /// The timestamp is too far ahead of the parent's
+    #[error("timestamp {timestamp} is more than {max}s after parent timestamp {parent_timestamp}")]
+    TimestampJump {
+        /// Parent timestamp
+        parent_timestamp: u64,
+        /// Block timestamp
+        timestamp: u64,
+        /// Maximum jump
+        max: u64,
+    },
+}
+
+/// Errors that can occur while reading the Evolve parameters of a chain spec
+#[derive(Debug, thiserror::Error)]
+pub enum EvolveChainConfigError {
+    /// The parameters are malformed or inconsistent
+    #[error("Invalid Evolve chain config: {0}")]
+    Invalid(String),
+}
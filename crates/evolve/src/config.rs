@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
 
+/// Bytes of an encoded block reserved for its header and RLP list headers
+pub const BLOCK_OVERHEAD_BYTES: u64 = 1024;
+
+/// Default maximum size of an RLP-encoded block
+pub const DEFAULT_MAX_BLOCK_BYTES: u64 = DEFAULT_MAX_TXPOOL_BYTES + BLOCK_OVERHEAD_BYTES;
+
 /// Default maximum bytes for txpool transactions (1.85 MiB)
 pub const DEFAULT_MAX_TXPOOL_BYTES: u64 = 1_939_865; // 1.85 MiB = 1,939,865 bytes
 
@@ -23,4 +29,15 @@ impl RollkitConfig {
     pub const fn new(max_txpool_bytes: u64) -> Self {
         Self { max_txpool_bytes }
     }
+
+    /// Creates a `RollkitConfig` returning at most the transactions fitting in a block of
+    /// `max_block_bytes`, or [`DEFAULT_MAX_TXPOOL_BYTES`] if blocks are unbounded
+    pub const fn for_max_block_bytes(max_block_bytes: Option<u64>) -> Self {
+        match max_block_bytes {
+            Some(max_block_bytes) => {
+                Self::new(max_block_bytes.saturating_sub(BLOCK_OVERHEAD_BYTES))
+            }
+            None => Self::new(DEFAULT_MAX_TXPOOL_BYTES),
+        }
+    }
 }
@@ -1,14 +1,24 @@
 //! Rollkit custom consensus implementation that allows same timestamps across blocks.
 
-use reth_chainspec::ChainSpec;
+use crate::{
+    chain_config::{
+        validate_timestamp_millis, EvolveChainConfig, EvolveChainConfigError, TimestampRuleError,
+    },
+    sequencer::{recover_sequencer, SequencerError},
+    types::EvolveExtraData,
+};
+use alloy_primitives::{Address, BlockNumber, Bytes};
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use reth_consensus::{Consensus, ConsensusError, FullConsensus, HeaderValidator};
-use reth_consensus_common::validation::validate_body_against_header;
+use reth_consensus_common::validation::{
+    validate_against_parent_4844, validate_body_against_header,
+};
 use reth_ethereum::node::builder::{components::ConsensusBuilder, BuilderContext};
 use reth_ethereum_consensus::EthBeaconConsensus;
 use reth_ethereum_primitives::{Block, BlockBody, EthPrimitives, Receipt};
 use reth_execution_types::BlockExecutionResult;
 use reth_node_api::{FullNodeTypes, NodeTypes};
-use reth_primitives::{RecoveredBlock, SealedBlock, SealedHeader};
+use reth_primitives::{Header, RecoveredBlock, SealedBlock, SealedHeader};
 use std::sync::Arc;
 
 /// Builder for `RollkitConsensus`
@@ -22,9 +32,12 @@ impl RollkitConsensusBuilder {
         Self
     }
 
-    /// Build the consensus implementation
-    pub fn build(chain_spec: Arc<ChainSpec>) -> Arc<RollkitConsensus> {
-        Arc::new(RollkitConsensus::new(chain_spec))
+    /// Build the consensus implementation, failing if the Evolve parameters of the chain spec
+    /// are invalid
+    pub fn build(
+        chain_spec: Arc<ChainSpec>,
+    ) -> Result<Arc<RollkitConsensus>, EvolveChainConfigError> {
+        RollkitConsensus::try_new(chain_spec).map(Arc::new)
     }
 }
 
@@ -36,7 +49,7 @@ where
     type Consensus = Arc<dyn FullConsensus<EthPrimitives, Error = ConsensusError>>;
 
     async fn build_consensus(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Consensus> {
-        Ok(Arc::new(RollkitConsensus::new(ctx.chain_spec())) as Self::Consensus)
+        Ok(Self::build(ctx.chain_spec())? as Self::Consensus)
     }
 }
 
@@ -44,23 +57,153 @@ where
 ///
 /// This consensus implementation wraps the standard Ethereum beacon consensus
 /// but modifies the timestamp validation to allow multiple blocks to have the
-/// same timestamp, which is required for Rollkit's operation.
+/// same timestamp, which is required for Rollkit's operation. When the chain spec
+/// configures a proposer schedule, it also requires every block to be signed by the
+/// proposer scheduled at its height, and it enforces the chain spec's timestamp rules
+/// (clock drift, equal-timestamp runs and timestamp jumps).
 #[derive(Debug, Clone)]
 pub struct RollkitConsensus {
     /// Inner Ethereum beacon consensus for standard validation
     inner: EthBeaconConsensus<ChainSpec>,
+    /// Chain spec, for the checks run outside of the inner consensus
+    chain_spec: Arc<ChainSpec>,
+    /// Evolve parameters of the chain spec
+    config: EvolveChainConfig,
 }
 
 impl RollkitConsensus {
-    /// Create a new Rollkit consensus instance
-    pub const fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        let inner = EthBeaconConsensus::new(chain_spec);
-        Self { inner }
+    /// Create a new Rollkit consensus instance, reading the Evolve parameters of the chain spec
+    pub fn try_new(chain_spec: Arc<ChainSpec>) -> Result<Self, EvolveChainConfigError> {
+        let config = EvolveChainConfig::from_chain_spec(&chain_spec)?;
+        Self::with_config(chain_spec, config)
+    }
+
+    /// Create a new Rollkit consensus instance with the given Evolve parameters, which must be
+    /// valid
+    pub fn with_config(
+        chain_spec: Arc<ChainSpec>,
+        config: EvolveChainConfig,
+    ) -> Result<Self, EvolveChainConfigError> {
+        config.validate()?;
+        let inner = EthBeaconConsensus::new(chain_spec.clone());
+        Ok(Self {
+            inner,
+            chain_spec,
+            config,
+        })
+    }
+
+    /// Returns the Evolve parameters enforced by this instance
+    pub const fn config(&self) -> &EvolveChainConfig {
+        &self.config
+    }
+
+    /// Checks that the header is signed by the proposer scheduled at its height, if any.
+    ///
+    /// The schedule only depends on the block's height, so this runs once per header, with a
+    /// single signature recovery.
+    fn validate_proposer(&self, header: &Header) -> Result<(), EvolveConsensusError> {
+        let Some(expected) = self.config.proposer_at(header.number) else {
+            return Ok(());
+        };
+
+        let signer = recover_sequencer(header).map_err(|source| {
+            EvolveConsensusError::InvalidSequencerSignature {
+                number: header.number,
+                expected,
+                source,
+            }
+        })?;
+        if signer != expected {
+            return Err(EvolveConsensusError::UnexpectedProposer {
+                number: header.number,
+                expected,
+                signer,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs the Ethereum checks of a header against its parent, allowing the header to share
+    /// its parent's timestamp and, on zero-fee chains, a base fee pinned at zero
+    fn validate_ethereum_against_parent(
+        &self,
+        header: &SealedHeader,
+        parent: &SealedHeader,
+    ) -> Result<(), ConsensusError> {
+        match self.inner.validate_header_against_parent(header, parent) {
+            // Upstream requires a later timestamp and stops at that check, so a header sharing
+            // its parent's timestamp goes through the checks following it (gas limit, base fee,
+            // blob gas) against the parent as if it were a second older, or, at timestamp zero,
+            // as if the header were a second later
+            Err(ConsensusError::TimestampIsInPast { .. })
+                if header.timestamp == parent.timestamp =>
+            {
+                match parent.timestamp.checked_sub(1) {
+                    Some(timestamp) => {
+                        let mut older = parent.header().clone();
+                        older.timestamp = timestamp;
+                        self.validate_ethereum_against_parent(
+                            header,
+                            &SealedHeader::new(older, parent.hash()),
+                        )
+                    }
+                    None => {
+                        let mut later = header.header().clone();
+                        later.timestamp = 1;
+                        self.validate_ethereum_against_parent(
+                            &SealedHeader::new(later, header.hash()),
+                            parent,
+                        )
+                    }
+                }
+            }
+            // Zero-fee chains pin the base fee at zero instead of following EIP-1559, which
+            // `validate_header` checks. Upstream stops at the base fee, so the blob gas checks
+            // following it still have to run
+            Err(ConsensusError::BaseFeeDiff(_)) if self.config.zero_fee => {
+                match self.chain_spec.blob_params_at_timestamp(header.timestamp) {
+                    Some(blob_params) => {
+                        validate_against_parent_4844(header.header(), parent.header(), blob_params)
+                    }
+                    None => Ok(()),
+                }
+            }
+            result => result,
+        }
     }
 }
 
 impl HeaderValidator for RollkitConsensus {
     fn validate_header(&self, header: &SealedHeader) -> Result<(), ConsensusError> {
+        self.validate_proposer(header.header())?;
+        if self.config.zero_fee {
+            if let Some(base_fee) = header.base_fee_per_gas.filter(|base_fee| *base_fee != 0) {
+                return Err(EvolveConsensusError::NonZeroBaseFee {
+                    number: header.number,
+                    base_fee,
+                }
+                .into());
+            }
+        }
+        self.config
+            .timestamp_rules
+            .validate_drift_now(header.timestamp)
+            .map_err(|source| EvolveConsensusError::Timestamp {
+                number: header.number,
+                source,
+            })?;
+
+        // An Evolve header extension is larger than Ethereum's extra data cap, so validate the
+        // rest of the header without it
+        if EvolveExtraData::decode(&header.extra_data).is_some() {
+            let mut stripped = header.header().clone();
+            stripped.extra_data = Bytes::new();
+            return self
+                .inner
+                .validate_header(&SealedHeader::new(stripped, header.hash()));
+        }
+
         // Use inner consensus for basic header validation
         self.inner.validate_header(header)
     }
@@ -70,23 +213,30 @@ impl HeaderValidator for RollkitConsensus {
         header: &SealedHeader,
         parent: &SealedHeader,
     ) -> Result<(), ConsensusError> {
-        match self.inner.validate_header_against_parent(header, parent) {
-            Ok(()) => Ok(()),
-            // upstream the check is that its greater than the parent's timestamp, if not we get
-            // TimestampIsInPast we check if the timestamp is equal to the parent's timestamp, if so we
-            // allow it
-            Err(ConsensusError::TimestampIsInPast { .. }) => {
-                if header.timestamp == parent.timestamp {
-                    Ok(())
-                } else {
-                    Err(ConsensusError::TimestampIsInPast {
-                        parent_timestamp: parent.timestamp,
-                        timestamp: header.timestamp,
-                    })
-                }
-            }
-            Err(e) => Err(e),
-        }
+        self.validate_ethereum_against_parent(header, parent)?;
+
+        let extension = EvolveExtraData::decode(&header.extra_data).unwrap_or_default();
+        self.config
+            .timestamp_rules
+            .validate_against_parent(
+                header.timestamp,
+                parent.header(),
+                Some(extension.timestamp_run),
+            )
+            .and_then(|()| {
+                validate_timestamp_millis(
+                    header.timestamp,
+                    extension.timestamp_millis,
+                    parent.header(),
+                )
+            })
+            .map_err(|source| EvolveConsensusError::Timestamp {
+                number: header.number,
+                source,
+            })?;
+
+        // The signer is checked against the proposer schedule by `validate_header`
+        Ok(())
     }
 }
 
@@ -102,6 +252,18 @@ impl Consensus<Block> for RollkitConsensus {
     }
 
     fn validate_block_pre_execution(&self, block: &SealedBlock) -> Result<(), Self::Error> {
+        if let Some(max) = self.config.max_block_bytes {
+            let size = block.rlp_length() as u64;
+            if size > max {
+                return Err(EvolveConsensusError::BlockTooLarge {
+                    number: block.number,
+                    size,
+                    max,
+                }
+                .into());
+            }
+        }
+
         // Use inner consensus for pre-execution validation
         self.inner.validate_block_pre_execution(block)
     }
@@ -116,3 +278,62 @@ impl FullConsensus<EthPrimitives> for RollkitConsensus {
         <EthBeaconConsensus<ChainSpec> as FullConsensus<EthPrimitives>>::validate_block_post_execution(&self.inner, block, result)
     }
 }
+
+/// Evolve-specific consensus violations
+#[derive(Debug, thiserror::Error)]
+pub enum EvolveConsensusError {
+    /// The block carries no valid sequencer signature
+    #[error("block {number} must be signed by proposer {expected}: {source}")]
+    InvalidSequencerSignature {
+        /// Block number
+        number: BlockNumber,
+        /// Proposer scheduled at the block's height
+        expected: Address,
+        /// Reason the signature is invalid
+        #[source]
+        source: SequencerError,
+    },
+    /// The block is signed by a key other than the scheduled proposer
+    #[error("block {number} is signed by {signer}, expected proposer {expected}")]
+    UnexpectedProposer {
+        /// Block number
+        number: BlockNumber,
+        /// Proposer scheduled at the block's height
+        expected: Address,
+        /// Recovered signer of the block
+        signer: Address,
+    },
+    /// The block's timestamp violates the timestamp rules of the chain spec
+    #[error("block {number} violates timestamp rules: {source}")]
+    Timestamp {
+        /// Block number
+        number: BlockNumber,
+        /// Violated rule
+        #[source]
+        source: TimestampRuleError,
+    },
+    /// The block of a zero-fee chain has a non-zero base fee
+    #[error("block {number} has base fee {base_fee} on a zero-fee chain")]
+    NonZeroBaseFee {
+        /// Block number
+        number: BlockNumber,
+        /// Base fee of the block
+        base_fee: u64,
+    },
+    /// The RLP-encoded block exceeds the maximum block size
+    #[error("block {number} is {size} bytes, above the maximum of {max}")]
+    BlockTooLarge {
+        /// Block number
+        number: BlockNumber,
+        /// Size of the RLP-encoded block
+        size: u64,
+        /// Maximum block size of the chain
+        max: u64,
+    },
+}
+
+impl From<EvolveConsensusError> for ConsensusError {
+    fn from(err: EvolveConsensusError) -> Self {
+        Self::Custom(Arc::new(err))
+    }
+}
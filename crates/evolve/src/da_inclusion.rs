@@ -0,0 +1,317 @@
+//! DA inclusion metadata of L2 blocks.
+//!
+//! Evolve reports, for every L2 block, the DA height, namespace and commitment of the blob
+//! that carried it. [`DaInclusionStore`] keeps that mapping in the [`DaInclusions`] table of
+//! the node's database. Entries record the hash of the block they describe, so entries of
+//! blocks dropped by a reorg are ignored on read and removed by
+//! [`DaInclusionStore::unwind_blocks`].
+
+use crate::build_attributes::BuildAttributesTable;
+use alloy_eips::BlockNumHash;
+use alloy_primitives::{bytes::BufMut, BlockNumber, Bytes, B256};
+use reth_db::{
+    cursor::DbCursorRO,
+    table::{Compress, Decompress, Table, TableInfo, TableSet},
+    transaction::{DbTx, DbTxMut},
+    Database, DatabaseError,
+};
+use reth_primitives_traits::NodePrimitives;
+use reth_provider::{CanonStateNotification, CanonStateNotifications};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+/// Maximum length of a DA namespace
+pub const MAX_DA_NAMESPACE_LEN: usize = 64;
+
+/// Maximum length of a DA commitment
+pub const MAX_DA_COMMITMENT_LEN: usize = 256;
+
+/// Maximum number of blocks returned by a range query
+pub const MAX_DA_INCLUSION_RANGE: u64 = 1_000;
+
+/// DA inclusion of an L2 block, as reported by Evolve
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaInclusion {
+    /// Height of the DA block that carried the L2 block
+    pub da_height: u64,
+    /// Namespace of the blob on the DA layer
+    pub namespace: Bytes,
+    /// Commitment of the blob on the DA layer
+    pub commitment: Bytes,
+}
+
+impl DaInclusion {
+    /// Validates the namespace and commitment lengths
+    pub fn validate(&self) -> Result<(), DaInclusionError> {
+        if self.namespace.len() > MAX_DA_NAMESPACE_LEN {
+            return Err(DaInclusionError::InvalidNamespace(self.namespace.len()));
+        }
+        if self.commitment.is_empty() || self.commitment.len() > MAX_DA_COMMITMENT_LEN {
+            return Err(DaInclusionError::InvalidCommitment(self.commitment.len()));
+        }
+        Ok(())
+    }
+}
+
+/// DA inclusion of a block together with the block it describes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaInclusionEntry {
+    /// Number of the L2 block
+    pub block_number: BlockNumber,
+    /// Hash of the L2 block
+    pub block_hash: B256,
+    /// DA inclusion of the block
+    #[serde(flatten)]
+    pub inclusion: DaInclusion,
+}
+
+impl DaInclusionEntry {
+    /// Returns the number and hash of the block
+    pub const fn block(&self) -> BlockNumHash {
+        BlockNumHash::new(self.block_number, self.block_hash)
+    }
+}
+
+impl Compress for DaInclusionEntry {
+    type Compressed = Vec<u8>;
+
+    /// Layout: block hash, DA height (big-endian), namespace length (big-endian `u16`),
+    /// namespace, commitment. The block number is the table key.
+    fn compress_to_buf<B: BufMut + AsMut<[u8]>>(&self, buf: &mut B) {
+        buf.put_slice(self.block_hash.as_slice());
+        buf.put_u64(self.inclusion.da_height);
+        buf.put_u16(self.inclusion.namespace.len() as u16);
+        buf.put_slice(&self.inclusion.namespace);
+        buf.put_slice(&self.inclusion.commitment);
+    }
+}
+
+impl Decompress for DaInclusionEntry {
+    /// Decodes an entry, leaving the block number to be filled in from the table key
+    fn decompress(value: &[u8]) -> Result<Self, DatabaseError> {
+        const HEADER_LEN: usize = 32 + 8 + 2;
+        if value.len() < HEADER_LEN {
+            return Err(DatabaseError::Decode);
+        }
+
+        let block_hash = B256::from_slice(&value[..32]);
+        let da_height = u64::from_be_bytes(value[32..40].try_into().expect("8 bytes"));
+        let namespace_len = u16::from_be_bytes(value[40..42].try_into().expect("2 bytes")) as usize;
+        let rest = &value[HEADER_LEN..];
+        if rest.len() < namespace_len {
+            return Err(DatabaseError::Decode);
+        }
+        let (namespace, commitment) = rest.split_at(namespace_len);
+
+        Ok(Self {
+            block_number: 0,
+            block_hash,
+            inclusion: DaInclusion {
+                da_height,
+                namespace: Bytes::copy_from_slice(namespace),
+                commitment: Bytes::copy_from_slice(commitment),
+            },
+        })
+    }
+}
+
+/// Table mapping L2 block numbers to their DA inclusion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DaInclusions;
+
+impl Table for DaInclusions {
+    const NAME: &'static str = "EvolveDaInclusions";
+    const DUPSORT: bool = false;
+    type Key = BlockNumber;
+    type Value = DaInclusionEntry;
+}
+
+impl TableInfo for DaInclusions {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn is_dupsort(&self) -> bool {
+        Self::DUPSORT
+    }
+}
+
+/// Evolve tables created in the node's database on startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EvolveTables;
+
+impl TableSet for EvolveTables {
+    fn tables() -> Box<dyn Iterator<Item = Box<dyn TableInfo>>> {
+        Box::new(
+            [
+                Box::new(DaInclusions) as Box<dyn TableInfo>,
+                Box::new(BuildAttributesTable),
+            ]
+            .into_iter(),
+        )
+    }
+}
+
+/// Store of DA inclusion metadata backed by the node's database
+#[derive(Debug, Clone)]
+pub struct DaInclusionStore<DB> {
+    db: DB,
+}
+
+impl<DB: Database> DaInclusionStore<DB> {
+    /// Creates a new store on a database in which [`EvolveTables`] have been created
+    pub const fn new(db: DB) -> Self {
+        Self { db }
+    }
+
+    /// Records the DA inclusion of a block, replacing any previous entry at its height
+    pub fn insert(
+        &self,
+        block: BlockNumHash,
+        inclusion: DaInclusion,
+    ) -> Result<(), DaInclusionError> {
+        inclusion.validate()?;
+
+        let tx = self.db.tx_mut()?;
+        tx.put::<DaInclusions>(
+            block.number,
+            DaInclusionEntry {
+                block_number: block.number,
+                block_hash: block.hash,
+                inclusion,
+            },
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns the entry recorded at the given block number, whatever block it describes
+    pub fn get(&self, number: BlockNumber) -> Result<Option<DaInclusionEntry>, DaInclusionError> {
+        let tx = self.db.tx()?;
+        let entry = tx
+            .get::<DaInclusions>(number)?
+            .map(|entry| DaInclusionEntry {
+                block_number: number,
+                ..entry
+            });
+        tx.commit()?;
+        Ok(entry)
+    }
+
+    /// Returns the entries recorded for the blocks in `from..=to`
+    pub fn range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<DaInclusionEntry>, DaInclusionError> {
+        if to < from || to - from >= MAX_DA_INCLUSION_RANGE {
+            return Err(DaInclusionError::InvalidRange { from, to });
+        }
+
+        let tx = self.db.tx()?;
+        let entries = tx
+            .cursor_read::<DaInclusions>()?
+            .walk_range(from..=to)?
+            .map(|entry| {
+                entry.map(|(number, entry)| DaInclusionEntry {
+                    block_number: number,
+                    ..entry
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        tx.commit()?;
+        Ok(entries)
+    }
+
+    /// Removes the entries of blocks that are no longer canonical
+    pub fn unwind_blocks(
+        &self,
+        blocks: impl IntoIterator<Item = BlockNumHash>,
+    ) -> Result<usize, DaInclusionError> {
+        let tx = self.db.tx_mut()?;
+        let mut removed = 0;
+        for block in blocks {
+            let recorded = tx.get::<DaInclusions>(block.number)?;
+            if recorded.is_some_and(|entry| entry.block_hash == block.hash) {
+                tx.delete::<DaInclusions>(block.number, None)?;
+                removed += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Removes the entries of all blocks above `number`, returning how many were removed
+    pub fn unwind_above(&self, number: BlockNumber) -> Result<usize, DaInclusionError> {
+        let tx = self.db.tx_mut()?;
+        let numbers = tx
+            .cursor_read::<DaInclusions>()?
+            .walk_range(number.saturating_add(1)..)?
+            .map(|entry| entry.map(|(number, _)| number))
+            .collect::<Result<Vec<_>, _>>()?;
+        for number in &numbers {
+            tx.delete::<DaInclusions>(*number, None)?;
+        }
+        tx.commit()?;
+        Ok(numbers.len())
+    }
+
+    /// Removes the entries of blocks reorged out of the canonical chain until the
+    /// notification stream closes
+    pub async fn unwind_on_reorgs<N: NodePrimitives>(
+        self,
+        mut notifications: CanonStateNotifications<N>,
+    ) {
+        loop {
+            let notification = match notifications.recv().await {
+                Ok(notification) => notification,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        skipped,
+                        "DA inclusion store lagged behind canonical state notifications"
+                    );
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            if let CanonStateNotification::Reorg { old, .. } = notification {
+                let blocks = old.blocks().values().map(|block| block.num_hash());
+                match self.unwind_blocks(blocks) {
+                    Ok(removed) if removed > 0 => {
+                        debug!(removed, "Removed DA inclusions of reorged blocks")
+                    }
+                    Ok(_) => {}
+                    Err(err) => warn!(%err, "Failed to remove DA inclusions of reorged blocks"),
+                }
+            }
+        }
+    }
+}
+
+/// Errors that can occur while storing or querying DA inclusion metadata
+#[derive(Debug, thiserror::Error)]
+pub enum DaInclusionError {
+    /// The namespace is too long
+    #[error("Invalid DA namespace length {0}, expected at most {MAX_DA_NAMESPACE_LEN}")]
+    InvalidNamespace(usize),
+    /// The commitment is empty or too long
+    #[error("Invalid DA commitment length {0}, expected 1 to {MAX_DA_COMMITMENT_LEN}")]
+    InvalidCommitment(usize),
+    /// The range is empty or too large
+    #[error("Invalid block range {from}..={to}, expected at most {MAX_DA_INCLUSION_RANGE} blocks")]
+    InvalidRange {
+        /// First block of the range
+        from: BlockNumber,
+        /// Last block of the range
+        to: BlockNumber,
+    },
+    /// Accessing the database failed
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
@@ -0,0 +1,297 @@
+//! Generation of Evolve genesis files.
+//!
+//! [`GenesisConfig`] is a small description of a chain (chain ID, gas limit, fork schedule,
+//! prefunded accounts and Evolve parameters) that [`GenesisConfig::build`] turns into a
+//! [`Genesis`] accepted by the reth chain spec parser.
+
+use alloy_genesis::{ChainConfig, Genesis, GenesisAccount};
+use alloy_primitives::{uint, Address, U256};
+use alloy_signer_local::{coins_bip39::English, MnemonicBuilder};
+use ev_common::DEFAULT_CHAIN_ID;
+use reth_chainspec::ChainSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{chain_config::EvolveChainConfig, predeploys::insert_predeploys};
+
+/// Key of the Evolve parameters in the genesis `config` object
+pub const EVOLVE_CONFIG_KEY: &str = "evolve";
+
+/// Default block gas limit of generated genesis files
+pub const DEFAULT_GENESIS_GAS_LIMIT: u64 = 30_000_000;
+
+/// Default number of accounts derived from a mnemonic
+pub const DEFAULT_MNEMONIC_ACCOUNTS: u32 = 10;
+
+/// Default balance of accounts derived from a mnemonic (1,000,000 ETH)
+pub const DEFAULT_MNEMONIC_BALANCE: U256 = uint!(1_000_000_000_000_000_000_000_000_U256);
+
+/// Description of an Evolve chain used to generate its genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenesisConfig {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Block gas limit of the genesis block
+    pub gas_limit: u64,
+    /// Timestamp of the genesis block
+    pub timestamp: u64,
+    /// Base fee of the genesis block, defaults to the EIP-1559 initial base fee or to zero on
+    /// zero-fee chains
+    pub base_fee_per_gas: Option<u64>,
+    /// Hardfork activation schedule
+    pub forks: ForkSchedule,
+    /// Explicitly prefunded accounts
+    pub accounts: Vec<PrefundedAccount>,
+    /// Accounts derived from a mnemonic
+    pub mnemonic: Option<MnemonicAccounts>,
+    /// Whether to include the ev-reth predeploys
+    pub predeploys: bool,
+    /// Evolve-specific parameters, stored under `config.evolve`
+    pub evolve: Option<serde_json::Value>,
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: DEFAULT_CHAIN_ID,
+            gas_limit: DEFAULT_GENESIS_GAS_LIMIT,
+            timestamp: 0,
+            base_fee_per_gas: None,
+            forks: ForkSchedule::default(),
+            accounts: Vec::new(),
+            mnemonic: None,
+            predeploys: true,
+            evolve: None,
+        }
+    }
+}
+
+/// Timestamp-based hardfork activation schedule.
+///
+/// All block-based forks up to and including the merge are active at genesis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForkSchedule {
+    /// Shanghai activation timestamp
+    pub shanghai_time: Option<u64>,
+    /// Cancun activation timestamp
+    pub cancun_time: Option<u64>,
+    /// Prague activation timestamp
+    pub prague_time: Option<u64>,
+}
+
+impl Default for ForkSchedule {
+    fn default() -> Self {
+        Self {
+            shanghai_time: Some(0),
+            cancun_time: Some(0),
+            prague_time: None,
+        }
+    }
+}
+
+/// Account prefunded at genesis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrefundedAccount {
+    /// Account address
+    pub address: Address,
+    /// Account balance in wei
+    pub balance: U256,
+}
+
+/// Accounts derived from a BIP-39 mnemonic along `m/44'/60'/0'/0/{index}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MnemonicAccounts {
+    /// Mnemonic phrase
+    pub phrase: String,
+    /// Number of accounts to derive
+    #[serde(default = "default_mnemonic_accounts")]
+    pub count: u32,
+    /// Balance of every derived account in wei
+    #[serde(default = "default_mnemonic_balance")]
+    pub balance: U256,
+}
+
+const fn default_mnemonic_accounts() -> u32 {
+    DEFAULT_MNEMONIC_ACCOUNTS
+}
+
+const fn default_mnemonic_balance() -> U256 {
+    DEFAULT_MNEMONIC_BALANCE
+}
+
+impl GenesisConfig {
+    /// Builds the genesis described by this configuration and validates it against the
+    /// chain spec parser
+    pub fn build(&self) -> Result<Genesis, GenesisError> {
+        self.forks.validate()?;
+
+        let mut config = ChainConfig {
+            chain_id: self.chain_id,
+            homestead_block: Some(0),
+            eip150_block: Some(0),
+            eip155_block: Some(0),
+            eip158_block: Some(0),
+            byzantium_block: Some(0),
+            constantinople_block: Some(0),
+            petersburg_block: Some(0),
+            istanbul_block: Some(0),
+            berlin_block: Some(0),
+            london_block: Some(0),
+            merge_netsplit_block: Some(0),
+            terminal_total_difficulty: Some(U256::ZERO),
+            terminal_total_difficulty_passed: true,
+            shanghai_time: self.forks.shanghai_time,
+            cancun_time: self.forks.cancun_time,
+            prague_time: self.forks.prague_time,
+            ..Default::default()
+        };
+        let mut zero_fee = false;
+        if let Some(evolve) = &self.evolve {
+            zero_fee = EvolveChainConfig::from_value(evolve)
+                .map_err(|err| GenesisError::InvalidGenesis(err.to_string()))?
+                .zero_fee;
+            config
+                .extra_fields
+                .insert(EVOLVE_CONFIG_KEY.to_string(), evolve.clone());
+        }
+
+        // Zero-fee chains start at a zero base fee and keep it
+        let base_fee_per_gas = match self.base_fee_per_gas {
+            None | Some(0) if zero_fee => Some(0),
+            Some(base_fee) if zero_fee => {
+                return Err(GenesisError::InvalidGenesis(format!(
+                    "zero-fee chains need a zero genesis base fee, got {base_fee}"
+                )))
+            }
+            base_fee => base_fee,
+        };
+
+        let mut alloc = BTreeMap::new();
+        for account in self
+            .derived_accounts()?
+            .into_iter()
+            .chain(self.accounts.clone())
+        {
+            if alloc
+                .insert(
+                    account.address,
+                    GenesisAccount::default().with_balance(account.balance),
+                )
+                .is_some()
+            {
+                return Err(GenesisError::DuplicateAccount(account.address));
+            }
+        }
+
+        let mut genesis = Genesis::default()
+            .with_timestamp(self.timestamp)
+            .with_gas_limit(self.gas_limit)
+            .with_base_fee(base_fee_per_gas.map(u128::from))
+            .extend_accounts(alloc);
+        genesis.config = config;
+        if self.forks.cancun_time.is_some() {
+            genesis = genesis
+                .with_excess_blob_gas(Some(0))
+                .with_blob_gas_used(Some(0));
+        }
+        if self.predeploys {
+            insert_predeploys(&mut genesis);
+        }
+
+        validate_genesis(&genesis, self.chain_id)?;
+        Ok(genesis)
+    }
+
+    /// Returns the accounts derived from the configured mnemonic
+    pub fn derived_accounts(&self) -> Result<Vec<PrefundedAccount>, GenesisError> {
+        let Some(mnemonic) = &self.mnemonic else {
+            return Ok(Vec::new());
+        };
+
+        (0..mnemonic.count)
+            .map(|index| {
+                let signer = MnemonicBuilder::<English>::default()
+                    .phrase(mnemonic.phrase.as_str())
+                    .index(index)
+                    .and_then(|builder| builder.build())
+                    .map_err(|err| GenesisError::Mnemonic(err.to_string()))?;
+                Ok(PrefundedAccount {
+                    address: signer.address(),
+                    balance: mnemonic.balance,
+                })
+            })
+            .collect()
+    }
+}
+
+impl ForkSchedule {
+    /// Validates that forks activate in order
+    pub fn validate(&self) -> Result<(), GenesisError> {
+        let forks = [
+            ("shanghai", self.shanghai_time),
+            ("cancun", self.cancun_time),
+            ("prague", self.prague_time),
+        ];
+
+        let mut previous: Option<(&str, Option<u64>)> = None;
+        for (name, time) in forks {
+            if let Some((previous_name, previous_time)) = previous {
+                match (previous_time, time) {
+                    (None, Some(_)) => {
+                        return Err(GenesisError::InvalidForkSchedule(format!(
+                            "{name} is scheduled but {previous_name} is not"
+                        )))
+                    }
+                    (Some(previous_time), Some(time)) if time < previous_time => {
+                        return Err(GenesisError::InvalidForkSchedule(format!(
+                            "{name} activates before {previous_name}"
+                        )))
+                    }
+                    _ => {}
+                }
+            }
+            previous = Some((name, time));
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates a genesis by round-tripping it through JSON and the chain spec parser
+pub fn validate_genesis(genesis: &Genesis, chain_id: u64) -> Result<(), GenesisError> {
+    let json = serde_json::to_string(genesis)
+        .map_err(|err| GenesisError::InvalidGenesis(err.to_string()))?;
+    let parsed: Genesis =
+        serde_json::from_str(&json).map_err(|err| GenesisError::InvalidGenesis(err.to_string()))?;
+
+    let chain_spec = ChainSpec::from(parsed);
+    if chain_spec.chain.id() != chain_id {
+        return Err(GenesisError::InvalidGenesis(format!(
+            "chain ID mismatch: expected {chain_id}, got {}",
+            chain_spec.chain.id()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Errors that can occur while generating a genesis
+#[derive(Debug, thiserror::Error)]
+pub enum GenesisError {
+    /// Forks are scheduled out of order
+    #[error("Invalid fork schedule: {0}")]
+    InvalidForkSchedule(String),
+    /// The same account is prefunded twice
+    #[error("Duplicate genesis account: {0}")]
+    DuplicateAccount(Address),
+    /// Accounts could not be derived from the mnemonic
+    #[error("Mnemonic derivation failed: {0}")]
+    Mnemonic(String),
+    /// The generated genesis is rejected by the chain spec parser
+    #[error("Invalid genesis: {0}")]
+    InvalidGenesis(String),
+}
@@ -4,6 +4,12 @@
 //! - Custom payload attributes for Rollkit
 //! - Rollkit-specific types and traits
 //! - Custom consensus implementation
+//! - Predeployed system contracts
+//! - Genesis generation
+//! - DA inclusion metadata storage
+//! - Evolve chain parameters and sequencer signatures
+//! - Soft confirmations of transactions in blocks being built
+//! - Payload attributes of built blocks
 
 /// Rollkit-specific types and related definitions.
 pub mod types;
@@ -17,10 +23,45 @@ pub mod rpc;
 /// Custom consensus implementation for Rollkit.
 pub mod consensus;
 
+/// Predeployed system contracts.
+pub mod predeploys;
+
+/// Genesis generation for Evolve chains.
+pub mod genesis;
+
+/// DA inclusion metadata of L2 blocks.
+pub mod da_inclusion;
+
+/// Evolve parameters of the chain spec.
+pub mod chain_config;
+
+/// Sequencer signatures on blocks.
+pub mod sequencer;
+
+/// Soft confirmations of transactions in blocks being built.
+pub mod preconfirmation;
+
+/// Payload attributes of built blocks.
+pub mod build_attributes;
+
 #[cfg(test)]
 mod tests;
 
 // Re-export public types
-pub use config::{RollkitConfig, DEFAULT_MAX_TXPOOL_BYTES};
-pub use consensus::{RollkitConsensus, RollkitConsensusBuilder};
-pub use types::{PayloadAttributesError, RollkitPayloadAttributes};
+pub use build_attributes::{
+    BuildAttributes, BuildAttributesRecorder, BuildAttributesStore, ExcludedTransaction,
+};
+pub use chain_config::{
+    DeployAllowlist, EvolveChainConfig, EvolveChainConfigError, ProposerRotation, SequencerKey,
+    TimestampRuleError, TimestampRules,
+};
+pub use config::{
+    RollkitConfig, BLOCK_OVERHEAD_BYTES, DEFAULT_MAX_BLOCK_BYTES, DEFAULT_MAX_TXPOOL_BYTES,
+};
+pub use consensus::{EvolveConsensusError, RollkitConsensus, RollkitConsensusBuilder};
+pub use da_inclusion::{DaInclusion, DaInclusionStore, EvolveTables};
+pub use genesis::{GenesisConfig, GenesisError};
+pub use preconfirmation::{Preconfirmation, PreconfirmationStatus, Preconfirmations};
+pub use predeploys::{BLOCK_TIME_PREDEPLOY_ADDRESS, DA_INFO_PREDEPLOY_ADDRESS, SYSTEM_ADDRESS};
+pub use sequencer::SequencerError;
+pub use types::{DaBlockInfo, EvolveExtraData, PayloadAttributesError, RollkitPayloadAttributes};
@@ -0,0 +1,169 @@
+//! Soft confirmations of transactions executed into a block that is still being built.
+//!
+//! The payload builder publishes a [`Preconfirmation`] for every transaction it executes
+//! successfully, before the block is sealed. [`Preconfirmations`] fans them out to
+//! subscribers and keeps those of the blocks under construction, so that wallets can show a
+//! transaction as preconfirmed until `eth_getTransactionReceipt` returns its receipt.
+//! Preconfirmations are not final: the block carrying them may still be dropped.
+//!
+//! A payload job rebuilds its block several times before it is resolved, so a transaction is
+//! only published again when a rebuild changes its preconfirmation. The preconfirmations of a
+//! height are dropped once a block at that height becomes canonical, see
+//! [`Preconfirmations::drop_sealed`].
+
+use alloy_primitives::{Address, BlockNumber, Log, TxHash, B256};
+use reth_primitives_traits::NodePrimitives;
+use reth_provider::CanonStateNotifications;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+/// Default number of preconfirmations buffered for each subscriber
+pub const DEFAULT_PRECONFIRMATION_CHANNEL_SIZE: usize = 4096;
+
+/// Confirmation level of a [`Preconfirmation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreconfirmationStatus {
+    /// Executed by the sequencer into a block that is not sealed yet
+    Preconfirmed,
+}
+
+/// Execution result of a transaction in a block that is still being built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Preconfirmation {
+    /// Confirmation level, sealed transactions have regular receipts instead
+    pub status: PreconfirmationStatus,
+    /// Transaction hash
+    pub transaction_hash: TxHash,
+    /// Index of the transaction in the block
+    pub transaction_index: u64,
+    /// Number of the block being built
+    pub block_number: BlockNumber,
+    /// Parent of the block being built
+    pub parent_hash: B256,
+    /// Sender of the transaction
+    pub from: Address,
+    /// Recipient of the transaction, `None` for contract creations
+    pub to: Option<Address>,
+    /// Whether the transaction succeeded
+    pub success: bool,
+    /// Gas used by the transaction
+    pub gas_used: u64,
+    /// Gas used by the block up to and including the transaction
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction
+    pub logs: Vec<Log>,
+}
+
+/// Publisher of preconfirmations, shared by the payload builder and the RPC
+#[derive(Debug, Clone)]
+pub struct Preconfirmations {
+    /// Channel to the subscribers
+    sender: broadcast::Sender<Preconfirmation>,
+    /// Preconfirmations of the blocks being built, by transaction hash
+    pending: Arc<Mutex<HashMap<TxHash, Preconfirmation>>>,
+}
+
+impl Default for Preconfirmations {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRECONFIRMATION_CHANNEL_SIZE)
+    }
+}
+
+impl Preconfirmations {
+    /// Creates a publisher buffering `channel_size` preconfirmations for each subscriber
+    pub fn new(channel_size: usize) -> Self {
+        Self {
+            sender: broadcast::channel(channel_size).0,
+            pending: Arc::default(),
+        }
+    }
+
+    /// Publishes the preconfirmation of a transaction, unless the same preconfirmation was
+    /// already published for the block being built
+    pub fn publish(&self, preconfirmation: Preconfirmation) {
+        let previous = self
+            .pending
+            .lock()
+            .expect("preconfirmations lock poisoned")
+            .insert(preconfirmation.transaction_hash, preconfirmation.clone());
+        if previous.as_ref() == Some(&preconfirmation) {
+            return;
+        }
+        // Nobody may be listening, which is fine
+        let _ = self.sender.send(preconfirmation);
+    }
+
+    /// Starts building the block at `block_number` on `parent_hash` from `transactions`.
+    ///
+    /// Preconfirmations of lower blocks are dropped, their transactions have receipts once
+    /// the blocks are sealed. Those of earlier attempts at the same height are only kept if
+    /// the attempt built on the same parent and the new block may include their transaction
+    /// again, so that rebuilding a payload does not publish them twice.
+    pub fn start_block(
+        &self,
+        block_number: BlockNumber,
+        parent_hash: B256,
+        transactions: &HashSet<TxHash>,
+    ) {
+        self.pending
+            .lock()
+            .expect("preconfirmations lock poisoned")
+            .retain(|hash, preconfirmation| {
+                preconfirmation.block_number > block_number
+                    || (preconfirmation.block_number == block_number
+                        && preconfirmation.parent_hash == parent_hash
+                        && transactions.contains(hash))
+            });
+    }
+
+    /// Drops the preconfirmations of the blocks up to `block_number`, once a block at that
+    /// height is sealed
+    pub fn seal_block(&self, block_number: BlockNumber) {
+        self.pending
+            .lock()
+            .expect("preconfirmations lock poisoned")
+            .retain(|_, preconfirmation| preconfirmation.block_number > block_number);
+    }
+
+    /// Drops the preconfirmations up to the canonical tip whenever it moves, whether the
+    /// sealed blocks came from the payloads they were published for or not. Runs until the
+    /// notification stream closes.
+    pub async fn drop_sealed<N: NodePrimitives>(
+        self,
+        mut notifications: CanonStateNotifications<N>,
+    ) {
+        loop {
+            match notifications.recv().await {
+                Ok(notification) => self.seal_block(notification.tip().num_hash().number),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        skipped,
+                        "Preconfirmations lagged behind canonical state notifications"
+                    );
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Returns the preconfirmation of a transaction of a block being built, if any
+    pub fn get(&self, transaction_hash: &TxHash) -> Option<Preconfirmation> {
+        self.pending
+            .lock()
+            .expect("preconfirmations lock poisoned")
+            .get(transaction_hash)
+            .cloned()
+    }
+
+    /// Subscribes to the preconfirmations published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Preconfirmation> {
+        self.sender.subscribe()
+    }
+}
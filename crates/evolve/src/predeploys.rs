@@ -0,0 +1,66 @@
+//! Predeployed system contracts shipped with ev-reth.
+//!
+//! The DA info predeploy exposes the latest DA layer block to contracts. At the start of
+//! every block carrying [`DaBlockInfo`](crate::types::DaBlockInfo) the node calls it from
+//! [`SYSTEM_ADDRESS`] with three 32-byte words (DA height, DA block hash, DA timestamp),
+//! which it stores in slots 0, 1 and 2. Any other caller gets those three words back,
+//! regardless of calldata.
+//!
+//! The block time predeploy exposes the millisecond timestamp of the current block. At the
+//! start of every block carrying one the node calls it from [`SYSTEM_ADDRESS`] with a single
+//! 32-byte word, which it stores in slot 0. Any other caller gets that word back.
+
+use alloy_genesis::{Genesis, GenesisAccount};
+use alloy_primitives::{address, bytes, Address, Bytes};
+
+/// Caller used for Evolve system calls, same as the EIP-4788 system address
+pub const SYSTEM_ADDRESS: Address = address!("0xfffffffffffffffffffffffffffffffffffffffe");
+
+/// Address of the DA info predeploy
+pub const DA_INFO_PREDEPLOY_ADDRESS: Address =
+    address!("0x00000000000000000000000000000000000da1f0");
+
+/// Runtime bytecode of the DA info predeploy
+pub static DA_INFO_PREDEPLOY_CODE: Bytes = bytes!(
+    "3373fffffffffffffffffffffffffffffffffffffffe1460315760005460005260015460205260025460405260606000f35b60003560005560203560015560403560025500"
+);
+
+/// Address of the block time predeploy
+pub const BLOCK_TIME_PREDEPLOY_ADDRESS: Address =
+    address!("0x000000000000000000000000000000000000b10c");
+
+/// Runtime bytecode of the block time predeploy
+pub static BLOCK_TIME_PREDEPLOY_CODE: Bytes = bytes!(
+    "3373fffffffffffffffffffffffffffffffffffffffe1460255760005460005260206000f35b60003560005500"
+);
+
+/// Returns the genesis account of the DA info predeploy
+pub fn da_info_genesis_account() -> GenesisAccount {
+    GenesisAccount::default()
+        .with_nonce(Some(1))
+        .with_code(Some(DA_INFO_PREDEPLOY_CODE.clone()))
+}
+
+/// Returns the genesis account of the block time predeploy
+pub fn block_time_genesis_account() -> GenesisAccount {
+    GenesisAccount::default()
+        .with_nonce(Some(1))
+        .with_code(Some(BLOCK_TIME_PREDEPLOY_CODE.clone()))
+}
+
+/// Returns all ev-reth predeploys with their genesis accounts
+pub fn predeploys() -> Vec<(Address, GenesisAccount)> {
+    vec![
+        (DA_INFO_PREDEPLOY_ADDRESS, da_info_genesis_account()),
+        (BLOCK_TIME_PREDEPLOY_ADDRESS, block_time_genesis_account()),
+    ]
+}
+
+/// Inserts the ev-reth predeploys into the genesis allocation.
+///
+/// Accounts already allocated at a predeploy address are left untouched.
+pub fn insert_predeploys(genesis: &mut Genesis) {
+    for (address, account) in predeploys() {
+        genesis.alloc.entry(address).or_insert(account);
+    }
+}
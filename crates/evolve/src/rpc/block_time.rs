@@ -0,0 +1,80 @@
+use crate::types::EvolveExtraData;
+use alloy_consensus::Header;
+use alloy_eips::BlockId;
+use alloy_primitives::{BlockNumber, B256};
+use async_trait::async_trait;
+use jsonrpsee::types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_provider::{BlockIdReader, HeaderProvider, ProviderError};
+use serde::{Deserialize, Serialize};
+
+/// Timestamps of a block, in seconds and milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTimestamp {
+    /// Block number
+    pub number: BlockNumber,
+    /// Block hash
+    pub hash: B256,
+    /// Block timestamp in seconds
+    pub timestamp: u64,
+    /// Block timestamp in milliseconds, the start of its second if the block records none
+    pub timestamp_millis: u64,
+}
+
+/// Evolve block time RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveBlockTimeApi {
+    /// Returns the timestamps of a block, if it exists
+    #[method(name = "getBlockTimestamp")]
+    async fn get_block_timestamp(&self, block: BlockId) -> RpcResult<Option<BlockTimestamp>>;
+}
+
+/// Implementation of the Evolve block time RPC API
+#[derive(Debug)]
+pub struct EvolveBlockTimeApiImpl<Provider> {
+    /// Blockchain provider
+    provider: Provider,
+}
+
+impl<Provider> EvolveBlockTimeApiImpl<Provider> {
+    /// Creates a new instance of `EvolveBlockTimeApi`.
+    pub const fn new(provider: Provider) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl<Provider> EvolveBlockTimeApiServer for EvolveBlockTimeApiImpl<Provider>
+where
+    Provider: BlockIdReader + HeaderProvider<Header = Header> + 'static,
+{
+    async fn get_block_timestamp(&self, block: BlockId) -> RpcResult<Option<BlockTimestamp>> {
+        let Some(number) = self
+            .provider
+            .block_number_for_id(block)
+            .map_err(internal_error)?
+        else {
+            return Ok(None);
+        };
+        let Some(header) = self
+            .provider
+            .sealed_header(number)
+            .map_err(internal_error)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(BlockTimestamp {
+            number,
+            hash: header.hash(),
+            timestamp: header.timestamp,
+            timestamp_millis: EvolveExtraData::timestamp_millis_of(header.header()),
+        }))
+    }
+}
+
+/// Maps a provider error to an internal JSON-RPC error
+fn internal_error(err: ProviderError) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
@@ -0,0 +1,45 @@
+use crate::build_attributes::{BuildAttributes, BuildAttributesStore};
+use alloy_primitives::B256;
+use async_trait::async_trait;
+use jsonrpsee::types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_db::{Database, DatabaseError};
+
+/// Evolve build attributes RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveBuildAttributesApi {
+    /// Returns the payload attributes a block was built from, if this node built it with
+    /// recording enabled
+    #[method(name = "getBuildAttributes")]
+    async fn get_build_attributes(&self, block_hash: B256) -> RpcResult<Option<BuildAttributes>>;
+}
+
+/// Implementation of the Evolve build attributes RPC API
+#[derive(Debug)]
+pub struct EvolveBuildAttributesApiImpl<DB> {
+    /// Build attributes store
+    store: BuildAttributesStore<DB>,
+}
+
+impl<DB> EvolveBuildAttributesApiImpl<DB> {
+    /// Creates a new instance of `EvolveBuildAttributesApi`.
+    pub const fn new(store: BuildAttributesStore<DB>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl<DB> EvolveBuildAttributesApiServer for EvolveBuildAttributesApiImpl<DB>
+where
+    DB: Database + 'static,
+{
+    async fn get_build_attributes(&self, block_hash: B256) -> RpcResult<Option<BuildAttributes>> {
+        Ok(self.store.get(block_hash).map_err(internal_error)?)
+    }
+}
+
+/// Maps a database error to an internal JSON-RPC error
+fn internal_error(err: DatabaseError) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
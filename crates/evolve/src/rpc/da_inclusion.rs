@@ -0,0 +1,170 @@
+use crate::da_inclusion::{DaInclusion, DaInclusionEntry, DaInclusionError, DaInclusionStore};
+use alloy_eips::{BlockId, BlockNumHash};
+use alloy_primitives::BlockNumber;
+use async_trait::async_trait;
+use jsonrpsee::types::{
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+    ErrorObjectOwned,
+};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_db::Database;
+use reth_provider::{BlockHashReader, BlockIdReader, ProviderError};
+
+/// Evolve DA inclusion admin RPC API trait, served on the authenticated Engine API endpoint
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveDaInclusionAdminApi {
+    /// Records the DA inclusion of the canonical block at `block_number`
+    #[method(name = "setDAInclusion")]
+    async fn set_da_inclusion(
+        &self,
+        block_number: BlockNumber,
+        inclusion: DaInclusion,
+    ) -> RpcResult<DaInclusionEntry>;
+}
+
+/// Evolve DA inclusion RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveDaInclusionApi {
+    /// Returns the DA inclusion of a canonical block, if recorded
+    #[method(name = "getDAInclusion")]
+    async fn get_da_inclusion(&self, block: BlockId) -> RpcResult<Option<DaInclusionEntry>>;
+
+    /// Returns the recorded DA inclusions of the canonical blocks in `from..=to`
+    #[method(name = "getDAInclusionRange")]
+    async fn get_da_inclusion_range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<Vec<DaInclusionEntry>>;
+}
+
+/// Implementation of the Evolve DA inclusion RPC API
+#[derive(Debug)]
+pub struct EvolveDaInclusionApiImpl<Provider, DB> {
+    /// Blockchain provider
+    provider: Provider,
+    /// DA inclusion store
+    store: DaInclusionStore<DB>,
+}
+
+impl<Provider, DB> EvolveDaInclusionApiImpl<Provider, DB> {
+    /// Creates a new instance of `EvolveDaInclusionApi`.
+    pub const fn new(provider: Provider, store: DaInclusionStore<DB>) -> Self {
+        Self { provider, store }
+    }
+}
+
+impl<Provider, DB> EvolveDaInclusionApiImpl<Provider, DB>
+where
+    Provider: BlockIdReader + BlockHashReader,
+    DB: Database,
+{
+    /// Returns the number and hash of the canonical block at `number`
+    fn canonical_block(&self, number: BlockNumber) -> Result<BlockNumHash, DaInclusionRpcError> {
+        let hash = self
+            .provider
+            .block_hash(number)?
+            .ok_or(DaInclusionRpcError::UnknownBlock(number))?;
+        Ok(BlockNumHash::new(number, hash))
+    }
+
+    /// Returns `entry` if it describes the canonical block at its height
+    fn canonical_entry(
+        &self,
+        entry: DaInclusionEntry,
+    ) -> Result<Option<DaInclusionEntry>, DaInclusionRpcError> {
+        let canonical = self.provider.block_hash(entry.block_number)?;
+        Ok((canonical == Some(entry.block_hash)).then_some(entry))
+    }
+}
+
+#[async_trait]
+impl<Provider, DB> EvolveDaInclusionAdminApiServer for EvolveDaInclusionApiImpl<Provider, DB>
+where
+    Provider: BlockIdReader + BlockHashReader + 'static,
+    DB: Database + 'static,
+{
+    async fn set_da_inclusion(
+        &self,
+        block_number: BlockNumber,
+        inclusion: DaInclusion,
+    ) -> RpcResult<DaInclusionEntry> {
+        let block = self.canonical_block(block_number)?;
+        self.store
+            .insert(block, inclusion.clone())
+            .map_err(DaInclusionRpcError::from)?;
+        Ok(DaInclusionEntry {
+            block_number: block.number,
+            block_hash: block.hash,
+            inclusion,
+        })
+    }
+}
+
+#[async_trait]
+impl<Provider, DB> EvolveDaInclusionApiServer for EvolveDaInclusionApiImpl<Provider, DB>
+where
+    Provider: BlockIdReader + BlockHashReader + 'static,
+    DB: Database + 'static,
+{
+    async fn get_da_inclusion(&self, block: BlockId) -> RpcResult<Option<DaInclusionEntry>> {
+        let Some(number) = self
+            .provider
+            .block_number_for_id(block)
+            .map_err(DaInclusionRpcError::from)?
+        else {
+            return Ok(None);
+        };
+        let Some(entry) = self.store.get(number).map_err(DaInclusionRpcError::from)? else {
+            return Ok(None);
+        };
+        Ok(self.canonical_entry(entry)?)
+    }
+
+    async fn get_da_inclusion_range(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> RpcResult<Vec<DaInclusionEntry>> {
+        let mut entries = Vec::new();
+        for entry in self
+            .store
+            .range(from, to)
+            .map_err(DaInclusionRpcError::from)?
+        {
+            entries.extend(self.canonical_entry(entry)?);
+        }
+        Ok(entries)
+    }
+}
+
+/// Errors returned by the DA inclusion RPC
+#[derive(Debug, thiserror::Error)]
+pub enum DaInclusionRpcError {
+    /// No canonical block exists at the requested height
+    #[error("unknown block {0}")]
+    UnknownBlock(BlockNumber),
+    /// Storing or querying the metadata failed
+    #[error(transparent)]
+    Store(#[from] DaInclusionError),
+    /// Reading from the database failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+impl From<DaInclusionRpcError> for ErrorObjectOwned {
+    fn from(err: DaInclusionRpcError) -> Self {
+        let code = match err {
+            DaInclusionRpcError::UnknownBlock(_)
+            | DaInclusionRpcError::Store(
+                DaInclusionError::InvalidNamespace(_)
+                | DaInclusionError::InvalidCommitment(_)
+                | DaInclusionError::InvalidRange { .. },
+            ) => INVALID_PARAMS_CODE,
+            DaInclusionRpcError::Store(DaInclusionError::Database(_))
+            | DaInclusionRpcError::Provider(_) => INTERNAL_ERROR_CODE,
+        };
+        Self::owned(code, err.to_string(), None::<()>)
+    }
+}
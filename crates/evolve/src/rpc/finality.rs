@@ -0,0 +1,293 @@
+use alloy_eips::BlockNumHash;
+use async_trait::async_trait;
+use jsonrpsee::{tracing::info, types::ErrorObjectOwned};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_primitives_traits::SealedHeader;
+use reth_provider::{
+    BlockIdReader, BlockNumReader, CanonChainTracker, ChainStateBlockWriter, DBProvider,
+    DatabaseProviderFactory, HeaderProvider, ProviderError,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+/// Evolve finality RPC API trait, served on the authenticated Engine API endpoint
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveFinalityApi {
+    /// Marks the blocks at the given heights as safe (DA-included) and finalized (settled).
+    ///
+    /// Either height may be omitted to leave the corresponding head unchanged.
+    #[method(name = "setFinal")]
+    async fn set_final(
+        &self,
+        safe: Option<u64>,
+        finalized: Option<u64>,
+    ) -> RpcResult<FinalityStatus>;
+}
+
+/// Read-only Evolve finality RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveFinalityStatusApi {
+    /// Returns the current head, safe and finalized blocks
+    #[method(name = "getFinal")]
+    async fn get_final(&self) -> RpcResult<FinalityStatus>;
+}
+
+/// Current head, safe and finalized blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalityStatus {
+    /// Canonical head
+    pub head: BlockNumHash,
+    /// Latest DA-included block
+    pub safe: Option<BlockNumHash>,
+    /// Latest settled block
+    pub finalized: Option<BlockNumHash>,
+}
+
+/// Implementation of the Evolve finality RPC API.
+///
+/// Heads are written to the database and to the canonical in-memory state shared with the
+/// engine tree, so they survive restarts and `safe`/`finalized` block tags resolve to them.
+/// No forkchoice update is sent: the canonical head is left to ev-node, and an update built
+/// on a stale head could otherwise unwind the blocks produced in the meantime.
+#[derive(Debug)]
+pub struct EvolveFinalityApiImpl<Provider> {
+    /// Blockchain provider
+    provider: Provider,
+    /// Serializes the updates of this instance without blocking the runtime, the database
+    /// write transaction serializes them with the other instances
+    lock: Mutex<()>,
+}
+
+impl<Provider> EvolveFinalityApiImpl<Provider>
+where
+    Provider: BlockIdReader + BlockNumReader,
+{
+    /// Creates a new instance of `EvolveFinalityApi`.
+    pub fn new(provider: Provider) -> Self {
+        Self {
+            provider,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current head, safe and finalized blocks
+    pub fn status(&self) -> Result<FinalityStatus, FinalityError> {
+        let number = self.provider.best_block_number()?;
+        let hash = self
+            .provider
+            .block_hash(number)?
+            .ok_or(FinalityError::UnknownBlock(number))?;
+        Ok(FinalityStatus {
+            head: BlockNumHash::new(number, hash),
+            safe: self.provider.safe_block_num_hash()?,
+            finalized: self.provider.finalized_block_num_hash()?,
+        })
+    }
+}
+
+impl<Provider> EvolveFinalityApiImpl<Provider>
+where
+    Provider: BlockIdReader
+        + BlockNumReader
+        + HeaderProvider
+        + CanonChainTracker<Header = <Provider as HeaderProvider>::Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>,
+{
+    /// Moves the safe and finalized heads to the canonical blocks at the given heights.
+    ///
+    /// Heads never move backwards and the finalized block never passes the safe block. The
+    /// update is rejected if a block it resolved leaves the canonical chain before it is
+    /// written, e.g. because of a concurrent rollback.
+    pub async fn update(
+        &self,
+        safe: Option<u64>,
+        finalized: Option<u64>,
+    ) -> Result<FinalityStatus, FinalityError> {
+        let _guard = self.lock.lock().await;
+        // Held until the heads are written, so that updates through other instances cannot
+        // interleave with this one
+        let provider_rw = self.provider.database_provider_rw()?;
+        let current = self.status()?;
+
+        let safe = self.resolve(safe, current.safe, current.head, "safe")?;
+        let finalized = self.resolve(finalized, current.finalized, current.head, "finalized")?;
+        if let (Some(safe), Some(finalized)) = (safe, finalized) {
+            if finalized.number > safe.number {
+                return Err(FinalityError::FinalizedAfterSafe {
+                    finalized: finalized.number,
+                    safe: safe.number,
+                });
+            }
+        }
+
+        let safe_header = self.canonical_header(safe, current.safe)?;
+        let finalized_header = self.canonical_header(finalized, current.finalized)?;
+        if let Some(header) = &safe_header {
+            provider_rw.save_safe_block_number(header.num_hash().number)?;
+        }
+        if let Some(header) = &finalized_header {
+            provider_rw.save_finalized_block_number(header.num_hash().number)?;
+        }
+        // The in-memory heads are set before the transaction releases the write lock, like
+        // the engine tree sets them before persisting them
+        if let Some(header) = safe_header {
+            self.provider.set_safe(header);
+        }
+        if let Some(header) = finalized_header {
+            self.provider.set_finalized(header);
+        }
+        provider_rw.commit()?;
+
+        info!(
+            safe = ?safe.map(|block| block.number),
+            finalized = ?finalized.map(|block| block.number),
+            "Updated Evolve finality"
+        );
+        Ok(FinalityStatus {
+            head: current.head,
+            safe,
+            finalized,
+        })
+    }
+
+    /// Returns the header of a head to write, `None` if it is unchanged.
+    ///
+    /// Fails if the block is no longer the canonical block at its height.
+    fn canonical_header(
+        &self,
+        block: Option<BlockNumHash>,
+        current: Option<BlockNumHash>,
+    ) -> Result<Option<SealedHeader<<Provider as HeaderProvider>::Header>>, FinalityError> {
+        let Some(block) = block.filter(|block| Some(*block) != current) else {
+            return Ok(None);
+        };
+        match self.provider.sealed_header(block.number)? {
+            Some(header) if header.hash() == block.hash => Ok(Some(header)),
+            _ => Err(FinalityError::NotCanonical(block.number)),
+        }
+    }
+
+    /// Resolves a requested height to a canonical block, keeping the current block if no
+    /// height is requested
+    fn resolve(
+        &self,
+        requested: Option<u64>,
+        current: Option<BlockNumHash>,
+        head: BlockNumHash,
+        kind: &'static str,
+    ) -> Result<Option<BlockNumHash>, FinalityError> {
+        let Some(number) = requested else {
+            return Ok(current);
+        };
+        if number > head.number {
+            return Err(FinalityError::AheadOfHead {
+                kind,
+                number,
+                head: head.number,
+            });
+        }
+        if let Some(current) = current {
+            if number < current.number {
+                return Err(FinalityError::Backwards {
+                    kind,
+                    number,
+                    current: current.number,
+                });
+            }
+        }
+
+        let hash = self
+            .provider
+            .block_hash(number)?
+            .ok_or(FinalityError::UnknownBlock(number))?;
+        Ok(Some(BlockNumHash::new(number, hash)))
+    }
+}
+
+#[async_trait]
+impl<Provider> EvolveFinalityApiServer for EvolveFinalityApiImpl<Provider>
+where
+    Provider: BlockIdReader
+        + BlockNumReader
+        + HeaderProvider
+        + CanonChainTracker<Header = <Provider as HeaderProvider>::Header>
+        + DatabaseProviderFactory<ProviderRW: ChainStateBlockWriter>
+        + 'static,
+{
+    async fn set_final(
+        &self,
+        safe: Option<u64>,
+        finalized: Option<u64>,
+    ) -> RpcResult<FinalityStatus> {
+        Ok(self.update(safe, finalized).await?)
+    }
+}
+
+#[async_trait]
+impl<Provider> EvolveFinalityStatusApiServer for EvolveFinalityApiImpl<Provider>
+where
+    Provider: BlockIdReader + BlockNumReader + 'static,
+{
+    async fn get_final(&self) -> RpcResult<FinalityStatus> {
+        Ok(self.status()?)
+    }
+}
+
+/// Errors returned when updating the safe and finalized heads
+#[derive(Debug, thiserror::Error)]
+pub enum FinalityError {
+    /// The requested block is above the canonical head
+    #[error("{kind} block {number} is ahead of the canonical head {head}")]
+    AheadOfHead {
+        /// Head being updated
+        kind: &'static str,
+        /// Requested height
+        number: u64,
+        /// Canonical head height
+        head: u64,
+    },
+    /// The requested block is below the current head of the same kind
+    #[error("{kind} block {number} is below the current {kind} block {current}")]
+    Backwards {
+        /// Head being updated
+        kind: &'static str,
+        /// Requested height
+        number: u64,
+        /// Current height
+        current: u64,
+    },
+    /// The finalized block would be above the safe block
+    #[error("finalized block {finalized} is ahead of safe block {safe}")]
+    FinalizedAfterSafe {
+        /// Finalized height
+        finalized: u64,
+        /// Safe height
+        safe: u64,
+    },
+    /// No canonical block exists at the requested height
+    #[error("unknown block {0}")]
+    UnknownBlock(u64),
+    /// A block being written left the canonical chain during the update
+    #[error("block {0} left the canonical chain during the update")]
+    NotCanonical(u64),
+    /// Reading from the database failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+impl From<FinalityError> for ErrorObjectOwned {
+    fn from(err: FinalityError) -> Self {
+        let code = match err {
+            FinalityError::NotCanonical(_) | FinalityError::Provider(_) => {
+                jsonrpsee::types::error::INTERNAL_ERROR_CODE
+            }
+            FinalityError::AheadOfHead { .. }
+            | FinalityError::Backwards { .. }
+            | FinalityError::FinalizedAfterSafe { .. }
+            | FinalityError::UnknownBlock(_) => jsonrpsee::types::error::INVALID_PARAMS_CODE,
+        };
+        Self::owned(code, err.to_string(), None::<()>)
+    }
+}
@@ -1,4 +1,28 @@
 /// Rollkit RPC modules
 pub mod txpool;
 
-pub use txpool::{create_rollkit_txpool_module, RollkitTxpoolApiImpl};
+/// Evolve finality RPC module
+pub mod finality;
+
+/// Evolve DA inclusion RPC module
+pub mod da_inclusion;
+
+/// Evolve block time RPC module
+pub mod block_time;
+
+/// Evolve preconfirmation RPC module
+pub mod preconfirmation;
+
+/// Evolve build attributes RPC module
+pub mod build_attributes;
+
+/// Evolve rollback RPC module
+pub mod rollback;
+
+pub use block_time::{BlockTimestamp, EvolveBlockTimeApiImpl};
+pub use build_attributes::EvolveBuildAttributesApiImpl;
+pub use da_inclusion::EvolveDaInclusionApiImpl;
+pub use finality::{EvolveFinalityApiImpl, FinalityError, FinalityStatus};
+pub use preconfirmation::EvolvePreconfirmationApiImpl;
+pub use rollback::{EvolveRollbackApiImpl, RollbackError};
+pub use txpool::{create_rollkit_txpool_module, select_transactions, RollkitTxpoolApiImpl};
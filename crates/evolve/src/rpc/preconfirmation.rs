@@ -0,0 +1,76 @@
+use crate::preconfirmation::{Preconfirmation, Preconfirmations};
+use alloy_primitives::TxHash;
+use async_trait::async_trait;
+use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage};
+use jsonrpsee_core::{RpcResult, SubscriptionResult};
+use jsonrpsee_proc_macros::rpc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+/// Evolve preconfirmation RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolvePreconfirmationApi {
+    /// Returns the preconfirmation of a transaction executed into a block that is still
+    /// being built, if any
+    #[method(name = "getPendingReceipt")]
+    async fn get_pending_receipt(&self, hash: TxHash) -> RpcResult<Option<Preconfirmation>>;
+
+    /// Streams the preconfirmations of transactions as the sequencer executes them
+    #[subscription(
+        name = "subscribePreconfirmations",
+        unsubscribe = "unsubscribePreconfirmations",
+        item = Preconfirmation
+    )]
+    async fn subscribe_preconfirmations(&self) -> SubscriptionResult;
+}
+
+/// Implementation of the Evolve preconfirmation RPC API
+#[derive(Debug)]
+pub struct EvolvePreconfirmationApiImpl {
+    /// Preconfirmations published by the payload builder
+    preconfirmations: Preconfirmations,
+}
+
+impl EvolvePreconfirmationApiImpl {
+    /// Creates a new instance of `EvolvePreconfirmationApi`.
+    pub const fn new(preconfirmations: Preconfirmations) -> Self {
+        Self { preconfirmations }
+    }
+}
+
+#[async_trait]
+impl EvolvePreconfirmationApiServer for EvolvePreconfirmationApiImpl {
+    async fn get_pending_receipt(&self, hash: TxHash) -> RpcResult<Option<Preconfirmation>> {
+        Ok(self.preconfirmations.get(&hash))
+    }
+
+    async fn subscribe_preconfirmations(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> SubscriptionResult {
+        let mut preconfirmations = self.preconfirmations.subscribe();
+        let sink = pending.accept().await?;
+        loop {
+            let preconfirmation = tokio::select! {
+                _ = sink.closed() => break,
+                received = preconfirmations.recv() => match received {
+                    Ok(preconfirmation) => preconfirmation,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!(skipped, "Preconfirmation subscriber lagging behind");
+                        continue
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            let message = SubscriptionMessage::new(
+                sink.method_name(),
+                sink.subscription_id(),
+                &preconfirmation,
+            )?;
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    build_attributes::BuildAttributesStore,
+    da_inclusion::{DaInclusionError, DaInclusionStore},
+    rpc::finality::FinalityStatus,
+};
+use alloy_eips::BlockNumHash;
+use alloy_primitives::B256;
+use alloy_rpc_types_engine::ForkchoiceState;
+use async_trait::async_trait;
+use jsonrpsee::{
+    tracing::info,
+    types::{
+        error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+        ErrorObjectOwned,
+    },
+};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_db::{Database, DatabaseError};
+use reth_engine_primitives::BeaconConsensusEngineHandle;
+use reth_payload_primitives::{EngineApiMessageVersion, PayloadTypes};
+use reth_provider::{BlockIdReader, BlockNumReader, ProviderError};
+use tokio::sync::Mutex;
+
+/// Evolve rollback RPC API trait, served on the authenticated Engine API endpoint
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveRollbackApi {
+    /// Unwinds the canonical chain to the block at `height`, dropping the state, receipts,
+    /// DA inclusions and build attributes of the blocks above it.
+    ///
+    /// Unwinding below the finalized block is refused unless `force` is set.
+    #[method(name = "rollback")]
+    async fn rollback(&self, height: u64, force: Option<bool>) -> RpcResult<FinalityStatus>;
+}
+
+/// Implementation of the Evolve rollback RPC API.
+///
+/// The chain is unwound with a forkchoice update to the target block, which the engine tree
+/// follows by dropping the blocks above it from memory and from disk. This requires the
+/// engine to unwind its canonical head on such updates, which ev-reth enables.
+#[derive(Debug)]
+pub struct EvolveRollbackApiImpl<Provider, T: PayloadTypes, DB> {
+    /// Blockchain provider
+    provider: Provider,
+    /// Handle to the engine tree
+    engine: BeaconConsensusEngineHandle<T>,
+    /// DA inclusion store
+    da_inclusions: DaInclusionStore<DB>,
+    /// Build attributes store
+    build_attributes: BuildAttributesStore<DB>,
+    /// Serializes rollbacks
+    lock: Mutex<()>,
+}
+
+impl<Provider, T, DB> EvolveRollbackApiImpl<Provider, T, DB>
+where
+    Provider: BlockIdReader + BlockNumReader,
+    T: PayloadTypes,
+    DB: Database,
+{
+    /// Creates a new instance of `EvolveRollbackApi`.
+    pub fn new(
+        provider: Provider,
+        engine: BeaconConsensusEngineHandle<T>,
+        da_inclusions: DaInclusionStore<DB>,
+        build_attributes: BuildAttributesStore<DB>,
+    ) -> Self {
+        Self {
+            provider,
+            engine,
+            da_inclusions,
+            build_attributes,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Unwinds the canonical chain to the block at `height`.
+    ///
+    /// Safe and finalized blocks above the target are moved down to it.
+    pub async fn rollback(
+        &self,
+        height: u64,
+        force: bool,
+    ) -> Result<FinalityStatus, RollbackError> {
+        let _guard = self.lock.lock().await;
+        let head = self.provider.best_block_number()?;
+        if height > head {
+            return Err(RollbackError::AheadOfHead { height, head });
+        }
+        let finalized = self.provider.finalized_block_num_hash()?;
+        if let Some(finalized) = finalized.filter(|finalized| finalized.number > height) {
+            if !force {
+                return Err(RollbackError::BelowFinalized {
+                    height,
+                    finalized: finalized.number,
+                });
+            }
+        }
+
+        let hash = self
+            .provider
+            .block_hash(height)?
+            .ok_or(RollbackError::UnknownBlock(height))?;
+        let target = BlockNumHash::new(height, hash);
+        let clamp = |block: Option<BlockNumHash>| {
+            block.map(|block| if block.number > height { target } else { block })
+        };
+        let safe = clamp(self.provider.safe_block_num_hash()?);
+        let finalized = clamp(finalized);
+
+        let state = ForkchoiceState {
+            head_block_hash: target.hash,
+            safe_block_hash: safe.map_or(B256::ZERO, |block| block.hash),
+            finalized_block_hash: finalized.map_or(B256::ZERO, |block| block.hash),
+        };
+        let updated = self
+            .engine
+            .fork_choice_updated(state, None, EngineApiMessageVersion::default())
+            .await
+            .map_err(|err| RollbackError::Engine(err.to_string()))?;
+        if !updated.payload_status.is_valid() {
+            return Err(RollbackError::Engine(format!(
+                "forkchoice update rejected: {:?}",
+                updated.payload_status.status
+            )));
+        }
+
+        let removed = self.da_inclusions.unwind_above(height)?;
+        let removed_build_attributes = self
+            .build_attributes
+            .unwind_above(height)
+            .map_err(RollbackError::BuildAttributes)?;
+        info!(
+            height,
+            previous_head = head,
+            removed_da_inclusions = removed,
+            removed_build_attributes,
+            "Rolled back the canonical chain"
+        );
+        Ok(FinalityStatus {
+            head: target,
+            safe,
+            finalized,
+        })
+    }
+}
+
+#[async_trait]
+impl<Provider, T, DB> EvolveRollbackApiServer for EvolveRollbackApiImpl<Provider, T, DB>
+where
+    Provider: BlockIdReader + BlockNumReader + 'static,
+    T: PayloadTypes,
+    DB: Database + 'static,
+{
+    async fn rollback(&self, height: u64, force: Option<bool>) -> RpcResult<FinalityStatus> {
+        Ok(self.rollback(height, force.unwrap_or_default()).await?)
+    }
+}
+
+/// Errors returned when rolling the chain back
+#[derive(Debug, thiserror::Error)]
+pub enum RollbackError {
+    /// The target is above the canonical head
+    #[error("rollback target {height} is ahead of the canonical head {head}")]
+    AheadOfHead {
+        /// Target height
+        height: u64,
+        /// Canonical head height
+        head: u64,
+    },
+    /// The target is below the finalized block and the rollback is not forced
+    #[error("rollback target {height} is below the finalized block {finalized}")]
+    BelowFinalized {
+        /// Target height
+        height: u64,
+        /// Finalized height
+        finalized: u64,
+    },
+    /// No canonical block exists at the target height
+    #[error("unknown block {0}")]
+    UnknownBlock(u64),
+    /// The engine rejected the forkchoice update
+    #[error("engine error: {0}")]
+    Engine(String),
+    /// Removing the DA inclusions failed
+    #[error(transparent)]
+    DaInclusion(#[from] DaInclusionError),
+    /// Removing the build attributes failed
+    #[error("failed to remove build attributes: {0}")]
+    BuildAttributes(DatabaseError),
+    /// Reading from the database failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+impl From<RollbackError> for ErrorObjectOwned {
+    fn from(err: RollbackError) -> Self {
+        let code = match err {
+            RollbackError::AheadOfHead { .. }
+            | RollbackError::BelowFinalized { .. }
+            | RollbackError::UnknownBlock(_) => INVALID_PARAMS_CODE,
+            RollbackError::Engine(_)
+            | RollbackError::DaInclusion(_)
+            | RollbackError::BuildAttributes(_)
+            | RollbackError::Provider(_) => INTERNAL_ERROR_CODE,
+        };
+        Self::owned(code, err.to_string(), None::<()>)
+    }
+}
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use jsonrpsee::tracing::debug;
 use jsonrpsee_core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
-use reth_transaction_pool::{PoolTransaction, TransactionPool};
+use reth_transaction_pool::{BestTransactionsAttributes, PoolTransaction, TransactionPool};
 
 /// Rollkit txpool RPC API trait
 #[rpc(server, namespace = "txpoolExt")]
@@ -20,12 +20,24 @@ pub struct RollkitTxpoolApiImpl<Pool> {
     pool: Pool,
     /// Maximum bytes allowed for transaction selection
     max_bytes: u64,
+    /// Base fee the selected transactions must pay, the pool's pending base fee when unset
+    base_fee: Option<u64>,
 }
 
 impl<Pool> RollkitTxpoolApiImpl<Pool> {
     /// Creates a new instance of `TxpoolApi`.
     pub const fn new(pool: Pool, max_bytes: u64) -> Self {
-        Self { pool, max_bytes }
+        Self {
+            pool,
+            max_bytes,
+            base_fee: None,
+        }
+    }
+
+    /// Selects transactions paying `base_fee` instead of the pool's pending base fee
+    pub const fn with_base_fee(mut self, base_fee: Option<u64>) -> Self {
+        self.base_fee = base_fee;
+        self
     }
 }
 
@@ -37,7 +49,48 @@ pub const fn create_rollkit_txpool_module<Pool>(
 where
     Pool: TransactionPool + Send + Sync + 'static,
 {
-    RollkitTxpoolApiImpl { pool, max_bytes }
+    RollkitTxpoolApiImpl::new(pool, max_bytes)
+}
+
+/// Selects the best transactions from the pool, RLP-encoded, until `max_bytes` is reached.
+///
+/// Transactions must pay `base_fee` if set, or else the pool's pending base fee. Zero-fee
+/// chains pass zero, since the pool derives its pending base fee with EIP-1559.
+pub fn select_transactions<Pool>(pool: &Pool, max_bytes: u64, base_fee: Option<u64>) -> Vec<Bytes>
+where
+    Pool: TransactionPool,
+{
+    //------------------------------------------------------------------//
+    // 1. Iterate best txs (sorted by priority) and stop once we hit    //
+    //    the byte cap                                                   //
+    //------------------------------------------------------------------//
+    let mut total = 0u64;
+    let mut selected_txs: Vec<Bytes> = Vec::new();
+
+    // Use best_transactions() which returns an iterator of transactions
+    // ordered by their priority (gas price/priority fee)
+    let best_txs = match base_fee {
+        Some(base_fee) => {
+            pool.best_transactions_with_attributes(BestTransactionsAttributes::base_fee(base_fee))
+        }
+        None => pool.best_transactions(),
+    };
+    for best_tx in best_txs {
+        let sz = best_tx.encoded_length() as u64;
+        if total + sz > max_bytes {
+            break;
+        }
+
+        // Convert to consensus transaction and encode to RLP
+        let tx = best_tx.transaction.clone().into_consensus_with2718();
+        let bz = tx.encoded_bytes();
+
+        selected_txs.push(bz.clone());
+
+        total += sz;
+    }
+
+    selected_txs
 }
 
 #[async_trait]
@@ -47,29 +100,7 @@ where
 {
     /// Returns a Geth-style `TxpoolContent` with raw RLP hex strings.
     async fn get_txs(&self) -> RpcResult<Vec<Bytes>> {
-        //------------------------------------------------------------------//
-        // 1. Iterate best txs (sorted by priority) and stop once we hit    //
-        //    the byte cap                                                   //
-        //------------------------------------------------------------------//
-        let mut total = 0u64;
-        let mut selected_txs: Vec<Bytes> = Vec::new();
-
-        // Use best_transactions() which returns an iterator of transactions
-        // ordered by their priority (gas price/priority fee)
-        for best_tx in self.pool.best_transactions() {
-            let sz = best_tx.encoded_length() as u64;
-            if total + sz > self.max_bytes {
-                break;
-            }
-
-            // Convert to consensus transaction and encode to RLP
-            let tx = best_tx.transaction.clone().into_consensus_with2718();
-            let bz = tx.encoded_bytes();
-
-            selected_txs.push(bz.clone());
-
-            total += sz;
-        }
+        let selected_txs = select_transactions(&self.pool, self.max_bytes, self.base_fee);
 
         debug!("get_txs returning {} transactions", selected_txs.len());
         Ok(selected_txs)
@@ -78,7 +109,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::config::{RollkitConfig, DEFAULT_MAX_TXPOOL_BYTES};
+    use crate::config::{
+        RollkitConfig, BLOCK_OVERHEAD_BYTES, DEFAULT_MAX_BLOCK_BYTES, DEFAULT_MAX_TXPOOL_BYTES,
+    };
 
     #[test]
     fn test_default_config_value() {
@@ -101,4 +134,21 @@ mod tests {
         let custom_config = RollkitConfig::new(1000);
         assert_eq!(custom_config.max_txpool_bytes, 1000);
     }
+
+    #[test]
+    fn test_config_for_max_block_bytes() {
+        // The txpool returns a block's worth of transactions, leaving room for the header
+        assert_eq!(
+            RollkitConfig::for_max_block_bytes(Some(DEFAULT_MAX_BLOCK_BYTES)).max_txpool_bytes,
+            DEFAULT_MAX_TXPOOL_BYTES
+        );
+        assert_eq!(
+            RollkitConfig::for_max_block_bytes(Some(100_000)).max_txpool_bytes,
+            100_000 - BLOCK_OVERHEAD_BYTES
+        );
+        assert_eq!(
+            RollkitConfig::for_max_block_bytes(None).max_txpool_bytes,
+            DEFAULT_MAX_TXPOOL_BYTES
+        );
+    }
 }
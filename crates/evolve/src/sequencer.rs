@@ -0,0 +1,95 @@
+//! Sequencer signatures on blocks.
+//!
+//! The sequencer signs the block's seal hash: the hash of its header with the signature
+//! removed from the [`EvolveExtraData`] extension. The signature is then added to the
+//! extension, so the block hash commits to it.
+
+use crate::types::EvolveExtraData;
+use alloy_consensus::Header;
+use alloy_primitives::{Address, B256};
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use std::path::Path;
+
+/// Returns the hash signed by the sequencer of a block
+pub fn seal_hash(header: &Header) -> B256 {
+    let extension = EvolveExtraData::decode(&header.extra_data).unwrap_or_default();
+    if extension.signature.is_none() {
+        return header.hash_slow();
+    }
+
+    let mut unsigned = header.clone();
+    unsigned.extra_data = extension.without_signature().encode();
+    unsigned.hash_slow()
+}
+
+/// Signs a header with the sequencer key.
+///
+/// Any previous signature is replaced, as is extra data that is not an Evolve extension
+/// (such as the client version written by the block assembler).
+pub fn sign_header(header: &mut Header, signer: &PrivateKeySigner) -> Result<(), SequencerError> {
+    let mut extension = EvolveExtraData::decode(&header.extra_data)
+        .unwrap_or_default()
+        .without_signature();
+    header.extra_data = extension.encode();
+
+    let signature = signer
+        .sign_hash_sync(&header.hash_slow())
+        .map_err(|err| SequencerError::Signing(err.to_string()))?;
+    extension.signature = Some(signature);
+    header.extra_data = extension.encode();
+    Ok(())
+}
+
+/// Recovers the sequencer that signed a header.
+///
+/// Only low-s signatures are accepted: the high-s twin of a signature recovers the same
+/// signer, so accepting it would let anyone give a signed block a second hash.
+pub fn recover_sequencer(header: &Header) -> Result<Address, SequencerError> {
+    let signature = EvolveExtraData::decode(&header.extra_data)
+        .and_then(|extension| extension.signature)
+        .ok_or(SequencerError::MissingSignature)?;
+    if signature.normalize_s().is_some() {
+        return Err(SequencerError::HighS);
+    }
+    signature
+        .recover_address_from_prehash(&seal_hash(header))
+        .map_err(|_| SequencerError::InvalidSignature)
+}
+
+/// Decrypts the sequencer key from a keystore file
+pub fn load_keystore(
+    path: impl AsRef<Path>,
+    password: impl AsRef<[u8]>,
+) -> Result<PrivateKeySigner, SequencerError> {
+    let path = path.as_ref();
+    PrivateKeySigner::decrypt_keystore(path, password).map_err(|err| SequencerError::Keystore {
+        path: path.display().to_string(),
+        reason: err.to_string(),
+    })
+}
+
+/// Errors that can occur while signing blocks or verifying their signatures
+#[derive(Debug, thiserror::Error)]
+pub enum SequencerError {
+    /// The header carries no sequencer signature
+    #[error("Missing sequencer signature")]
+    MissingSignature,
+    /// The sequencer signature is malformed
+    #[error("Invalid sequencer signature")]
+    InvalidSignature,
+    /// The sequencer signature has a high `s` value
+    #[error("Sequencer signature has a high s value")]
+    HighS,
+    /// Signing the seal hash failed
+    #[error("Failed to sign block: {0}")]
+    Signing(String),
+    /// The keystore could not be decrypted
+    #[error("Failed to decrypt sequencer keystore {path}: {reason}")]
+    Keystore {
+        /// Path of the keystore file
+        path: String,
+        /// Reason the keystore could not be decrypted
+        reason: String,
+    },
+}
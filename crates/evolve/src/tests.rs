@@ -1,5 +1,52 @@
-use crate::types::{PayloadAttributesError, RollkitPayloadAttributes};
-use alloy_primitives::{Address, B256};
+use crate::{
+    build_attributes::{
+        BuildAttributes, BuildAttributesRecorder, BuildAttributesStore, ExcludedTransaction,
+    },
+    chain_config::{
+        validate_timestamp_millis, DeployAllowlist, EvolveChainConfig, ProposerRotation,
+        SequencerKey, TimestampRuleError, TimestampRules,
+    },
+    config::BLOCK_OVERHEAD_BYTES,
+    da_inclusion::{
+        DaInclusion, DaInclusionEntry, DaInclusionError, DaInclusionStore, EvolveTables,
+    },
+    genesis::{
+        ForkSchedule, GenesisConfig, GenesisError, MnemonicAccounts, PrefundedAccount,
+        DEFAULT_MNEMONIC_BALANCE, EVOLVE_CONFIG_KEY,
+    },
+    preconfirmation::{Preconfirmation, PreconfirmationStatus, Preconfirmations},
+    predeploys::{
+        insert_predeploys, BLOCK_TIME_PREDEPLOY_ADDRESS, BLOCK_TIME_PREDEPLOY_CODE,
+        DA_INFO_PREDEPLOY_ADDRESS, DA_INFO_PREDEPLOY_CODE,
+    },
+    rpc::{
+        finality::{FinalityError, FinalityStatus},
+        rollback::RollbackError,
+    },
+    sequencer::{recover_sequencer, seal_hash, sign_header, SequencerError},
+    types::{
+        DaBlockInfo, EvolveExtraData, PayloadAttributesError, RollkitPayloadAttributes,
+        DA_INFO_EXTRA_DATA_LEN, EVOLVE_EXTRA_DATA_VERSION, SEQUENCER_SIGNATURE_LEN,
+    },
+};
+use alloy_consensus::{Header, TxLegacy};
+use alloy_eips::BlockNumHash;
+use alloy_genesis::Genesis;
+use alloy_primitives::{address, bytes, Address, Bytes, Signature, TxKind, B256, U256};
+use alloy_signer::SignerSync;
+use alloy_signer_local::PrivateKeySigner;
+use jsonrpsee::types::{
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+    ErrorObjectOwned,
+};
+use reth_chainspec::{ChainSpec, EthereumHardforks};
+use reth_db::{
+    table::{Compress, Decompress},
+    test_utils::create_test_rw_db,
+};
+use reth_primitives::{Transaction, TransactionSigned};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::broadcast::error::TryRecvError;
 
 /// Test payload attributes creation and basic field assignment
 #[test]
@@ -248,3 +295,1106 @@ fn test_validation_consistency() {
     // Invalid gas limits should always fail
     assert!(base_attrs(Some(0)).validate().is_err());
 }
+
+/// Test DA block info round trips through the header extra data encoding
+#[test]
+fn test_da_info_extra_data_roundtrip() {
+    let da_info = DaBlockInfo::new(1234, B256::repeat_byte(0xab), 1710338135);
+
+    let encoded = da_info.encode_extra_data();
+    assert_eq!(encoded.len(), DA_INFO_EXTRA_DATA_LEN);
+    assert_eq!(DaBlockInfo::from_extra_data(&encoded), Some(da_info));
+
+    // Regular extra data is not mistaken for DA block info
+    assert_eq!(DaBlockInfo::from_extra_data(b"reth/v1.6.0/linux"), None);
+    assert_eq!(DaBlockInfo::from_extra_data(&[]), None);
+
+    // Unknown versions are rejected
+    let mut unknown_version = encoded.to_vec();
+    unknown_version[0] = 0xff;
+    assert_eq!(DaBlockInfo::from_extra_data(&unknown_version), None);
+}
+
+/// Test the flagged header extension encoding and its compatibility with version 1
+#[test]
+fn test_evolve_extra_data_roundtrip() {
+    let da_info = DaBlockInfo::new(1234, B256::repeat_byte(0xab), 1710338135);
+    let signature = PrivateKeySigner::random()
+        .sign_hash_sync(&B256::repeat_byte(0x01))
+        .unwrap();
+
+    // DA info alone keeps the version 1 encoding
+    let da_only = EvolveExtraData::new(Some(da_info));
+    assert_eq!(da_only.encode(), da_info.encode_extra_data());
+    assert_eq!(
+        EvolveExtraData::decode(&da_info.encode_extra_data()),
+        Some(da_only)
+    );
+    assert!(EvolveExtraData::default().encode().is_empty());
+
+    let signed = EvolveExtraData {
+        da_info: Some(da_info),
+        signature: Some(signature),
+        ..Default::default()
+    };
+    let encoded = signed.encode();
+    assert_eq!(encoded[0], EVOLVE_EXTRA_DATA_VERSION);
+    assert_eq!(
+        encoded.len(),
+        DA_INFO_EXTRA_DATA_LEN + 1 + SEQUENCER_SIGNATURE_LEN
+    );
+    assert_eq!(EvolveExtraData::decode(&encoded), Some(signed));
+    assert_eq!(DaBlockInfo::from_extra_data(&encoded), Some(da_info));
+
+    let signature_only = EvolveExtraData {
+        signature: Some(signature),
+        ..Default::default()
+    };
+    let encoded = signature_only.encode();
+    assert_eq!(encoded.len(), 2 + SEQUENCER_SIGNATURE_LEN);
+    assert_eq!(EvolveExtraData::decode(&encoded), Some(signature_only));
+    assert_eq!(DaBlockInfo::from_extra_data(&encoded), None);
+
+    // Truncated, padded and unknown-flag extensions are rejected
+    assert_eq!(EvolveExtraData::decode(&encoded[..encoded.len() - 1]), None);
+    let mut padded = encoded.to_vec();
+    padded.push(0);
+    assert_eq!(EvolveExtraData::decode(&padded), None);
+    let mut unknown_flag = encoded.to_vec();
+    unknown_flag[1] |= 0x80;
+    assert_eq!(EvolveExtraData::decode(&unknown_flag), None);
+}
+
+/// Test recording equal-timestamp runs in the header extension
+#[test]
+fn test_timestamp_run_extra_data() {
+    let da_info = DaBlockInfo::new(1, B256::repeat_byte(0x33), 10);
+    let mut parent = Header {
+        timestamp: 1000,
+        extra_data: Bytes::from_static(b"reth/v1.6.0/linux"),
+        ..Default::default()
+    };
+
+    // Parents without an extension start a new run
+    assert_eq!(EvolveExtraData::next_timestamp_run(&parent, 1000), 1);
+    assert_eq!(EvolveExtraData::next_timestamp_run(&parent, 1001), 0);
+
+    let extension = EvolveExtraData {
+        da_info: Some(da_info),
+        timestamp_run: 4,
+        ..Default::default()
+    };
+    parent.extra_data = extension.encode();
+    assert_eq!(parent.extra_data[0], EVOLVE_EXTRA_DATA_VERSION);
+    assert_eq!(EvolveExtraData::decode(&parent.extra_data), Some(extension));
+    assert_eq!(EvolveExtraData::next_timestamp_run(&parent, 1000), 5);
+    assert_eq!(EvolveExtraData::next_timestamp_run(&parent, 1001), 0);
+
+    // A zero run is never encoded explicitly
+    let mut zero_run = extension.encode().to_vec();
+    let len = zero_run.len();
+    zero_run[len - 8..].fill(0);
+    assert_eq!(EvolveExtraData::decode(&zero_run), None);
+}
+
+/// Test recording millisecond timestamps in the header extension
+#[test]
+fn test_timestamp_millis_extra_data() {
+    let mut header = Header {
+        timestamp: 1000,
+        ..Default::default()
+    };
+    assert_eq!(EvolveExtraData::timestamp_millis_of(&header), 1_000_000);
+
+    let extension = EvolveExtraData {
+        timestamp_run: 2,
+        timestamp_millis: Some(1_000_250),
+        ..Default::default()
+    };
+    header.extra_data = extension.encode();
+    assert_eq!(header.extra_data[0], EVOLVE_EXTRA_DATA_VERSION);
+    assert_eq!(header.extra_data.len(), 2 + 8 + 8);
+    assert_eq!(EvolveExtraData::decode(&header.extra_data), Some(extension));
+    assert_eq!(EvolveExtraData::timestamp_millis_of(&header), 1_000_250);
+
+    // A millisecond timestamp alone is enough to write the extension
+    let millis_only = EvolveExtraData {
+        timestamp_millis: Some(0),
+        ..Default::default()
+    };
+    assert!(!millis_only.is_empty());
+    assert_eq!(
+        EvolveExtraData::decode(&millis_only.encode()),
+        Some(millis_only)
+    );
+}
+
+/// Test ordering blocks by millisecond timestamp
+#[test]
+fn test_validate_timestamp_millis() {
+    let parent = Header {
+        timestamp: 1000,
+        ..Default::default()
+    };
+    // Parents without millis count as the start of their second
+    assert!(validate_timestamp_millis(1000, Some(1_000_000), &parent).is_ok());
+    assert!(validate_timestamp_millis(1000, None, &parent).is_ok());
+    assert_eq!(
+        validate_timestamp_millis(999, Some(999_999), &parent),
+        Err(TimestampRuleError::MillisNotIncreasing {
+            parent_millis: 1_000_000,
+            timestamp_millis: 999_999
+        })
+    );
+    assert_eq!(
+        validate_timestamp_millis(1000, Some(1_001_000), &parent),
+        Err(TimestampRuleError::InconsistentMillis {
+            timestamp: 1000,
+            timestamp_millis: 1_001_000
+        })
+    );
+
+    let parent = Header {
+        timestamp: 1000,
+        extra_data: EvolveExtraData {
+            timestamp_millis: Some(1_000_400),
+            ..Default::default()
+        }
+        .encode(),
+        ..Default::default()
+    };
+    // Several blocks may share a second as long as their millis increase
+    assert!(validate_timestamp_millis(1000, Some(1_000_401), &parent).is_ok());
+    assert!(validate_timestamp_millis(1001, Some(1_001_000), &parent).is_ok());
+    assert_eq!(
+        validate_timestamp_millis(1000, Some(1_000_400), &parent),
+        Err(TimestampRuleError::MillisNotIncreasing {
+            parent_millis: 1_000_400,
+            timestamp_millis: 1_000_400
+        })
+    );
+    assert_eq!(
+        validate_timestamp_millis(1001, None, &parent),
+        Err(TimestampRuleError::MissingMillis {
+            parent_millis: 1_000_400
+        })
+    );
+}
+
+/// Test the millisecond timestamp of payload attributes
+#[test]
+fn test_payload_attributes_with_timestamp_millis() {
+    let attrs =
+        RollkitPayloadAttributes::new(vec![], None, 1000, B256::ZERO, Address::ZERO, B256::ZERO, 1);
+    assert!(attrs
+        .clone()
+        .with_timestamp_millis(1_000_999)
+        .validate()
+        .is_ok());
+    assert!(matches!(
+        attrs.with_timestamp_millis(999_999).validate(),
+        Err(PayloadAttributesError::InvalidTimestampMillis {
+            timestamp: 1000,
+            timestamp_millis: 999_999
+        })
+    ));
+}
+
+/// Test signing a header and recovering its sequencer
+#[test]
+fn test_sequencer_signature() {
+    let signer = PrivateKeySigner::random();
+    let da_info = DaBlockInfo::new(3, B256::repeat_byte(0x22), 77);
+    let mut header = Header {
+        number: 5,
+        gas_limit: 30_000_000,
+        extra_data: da_info.encode_extra_data(),
+        ..Default::default()
+    };
+    let unsigned_hash = header.hash_slow();
+
+    assert!(matches!(
+        recover_sequencer(&header),
+        Err(SequencerError::MissingSignature)
+    ));
+
+    sign_header(&mut header, &signer).unwrap();
+    assert_eq!(seal_hash(&header), unsigned_hash);
+    assert_ne!(header.hash_slow(), unsigned_hash);
+    assert_eq!(recover_sequencer(&header).unwrap(), signer.address());
+    assert_eq!(
+        DaBlockInfo::from_extra_data(&header.extra_data),
+        Some(da_info)
+    );
+
+    // Re-signing replaces the previous signature
+    let other = PrivateKeySigner::random();
+    sign_header(&mut header, &other).unwrap();
+    assert_eq!(recover_sequencer(&header).unwrap(), other.address());
+
+    // The high-s twin of the signature is rejected, though it recovers the same signer
+    let mut extension = EvolveExtraData::decode(&header.extra_data).unwrap();
+    let signature = extension.signature.unwrap();
+    let order = U256::from_str_radix(
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap();
+    let high_s = Signature::new(signature.r(), order - signature.s(), !signature.v());
+    assert_eq!(
+        high_s
+            .recover_address_from_prehash(&seal_hash(&header))
+            .unwrap(),
+        other.address()
+    );
+    extension.signature = Some(high_s);
+    let mut malleated = header.clone();
+    malleated.extra_data = extension.encode();
+    assert_ne!(malleated.hash_slow(), header.hash_slow());
+    assert!(matches!(
+        recover_sequencer(&malleated),
+        Err(SequencerError::HighS)
+    ));
+
+    // Client vanity extra data is replaced by the extension
+    let mut header = Header {
+        extra_data: Bytes::from_static(b"reth/v1.6.0/linux"),
+        ..Default::default()
+    };
+    sign_header(&mut header, &signer).unwrap();
+    assert_eq!(recover_sequencer(&header).unwrap(), signer.address());
+}
+
+/// Test the DA info system call input layout
+#[test]
+fn test_da_info_system_call_input() {
+    let da_info = DaBlockInfo::new(7, B256::repeat_byte(0x11), 99);
+    let input = da_info.system_call_input();
+
+    assert_eq!(input.len(), 96);
+    assert_eq!(input[31], 7);
+    assert!(input[..31].iter().all(|b| *b == 0));
+    assert_eq!(&input[32..64], B256::repeat_byte(0x11).as_slice());
+    assert_eq!(input[95], 99);
+}
+
+/// Test DA block info is carried by the payload attributes and serialized
+#[test]
+fn test_payload_attributes_with_da_info() {
+    let da_info = DaBlockInfo::new(10, B256::random(), 1234567880);
+    let attrs = RollkitPayloadAttributes::new(
+        vec![],
+        Some(1000000),
+        1234567890,
+        B256::random(),
+        Address::random(),
+        B256::random(),
+        1,
+    );
+    assert_eq!(attrs.da_info, None);
+
+    let attrs = attrs.with_da_info(da_info);
+    assert_eq!(attrs.da_info, Some(da_info));
+
+    let serialized = serde_json::to_string(&attrs).unwrap();
+    let deserialized: RollkitPayloadAttributes = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.da_info, Some(da_info));
+}
+
+/// Test predeploys are injected into genesis without overriding existing allocations
+#[test]
+fn test_insert_predeploys() {
+    let mut genesis = Genesis::default();
+    insert_predeploys(&mut genesis);
+
+    let account = genesis
+        .alloc
+        .get(&DA_INFO_PREDEPLOY_ADDRESS)
+        .expect("DA info predeploy should be allocated");
+    assert_eq!(account.code.as_ref(), Some(&DA_INFO_PREDEPLOY_CODE));
+    let account = genesis
+        .alloc
+        .get(&BLOCK_TIME_PREDEPLOY_ADDRESS)
+        .expect("block time predeploy should be allocated");
+    assert_eq!(account.code.as_ref(), Some(&BLOCK_TIME_PREDEPLOY_CODE));
+
+    // Existing allocations are kept
+    let mut genesis = Genesis::default();
+    genesis
+        .alloc
+        .insert(DA_INFO_PREDEPLOY_ADDRESS, Default::default());
+    insert_predeploys(&mut genesis);
+    assert!(genesis.alloc[&DA_INFO_PREDEPLOY_ADDRESS].code.is_none());
+}
+
+/// Test the default genesis is a valid Evolve chain spec
+#[test]
+fn test_default_genesis() {
+    let genesis = GenesisConfig::default().build().unwrap();
+
+    assert_eq!(genesis.config.chain_id, ev_common::DEFAULT_CHAIN_ID);
+    assert_eq!(genesis.gas_limit, 30_000_000);
+    assert!(genesis.alloc.contains_key(&DA_INFO_PREDEPLOY_ADDRESS));
+
+    let chain_spec = ChainSpec::from(genesis);
+    assert!(chain_spec.is_cancun_active_at_timestamp(0));
+    assert!(!chain_spec.is_prague_active_at_timestamp(0));
+}
+
+/// Test genesis accounts from explicit allocations and a mnemonic
+#[test]
+fn test_genesis_accounts() {
+    let funded = Address::repeat_byte(0x42);
+    let config = GenesisConfig {
+        accounts: vec![PrefundedAccount {
+            address: funded,
+            balance: U256::from(1000),
+        }],
+        mnemonic: Some(MnemonicAccounts {
+            phrase: "test test test test test test test test test test test junk".to_string(),
+            count: 2,
+            balance: DEFAULT_MNEMONIC_BALANCE,
+        }),
+        predeploys: false,
+        ..Default::default()
+    };
+
+    let genesis = config.build().unwrap();
+    assert_eq!(genesis.alloc.len(), 3);
+    assert_eq!(genesis.alloc[&funded].balance, U256::from(1000));
+    assert_eq!(
+        genesis.alloc[&address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")].balance,
+        DEFAULT_MNEMONIC_BALANCE
+    );
+    assert!(!genesis.alloc.contains_key(&DA_INFO_PREDEPLOY_ADDRESS));
+
+    // Allocating the same account twice is rejected
+    let mut config = config;
+    config.accounts.push(PrefundedAccount {
+        address: funded,
+        balance: U256::from(1),
+    });
+    assert!(matches!(
+        config.build(),
+        Err(GenesisError::DuplicateAccount(address)) if address == funded
+    ));
+}
+
+/// Test out of order fork schedules are rejected
+#[test]
+fn test_genesis_fork_schedule_validation() {
+    let config = GenesisConfig {
+        forks: ForkSchedule {
+            shanghai_time: Some(10),
+            cancun_time: Some(5),
+            prague_time: None,
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.build(),
+        Err(GenesisError::InvalidForkSchedule(_))
+    ));
+
+    let config = GenesisConfig {
+        forks: ForkSchedule {
+            shanghai_time: Some(0),
+            cancun_time: None,
+            prague_time: Some(0),
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.build(),
+        Err(GenesisError::InvalidForkSchedule(_))
+    ));
+}
+
+/// Test genesis configuration parsed from TOML-like input keeps Evolve parameters
+#[test]
+fn test_genesis_evolve_parameters() {
+    let config: GenesisConfig = serde_json::from_value(serde_json::json!({
+        "chain_id": 1234,
+        "forks": { "prague_time": 100 },
+        "evolve": { "foo": 1 }
+    }))
+    .unwrap();
+
+    let genesis = config.build().unwrap();
+    assert_eq!(genesis.config.chain_id, 1234);
+    assert_eq!(genesis.config.prague_time, Some(100));
+    assert_eq!(
+        genesis.config.extra_fields.get(EVOLVE_CONFIG_KEY),
+        Some(&serde_json::json!({ "foo": 1 }))
+    );
+}
+
+/// Test parsing Evolve parameters and scheduling sequencer keys
+#[test]
+fn test_evolve_chain_config() {
+    let first = address!("0x0000000000000000000000000000000000000001");
+    let second = address!("0x0000000000000000000000000000000000000002");
+    let config = EvolveChainConfig::from_value(&serde_json::json!({
+        "sequencers": [
+            { "activationHeight": 1, "address": first },
+            { "activationHeight": 100, "address": second }
+        ],
+        "unknown": true
+    }))
+    .unwrap();
+    assert_eq!(
+        config.sequencers,
+        vec![
+            SequencerKey {
+                activation_height: 1,
+                address: first
+            },
+            SequencerKey {
+                activation_height: 100,
+                address: second
+            }
+        ]
+    );
+
+    assert_eq!(config.proposer_at(0), None);
+    assert_eq!(config.proposer_at(1), Some(first));
+    assert_eq!(config.proposer_at(99), Some(first));
+    assert_eq!(config.proposer_at(100), Some(second));
+    assert_eq!(config.proposer_at(u64::MAX), Some(second));
+
+    // Missing parameters default to no sequencer
+    assert_eq!(
+        EvolveChainConfig::from_genesis(&Genesis::default()).unwrap(),
+        EvolveChainConfig::default()
+    );
+
+    // Activation heights must increase
+    let out_of_order = serde_json::json!({
+        "sequencers": [
+            { "activationHeight": 5, "address": first },
+            { "activationHeight": 5, "address": second }
+        ]
+    });
+    assert!(EvolveChainConfig::from_value(&out_of_order).is_err());
+
+    // A rotation needs proposers and must activate after the static keys
+    let invalid_rotations = [
+        serde_json::json!({ "activationHeight": 1, "proposers": [] }),
+        serde_json::json!({ "activationHeight": 1, "proposers": [first], "blocksPerProposer": 0 }),
+    ];
+    for rotation in invalid_rotations {
+        let config = serde_json::json!({ "proposerRotation": rotation });
+        assert!(EvolveChainConfig::from_value(&config).is_err());
+    }
+    let early_rotation = serde_json::json!({
+        "sequencers": [{ "activationHeight": 5, "address": first }],
+        "proposerRotation": { "activationHeight": 5, "proposers": [second] }
+    });
+    assert!(EvolveChainConfig::from_value(&early_rotation).is_err());
+
+    let config = GenesisConfig {
+        evolve: Some(out_of_order),
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.build(),
+        Err(GenesisError::InvalidGenesis(_))
+    ));
+}
+
+/// Test the timestamp rules of the chain spec
+#[test]
+fn test_timestamp_rules() {
+    let config = EvolveChainConfig::from_value(&serde_json::json!({
+        "timestampRules": { "maxFutureDrift": 10, "maxEqualTimestampRun": 2, "maxTimestampJump": 60 }
+    }))
+    .unwrap();
+    let rules = config.timestamp_rules;
+    assert_eq!(
+        rules,
+        TimestampRules {
+            activation_height: 0,
+            max_future_drift: Some(10),
+            max_equal_timestamp_run: Some(2),
+            max_timestamp_jump: Some(60),
+        }
+    );
+
+    assert!(rules.validate_drift(1010, 1000).is_ok());
+    assert_eq!(
+        rules.validate_drift(1011, 1000),
+        Err(TimestampRuleError::FutureDrift {
+            timestamp: 1011,
+            now: 1000,
+            max: 10
+        })
+    );
+
+    let parent = Header {
+        timestamp: 1000,
+        ..Default::default()
+    };
+    assert!(rules.validate_against_parent(1060, &parent, None).is_ok());
+    assert!(matches!(
+        rules.validate_against_parent(1061, &parent, None),
+        Err(TimestampRuleError::TimestampJump { .. })
+    ));
+    assert!(rules.validate_against_parent(1000, &parent, None).is_ok());
+    assert!(rules
+        .validate_against_parent(1000, &parent, Some(1))
+        .is_ok());
+    assert_eq!(
+        rules.validate_against_parent(1000, &parent, Some(0)),
+        Err(TimestampRuleError::TimestampRunMismatch {
+            expected: 1,
+            recorded: 0
+        })
+    );
+
+    let parent = Header {
+        timestamp: 1000,
+        extra_data: EvolveExtraData {
+            timestamp_run: 1,
+            ..Default::default()
+        }
+        .encode(),
+        ..Default::default()
+    };
+    assert_eq!(
+        rules.validate_against_parent(1000, &parent, None),
+        Err(TimestampRuleError::EqualTimestampRun {
+            timestamp: 1000,
+            blocks: 3,
+            max: 2
+        })
+    );
+
+    assert_eq!(rules.timestamp_run(&parent, 1000), 2);
+    assert_eq!(rules.timestamp_run(&parent, 1001), 0);
+
+    // Blocks below the activation height are neither checked nor record their run
+    let later = TimestampRules {
+        activation_height: 2,
+        ..rules
+    };
+    assert!(later.validate_against_parent(1000, &parent, None).is_ok());
+    assert!(later
+        .validate_against_parent(u64::MAX, &parent, Some(0))
+        .is_ok());
+    assert_eq!(later.timestamp_run(&parent, 1000), 0);
+    let active_parent = Header {
+        number: 1,
+        ..parent.clone()
+    };
+    assert!(matches!(
+        later.validate_against_parent(1000, &active_parent, None),
+        Err(TimestampRuleError::EqualTimestampRun { .. })
+    ));
+    assert_eq!(later.timestamp_run(&active_parent, 1000), 2);
+
+    // Unset rules are unbounded, and blocks do not record their run
+    let unbounded = TimestampRules::default();
+    assert_eq!(unbounded.timestamp_run(&parent, 1000), 0);
+    assert!(unbounded.validate_drift(u64::MAX, 0).is_ok());
+    assert!(unbounded
+        .validate_against_parent(u64::MAX, &parent, Some(0))
+        .is_ok());
+
+    let zero_run = serde_json::json!({ "timestampRules": { "maxEqualTimestampRun": 0 } });
+    assert!(EvolveChainConfig::from_value(&zero_run).is_err());
+}
+
+/// Test the contract deployment allow-list
+#[test]
+fn test_deploy_allowlist() {
+    let deployer = address!("0x944fDcD1c868E3cC566C78023CcB38A32cDA836E");
+    let governance = address!("0x0000000000000000000000000000000000000c0d");
+    let config = EvolveChainConfig::from_value(&serde_json::json!({
+        "deployAllowlist": {
+            "activationHeight": 10,
+            "deployers": [deployer],
+            "governanceContract": governance
+        }
+    }))
+    .unwrap();
+    let allowlist = config.deploy_allowlist.unwrap();
+    assert_eq!(allowlist.activation_height, 10);
+    assert_eq!(allowlist.governance_contract, Some(governance));
+
+    let no_storage = |_: Address, _: B256| -> Result<U256, ()> { Ok(U256::ZERO) };
+    let other = Address::repeat_byte(0x42);
+    // Everyone deploys before activation, only listed origins after
+    assert_eq!(allowlist.allows(9, other, no_storage), Ok(true));
+    assert_eq!(allowlist.allows(10, deployer, no_storage), Ok(true));
+    assert_eq!(allowlist.allows(10, other, no_storage), Ok(false));
+
+    // The governance contract lists further origins in a mapping at slot 0
+    let slot = DeployAllowlist::governance_slot(other);
+    let governed = |contract: Address, read: B256| -> Result<U256, ()> {
+        assert_eq!(contract, governance);
+        Ok(U256::from((read == slot) as u8))
+    };
+    assert_eq!(allowlist.allows(10, other, governed), Ok(true));
+    assert_eq!(
+        allowlist.allows(10, Address::repeat_byte(0x43), governed),
+        Ok(false)
+    );
+    assert_eq!(allowlist.allows(10, other, |_, _| Err(())), Err(()));
+
+    // Without a governance contract only the static list counts
+    let static_only = DeployAllowlist {
+        governance_contract: None,
+        ..allowlist
+    };
+    assert_eq!(static_only.allows(10, other, governed), Ok(false));
+}
+
+/// Test zero-fee chains pin the genesis base fee at zero
+#[test]
+fn test_zero_fee_genesis() {
+    let config = EvolveChainConfig::from_value(&serde_json::json!({ "zeroFee": true })).unwrap();
+    assert!(config.zero_fee);
+    assert!(!EvolveChainConfig::default().zero_fee);
+
+    let config = GenesisConfig {
+        evolve: Some(serde_json::json!({ "zeroFee": true })),
+        ..Default::default()
+    };
+    assert_eq!(config.build().unwrap().base_fee_per_gas, Some(0));
+
+    let explicit = GenesisConfig {
+        base_fee_per_gas: Some(0),
+        ..config.clone()
+    };
+    assert_eq!(explicit.build().unwrap().base_fee_per_gas, Some(0));
+
+    let priced = GenesisConfig {
+        base_fee_per_gas: Some(7),
+        ..config
+    };
+    assert!(matches!(
+        priced.build(),
+        Err(GenesisError::InvalidGenesis(_))
+    ));
+}
+
+/// Test the maximum block size leaves room for transactions
+#[test]
+fn test_max_block_bytes() {
+    let config =
+        EvolveChainConfig::from_value(&serde_json::json!({ "maxBlockBytes": 1_000_000 })).unwrap();
+    assert_eq!(config.max_block_bytes, Some(1_000_000));
+    assert_eq!(EvolveChainConfig::default().max_block_bytes, None);
+
+    let no_room = serde_json::json!({ "maxBlockBytes": BLOCK_OVERHEAD_BYTES });
+    assert!(EvolveChainConfig::from_value(&no_room).is_err());
+}
+
+/// Test publishing and pruning preconfirmations
+#[test]
+fn test_preconfirmations() {
+    let preconfirmation = |block_number: u64, hash: B256| Preconfirmation {
+        status: PreconfirmationStatus::Preconfirmed,
+        transaction_hash: hash,
+        transaction_index: 0,
+        block_number,
+        parent_hash: B256::ZERO,
+        from: Address::repeat_byte(0x01),
+        to: None,
+        success: true,
+        gas_used: 21_000,
+        cumulative_gas_used: 21_000,
+        logs: Vec::new(),
+    };
+    let preconfirmations = Preconfirmations::new(16);
+    let mut subscriber = preconfirmations.subscribe();
+
+    let first = preconfirmation(1, B256::repeat_byte(0x01));
+    preconfirmations.publish(first.clone());
+    assert_eq!(subscriber.try_recv().unwrap(), first);
+    assert_eq!(
+        preconfirmations.get(&first.transaction_hash),
+        Some(first.clone())
+    );
+
+    let json = serde_json::to_value(&first).unwrap();
+    assert_eq!(json["status"], "preconfirmed");
+    assert_eq!(
+        json["transactionHash"],
+        serde_json::json!(first.transaction_hash)
+    );
+
+    // Building the next block drops the preconfirmations of sealed blocks
+    preconfirmations.start_block(2, B256::ZERO, &HashSet::new());
+    assert_eq!(preconfirmations.get(&first.transaction_hash), None);
+
+    let second = preconfirmation(2, B256::repeat_byte(0x02));
+    preconfirmations.publish(second.clone());
+    assert_eq!(subscriber.try_recv().unwrap(), second);
+
+    // Rebuilding the same payload keeps the preconfirmation and does not publish it again
+    let transactions = HashSet::from([second.transaction_hash]);
+    preconfirmations.start_block(2, B256::ZERO, &transactions);
+    preconfirmations.publish(second.clone());
+    assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(
+        preconfirmations.get(&second.transaction_hash),
+        Some(second.clone())
+    );
+
+    // A rebuild changing the outcome publishes the new preconfirmation
+    let moved = Preconfirmation {
+        transaction_index: 1,
+        ..second.clone()
+    };
+    preconfirmations.publish(moved.clone());
+    assert_eq!(subscriber.try_recv().unwrap(), moved);
+
+    // Building the same height on another parent, or without the transaction, drops it
+    preconfirmations.start_block(2, B256::repeat_byte(0xff), &transactions);
+    assert_eq!(preconfirmations.get(&second.transaction_hash), None);
+    preconfirmations.publish(second.clone());
+    preconfirmations.start_block(2, B256::ZERO, &HashSet::new());
+    assert_eq!(preconfirmations.get(&second.transaction_hash), None);
+
+    // Sealing a height drops its preconfirmations even if no higher block is built
+    preconfirmations.publish(second.clone());
+    preconfirmations.seal_block(1);
+    assert_eq!(
+        preconfirmations.get(&second.transaction_hash),
+        Some(second.clone())
+    );
+    preconfirmations.seal_block(2);
+    assert_eq!(preconfirmations.get(&second.transaction_hash), None);
+}
+
+/// Test round-robin proposer turns
+#[test]
+fn test_proposer_rotation() {
+    let proposers = [
+        address!("0x0000000000000000000000000000000000000001"),
+        address!("0x0000000000000000000000000000000000000002"),
+        address!("0x0000000000000000000000000000000000000003"),
+    ];
+    let config = EvolveChainConfig::from_value(&serde_json::json!({
+        "proposerRotation": { "activationHeight": 100, "proposers": proposers }
+    }))
+    .unwrap();
+    assert_eq!(
+        config.proposer_rotation,
+        Some(ProposerRotation {
+            activation_height: 100,
+            proposers: proposers.to_vec(),
+            blocks_per_proposer: 1,
+        })
+    );
+    assert!(config.has_proposer_schedule());
+    assert!(config.is_scheduled(proposers[2]));
+    assert!(!config.is_scheduled(Address::ZERO));
+
+    assert_eq!(config.proposer_at(99), None);
+    assert_eq!(config.proposer_at(100), Some(proposers[0]));
+    assert_eq!(config.proposer_at(101), Some(proposers[1]));
+    assert_eq!(config.proposer_at(102), Some(proposers[2]));
+    assert_eq!(config.proposer_at(103), Some(proposers[0]));
+    assert_eq!(
+        config.proposer_at(u64::MAX),
+        Some(proposers[(u64::MAX - 100) as usize % 3])
+    );
+    assert!(!EvolveChainConfig::default().has_proposer_schedule());
+
+    // Sets that validation rejects schedule no one instead of dividing by zero
+    let empty = ProposerRotation {
+        activation_height: 100,
+        proposers: Vec::new(),
+        blocks_per_proposer: 1,
+    };
+    assert_eq!(empty.proposer_at(100), None);
+    let no_blocks = ProposerRotation {
+        activation_height: 100,
+        proposers: proposers.to_vec(),
+        blocks_per_proposer: 0,
+    };
+    assert_eq!(no_blocks.proposer_at(100), None);
+    assert_eq!(no_blocks.proposer_at(u64::MAX), None);
+}
+
+/// Test the JSON shape of the finality status returned by `evolve_getFinal`
+#[test]
+fn test_finality_status_serde() {
+    let status = FinalityStatus {
+        head: BlockNumHash::new(10, B256::repeat_byte(0x0a)),
+        safe: Some(BlockNumHash::new(8, B256::repeat_byte(0x08))),
+        finalized: None,
+    };
+
+    let json = serde_json::to_value(status).unwrap();
+    assert!(json["head"]["hash"].is_string());
+    assert!(json["safe"]["hash"].is_string());
+    assert!(json["finalized"].is_null());
+
+    let decoded: FinalityStatus = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, status);
+}
+
+/// Test that invalid finality updates are reported as invalid params
+#[test]
+fn test_finality_error_codes() {
+    let err = ErrorObjectOwned::from(FinalityError::Backwards {
+        kind: "finalized",
+        number: 3,
+        current: 5,
+    });
+    assert_eq!(err.code(), INVALID_PARAMS_CODE);
+    assert_eq!(
+        err.message(),
+        "finalized block 3 is below the current finalized block 5"
+    );
+
+    let err = ErrorObjectOwned::from(FinalityError::FinalizedAfterSafe {
+        finalized: 7,
+        safe: 6,
+    });
+    assert_eq!(err.code(), INVALID_PARAMS_CODE);
+}
+
+/// Test that rollbacks below the finalized block are reported as invalid params
+#[test]
+fn test_rollback_error_codes() {
+    let err = ErrorObjectOwned::from(RollbackError::BelowFinalized {
+        height: 3,
+        finalized: 5,
+    });
+    assert_eq!(err.code(), INVALID_PARAMS_CODE);
+    assert_eq!(
+        err.message(),
+        "rollback target 3 is below the finalized block 5"
+    );
+
+    let err = ErrorObjectOwned::from(RollbackError::Engine("syncing".to_string()));
+    assert_eq!(err.code(), INTERNAL_ERROR_CODE);
+}
+
+fn test_da_inclusion(da_height: u64) -> DaInclusion {
+    DaInclusion {
+        da_height,
+        namespace: bytes!("00000000000000000000000000000000000000000000657672657468"),
+        commitment: Bytes::from(vec![da_height as u8; 32]),
+    }
+}
+
+/// Test that DA inclusion entries survive the database encoding
+#[test]
+fn test_da_inclusion_entry_encoding() {
+    let entry = DaInclusionEntry {
+        block_number: 12,
+        block_hash: B256::repeat_byte(0x12),
+        inclusion: test_da_inclusion(100),
+    };
+
+    let encoded = entry.clone().compress();
+    let decoded = DaInclusionEntry::decompress(&encoded).unwrap();
+    // The block number is stored as the table key
+    assert_eq!(decoded.block_number, 0);
+    assert_eq!(decoded.block_hash, entry.block_hash);
+    assert_eq!(decoded.inclusion, entry.inclusion);
+
+    assert!(DaInclusionEntry::decompress(&encoded[..20]).is_err());
+}
+
+/// Test DA inclusion validation
+#[test]
+fn test_da_inclusion_validation() {
+    assert!(test_da_inclusion(1).validate().is_ok());
+
+    let mut inclusion = test_da_inclusion(1);
+    inclusion.commitment = Bytes::new();
+    assert!(matches!(
+        inclusion.validate(),
+        Err(DaInclusionError::InvalidCommitment(0))
+    ));
+
+    let mut inclusion = test_da_inclusion(1);
+    inclusion.namespace = Bytes::from(vec![0u8; 65]);
+    assert!(matches!(
+        inclusion.validate(),
+        Err(DaInclusionError::InvalidNamespace(65))
+    ));
+}
+
+/// Test storing, querying and unwinding DA inclusions
+#[test]
+fn test_da_inclusion_store() {
+    let db = create_test_rw_db();
+    db.db().create_tables_for::<EvolveTables>().unwrap();
+    let store = DaInclusionStore::new(db);
+
+    for number in 1..=5 {
+        let block = BlockNumHash::new(number, B256::repeat_byte(number as u8));
+        store
+            .insert(block, test_da_inclusion(100 + number))
+            .unwrap();
+    }
+
+    let entry = store.get(3).unwrap().unwrap();
+    assert_eq!(entry.block(), BlockNumHash::new(3, B256::repeat_byte(3)));
+    assert_eq!(entry.inclusion.da_height, 103);
+    assert!(store.get(6).unwrap().is_none());
+
+    let entries = store.range(2, 4).unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.block_number)
+            .collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+    assert!(matches!(
+        store.range(4, 2),
+        Err(DaInclusionError::InvalidRange { .. })
+    ));
+
+    // Only entries describing the reorged blocks are removed
+    let removed = store
+        .unwind_blocks([
+            BlockNumHash::new(4, B256::repeat_byte(4)),
+            BlockNumHash::new(5, B256::repeat_byte(0xff)),
+        ])
+        .unwrap();
+    assert_eq!(removed, 1);
+    assert!(store.get(4).unwrap().is_none());
+    assert!(store.get(5).unwrap().is_some());
+
+    // Rolling back removes every entry above the target, whatever block it describes
+    assert_eq!(store.unwind_above(2).unwrap(), 2);
+    assert!(store.get(2).unwrap().is_some());
+    assert!(store.get(3).unwrap().is_none());
+    assert!(store.get(5).unwrap().is_none());
+    assert_eq!(store.unwind_above(2).unwrap(), 0);
+}
+
+/// Test recording, encoding and storing the attributes of a built block
+#[test]
+fn test_build_attributes_store() {
+    let transaction = TransactionSigned::new_unhashed(
+        Transaction::Legacy(TxLegacy {
+            chain_id: Some(1234),
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::with_last_byte(1)),
+            ..Default::default()
+        }),
+        Signature::test_signature(),
+    );
+    let da_info = DaBlockInfo::new(42, B256::repeat_byte(0xda), 1_700_000_000);
+    let attributes = RollkitPayloadAttributes::new(
+        vec![transaction.clone()],
+        Some(30_000_000),
+        1_700_000_012,
+        B256::repeat_byte(0x0e),
+        Address::with_last_byte(0xfe),
+        B256::repeat_byte(0x01),
+        2,
+    )
+    .with_da_info(da_info)
+    .with_timestamp_millis(1_700_000_012_500);
+    let excluded = vec![ExcludedTransaction {
+        hash: *transaction.hash(),
+        reason: "effective gas price 0 is below the minimum of 1".to_string(),
+    }];
+    let record = BuildAttributes::new(&attributes, B256::repeat_byte(0x02), excluded);
+    assert_eq!(record.block_number, 2);
+    assert_eq!(record.transactions.len(), 1);
+
+    // The recorded attributes decode back to the original ones
+    let decoded = record.payload_attributes().unwrap();
+    assert_eq!(decoded.transactions, attributes.transactions);
+    assert_eq!(decoded.gas_limit, attributes.gas_limit);
+    assert_eq!(decoded.timestamp, attributes.timestamp);
+    assert_eq!(decoded.timestamp_millis, attributes.timestamp_millis);
+    assert_eq!(decoded.prev_randao, attributes.prev_randao);
+    assert_eq!(
+        decoded.suggested_fee_recipient,
+        attributes.suggested_fee_recipient
+    );
+    assert_eq!(decoded.parent_hash, attributes.parent_hash);
+    assert_eq!(decoded.block_number, attributes.block_number);
+    assert_eq!(decoded.da_info, Some(da_info));
+
+    let compressed = record.clone().compress();
+    assert_eq!(BuildAttributes::decompress(&compressed).unwrap(), record);
+
+    let db = create_test_rw_db();
+    db.db().create_tables_for::<EvolveTables>().unwrap();
+    let store = BuildAttributesStore::new(db);
+    let (recorder, mut records) = BuildAttributesRecorder::channel();
+    recorder.record(record.clone());
+    store.insert(&records.try_recv().unwrap()).unwrap();
+
+    assert_eq!(store.get(record.block_hash).unwrap(), Some(record));
+    assert!(store.get(B256::repeat_byte(0x03)).unwrap().is_none());
+}
+
+/// Test that only records of canonical blocks are stored, and that records of blocks leaving
+/// the canonical chain are removed
+#[test]
+fn test_build_attributes_store_keeps_canonical_blocks() {
+    let record = |number: u64, hash: u8| {
+        let attributes = RollkitPayloadAttributes::new(
+            Vec::new(),
+            Some(30_000_000),
+            1_700_000_000 + number,
+            B256::ZERO,
+            Address::ZERO,
+            B256::repeat_byte(hash.wrapping_sub(1)),
+            number,
+        );
+        BuildAttributes::new(&attributes, B256::repeat_byte(hash), Vec::new())
+    };
+    let block = |number: u64, hash: u8| BlockNumHash::new(number, B256::repeat_byte(hash));
+
+    let db = create_test_rw_db();
+    db.db().create_tables_for::<EvolveTables>().unwrap();
+    let store = BuildAttributesStore::new(db);
+
+    // Two payloads built at height 1, one at height 2 and one at height 3
+    let mut pending: HashMap<_, _> = [
+        record(1, 0x11),
+        record(1, 0x12),
+        record(2, 0x21),
+        record(3, 0x31),
+    ]
+    .into_iter()
+    .map(|record| (record.block_hash, record))
+    .collect();
+
+    // Only the canonical payloads are stored, the other one at their heights is dropped
+    let stored = store
+        .store_canonical(&mut pending, [block(1, 0x11), block(2, 0x21)])
+        .unwrap();
+    assert_eq!(stored, 2);
+    assert!(store.get(B256::repeat_byte(0x11)).unwrap().is_some());
+    assert!(store.get(B256::repeat_byte(0x12)).unwrap().is_none());
+    assert!(store.get(B256::repeat_byte(0x21)).unwrap().is_some());
+    assert_eq!(
+        pending.keys().collect::<Vec<_>>(),
+        vec![&B256::repeat_byte(0x31)]
+    );
+
+    store
+        .store_canonical(&mut pending, [block(3, 0x31)])
+        .unwrap();
+    assert!(pending.is_empty());
+
+    // Reorged blocks are removed, whatever else is recorded
+    let removed = store
+        .unwind_blocks([block(3, 0x31), block(3, 0x32)])
+        .unwrap();
+    assert_eq!(removed, 1);
+    assert!(store.get(B256::repeat_byte(0x31)).unwrap().is_none());
+
+    // Rolling back removes every record above the target
+    assert_eq!(store.unwind_above(1).unwrap(), 1);
+    assert!(store.get(B256::repeat_byte(0x11)).unwrap().is_some());
+    assert!(store.get(B256::repeat_byte(0x21)).unwrap().is_none());
+    assert_eq!(store.unwind_above(1).unwrap(), 0);
+}
@@ -1,7 +1,259 @@
-use alloy_primitives::{Address, B256};
+use alloy_consensus::Header;
+use alloy_primitives::{Address, Bytes, Signature, B256};
 use reth_primitives::TransactionSigned;
 use serde::{Deserialize, Serialize};
 
+/// Version byte prefixing a DA block-info header extension in `extra_data`
+pub const DA_INFO_EXTRA_DATA_VERSION: u8 = 0x01;
+
+/// Length of a version 1 DA block-info header extension: version, DA height,
+/// DA timestamp and DA block hash
+pub const DA_INFO_EXTRA_DATA_LEN: usize = 1 + 8 + 8 + 32;
+
+/// Version byte prefixing a flagged Evolve header extension in `extra_data`.
+///
+/// A version 2 extension is the version byte, a flags byte, then each present field in flag
+/// order: the DA block info (DA height, DA timestamp and DA block hash), the sequencer
+/// signature, the equal-timestamp run and the millisecond timestamp (both big-endian `u64`).
+pub const EVOLVE_EXTRA_DATA_VERSION: u8 = 0x02;
+
+/// Flag of a version 2 extension carrying DA block info
+const EXTRA_DATA_FLAG_DA_INFO: u8 = 0x01;
+
+/// Flag of a version 2 extension carrying a sequencer signature
+const EXTRA_DATA_FLAG_SIGNATURE: u8 = 0x02;
+
+/// Flag of a version 2 extension carrying an equal-timestamp run
+const EXTRA_DATA_FLAG_TIMESTAMP_RUN: u8 = 0x04;
+
+/// Flag of a version 2 extension carrying a millisecond timestamp
+const EXTRA_DATA_FLAG_TIMESTAMP_MILLIS: u8 = 0x08;
+
+/// Flags understood by this version of the extension
+const EXTRA_DATA_KNOWN_FLAGS: u8 = EXTRA_DATA_FLAG_DA_INFO
+    | EXTRA_DATA_FLAG_SIGNATURE
+    | EXTRA_DATA_FLAG_TIMESTAMP_RUN
+    | EXTRA_DATA_FLAG_TIMESTAMP_MILLIS;
+
+/// Length of the DA block-info fields of a header extension
+const DA_INFO_FIELDS_LEN: usize = 8 + 8 + 32;
+
+/// Length of a sequencer signature in a header extension: `r`, `s` and `v`
+pub const SEQUENCER_SIGNATURE_LEN: usize = 65;
+
+/// Information about the DA layer block that an Evolve payload is derived from.
+///
+/// When provided with the payload attributes it is written into the DA info predeploy
+/// before any transaction of the block executes, and recorded in the block header so
+/// that followers replay the same system call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaBlockInfo {
+    /// Height of the latest DA layer block
+    pub da_height: u64,
+    /// Hash of the latest DA layer block
+    pub da_block_hash: B256,
+    /// Timestamp of the latest DA layer block, as reported by the settlement layer
+    pub da_timestamp: u64,
+}
+
+impl DaBlockInfo {
+    /// Creates a new instance of `DaBlockInfo`
+    pub const fn new(da_height: u64, da_block_hash: B256, da_timestamp: u64) -> Self {
+        Self {
+            da_height,
+            da_block_hash,
+            da_timestamp,
+        }
+    }
+
+    /// Returns the calldata of the system call updating the DA info predeploy.
+    ///
+    /// The layout is three 32-byte words: DA height, DA block hash and DA timestamp.
+    pub fn system_call_input(&self) -> Bytes {
+        let mut input = Vec::with_capacity(96);
+        input.extend_from_slice(&B256::left_padding_from(&self.da_height.to_be_bytes()).0);
+        input.extend_from_slice(self.da_block_hash.as_slice());
+        input.extend_from_slice(&B256::left_padding_from(&self.da_timestamp.to_be_bytes()).0);
+        input.into()
+    }
+
+    /// Encodes the DA block info as a versioned header `extra_data` extension
+    pub fn encode_extra_data(&self) -> Bytes {
+        let mut out = Vec::with_capacity(DA_INFO_EXTRA_DATA_LEN);
+        out.push(DA_INFO_EXTRA_DATA_VERSION);
+        self.encode_fields(&mut out);
+        out.into()
+    }
+
+    /// Decodes the DA block info from a header's `extra_data`.
+    ///
+    /// Returns `None` if the extra data does not carry a DA block-info extension.
+    pub fn from_extra_data(extra_data: &[u8]) -> Option<Self> {
+        EvolveExtraData::decode(extra_data)?.da_info
+    }
+
+    fn encode_fields(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.da_height.to_be_bytes());
+        out.extend_from_slice(&self.da_timestamp.to_be_bytes());
+        out.extend_from_slice(self.da_block_hash.as_slice());
+    }
+
+    fn decode_fields(fields: &[u8]) -> Option<Self> {
+        if fields.len() != DA_INFO_FIELDS_LEN {
+            return None;
+        }
+
+        let da_height = u64::from_be_bytes(fields[..8].try_into().ok()?);
+        let da_timestamp = u64::from_be_bytes(fields[8..16].try_into().ok()?);
+        let da_block_hash = B256::from_slice(&fields[16..]);
+        Some(Self::new(da_height, da_block_hash, da_timestamp))
+    }
+}
+
+/// Evolve header extension carried in a block's `extra_data`.
+///
+/// Blocks carrying only DA block info use the version 1 encoding, so their headers are
+/// unchanged; any other combination of fields uses the flagged version 2 encoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvolveExtraData {
+    /// DA layer block info applied by the block
+    pub da_info: Option<DaBlockInfo>,
+    /// Sequencer signature over the block's seal hash
+    pub signature: Option<Signature>,
+    /// Number of consecutive ancestors sharing the block's timestamp
+    pub timestamp_run: u64,
+    /// Block timestamp in milliseconds, refining the header's seconds timestamp
+    pub timestamp_millis: Option<u64>,
+}
+
+impl EvolveExtraData {
+    /// Creates an extension carrying the given DA block info and no other field
+    pub const fn new(da_info: Option<DaBlockInfo>) -> Self {
+        Self {
+            da_info,
+            signature: None,
+            timestamp_run: 0,
+            timestamp_millis: None,
+        }
+    }
+
+    /// Returns the extension without its signature, as covered by the seal hash
+    pub const fn without_signature(self) -> Self {
+        Self {
+            signature: None,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the extension carries no field
+    pub const fn is_empty(&self) -> bool {
+        self.da_info.is_none()
+            && self.signature.is_none()
+            && self.timestamp_run == 0
+            && self.timestamp_millis.is_none()
+    }
+
+    /// Returns the millisecond timestamp of a block: the recorded one if any, the start of
+    /// its second otherwise
+    pub fn timestamp_millis_of(header: &Header) -> u64 {
+        Self::decode(&header.extra_data)
+            .and_then(|extension| extension.timestamp_millis)
+            .unwrap_or_else(|| header.timestamp.saturating_mul(1000))
+    }
+
+    /// Returns the equal-timestamp run of a block with the given timestamp on top of
+    /// `parent`.
+    ///
+    /// The run is `0` if the timestamp differs from the parent's, and one more than the
+    /// parent's recorded run otherwise.
+    pub fn next_timestamp_run(parent: &Header, timestamp: u64) -> u64 {
+        if timestamp != parent.timestamp {
+            return 0;
+        }
+        Self::decode(&parent.extra_data)
+            .map_or(0, |extension| extension.timestamp_run)
+            .saturating_add(1)
+    }
+
+    /// Encodes the extension, returning empty extra data if it carries no field
+    pub fn encode(&self) -> Bytes {
+        if self.signature.is_none() && self.timestamp_run == 0 && self.timestamp_millis.is_none() {
+            return self
+                .da_info
+                .map_or_else(Bytes::new, |da_info| da_info.encode_extra_data());
+        }
+
+        let mut flags = 0;
+        let mut out = Vec::with_capacity(2 + DA_INFO_FIELDS_LEN + SEQUENCER_SIGNATURE_LEN + 16);
+        out.extend_from_slice(&[EVOLVE_EXTRA_DATA_VERSION, 0]);
+        if let Some(da_info) = self.da_info {
+            flags |= EXTRA_DATA_FLAG_DA_INFO;
+            da_info.encode_fields(&mut out);
+        }
+        if let Some(signature) = self.signature {
+            flags |= EXTRA_DATA_FLAG_SIGNATURE;
+            out.extend_from_slice(&signature.as_bytes());
+        }
+        if self.timestamp_run != 0 {
+            flags |= EXTRA_DATA_FLAG_TIMESTAMP_RUN;
+            out.extend_from_slice(&self.timestamp_run.to_be_bytes());
+        }
+        if let Some(timestamp_millis) = self.timestamp_millis {
+            flags |= EXTRA_DATA_FLAG_TIMESTAMP_MILLIS;
+            out.extend_from_slice(&timestamp_millis.to_be_bytes());
+        }
+        out[1] = flags;
+        out.into()
+    }
+
+    /// Decodes the extension from a header's `extra_data`.
+    ///
+    /// Returns `None` if the extra data does not carry an Evolve extension.
+    pub fn decode(extra_data: &[u8]) -> Option<Self> {
+        match *extra_data.first()? {
+            DA_INFO_EXTRA_DATA_VERSION => {
+                DaBlockInfo::decode_fields(&extra_data[1..]).map(|da_info| Self::new(Some(da_info)))
+            }
+            EVOLVE_EXTRA_DATA_VERSION => {
+                let flags = *extra_data.get(1)?;
+                if flags & !EXTRA_DATA_KNOWN_FLAGS != 0 {
+                    return None;
+                }
+
+                let mut rest = &extra_data[2..];
+                let mut decoded = Self::default();
+                if flags & EXTRA_DATA_FLAG_DA_INFO != 0 {
+                    let (fields, tail) = rest.split_at_checked(DA_INFO_FIELDS_LEN)?;
+                    decoded.da_info = Some(DaBlockInfo::decode_fields(fields)?);
+                    rest = tail;
+                }
+                if flags & EXTRA_DATA_FLAG_SIGNATURE != 0 {
+                    let (signature, tail) = rest.split_at_checked(SEQUENCER_SIGNATURE_LEN)?;
+                    decoded.signature = Some(Signature::from_raw(signature).ok()?);
+                    rest = tail;
+                }
+                if flags & EXTRA_DATA_FLAG_TIMESTAMP_RUN != 0 {
+                    let (run, tail) = rest.split_at_checked(8)?;
+                    decoded.timestamp_run = u64::from_be_bytes(run.try_into().ok()?);
+                    // A zero run is encoded by leaving the flag unset
+                    if decoded.timestamp_run == 0 {
+                        return None;
+                    }
+                    rest = tail;
+                }
+                if flags & EXTRA_DATA_FLAG_TIMESTAMP_MILLIS != 0 {
+                    let (millis, tail) = rest.split_at_checked(8)?;
+                    decoded.timestamp_millis = Some(u64::from_be_bytes(millis.try_into().ok()?));
+                    rest = tail;
+                }
+                rest.is_empty().then_some(decoded)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Payload attributes for the Rollkit Reth node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RollkitPayloadAttributes {
@@ -19,6 +271,12 @@ pub struct RollkitPayloadAttributes {
     pub parent_hash: B256,
     /// Block number
     pub block_number: u64,
+    /// DA layer block info to write into the DA info predeploy
+    #[serde(default)]
+    pub da_info: Option<DaBlockInfo>,
+    /// Block timestamp in milliseconds, within the second of `timestamp`
+    #[serde(default)]
+    pub timestamp_millis: Option<u64>,
 }
 
 impl RollkitPayloadAttributes {
@@ -40,9 +298,23 @@ impl RollkitPayloadAttributes {
             suggested_fee_recipient,
             parent_hash,
             block_number,
+            da_info: None,
+            timestamp_millis: None,
         }
     }
 
+    /// Sets the DA layer block info for the payload
+    pub const fn with_da_info(mut self, da_info: DaBlockInfo) -> Self {
+        self.da_info = Some(da_info);
+        self
+    }
+
+    /// Sets the millisecond timestamp for the payload
+    pub const fn with_timestamp_millis(mut self, timestamp_millis: u64) -> Self {
+        self.timestamp_millis = Some(timestamp_millis);
+        self
+    }
+
     /// Validates the payload attributes
     pub const fn validate(&self) -> Result<(), PayloadAttributesError> {
         // For rollkit, empty transactions are allowed (empty blocks are valid)
@@ -53,6 +325,15 @@ impl RollkitPayloadAttributes {
             }
         }
 
+        if let Some(timestamp_millis) = self.timestamp_millis {
+            if timestamp_millis / 1000 != self.timestamp {
+                return Err(PayloadAttributesError::InvalidTimestampMillis {
+                    timestamp: self.timestamp,
+                    timestamp_millis,
+                });
+            }
+        }
+
         Ok(())
     }
 }
@@ -78,6 +359,18 @@ pub enum PayloadAttributesError {
     #[error("Invalid gas limit")]
     InvalidGasLimit,
 
+    /// Error when the millisecond timestamp does not match the timestamp
+    ///
+    /// This error occurs when the millisecond timestamp falls outside the
+    /// second given by the seconds timestamp of the payload.
+    #[error("Millisecond timestamp {timestamp_millis} is not within timestamp {timestamp}")]
+    InvalidTimestampMillis {
+        /// Timestamp in seconds
+        timestamp: u64,
+        /// Timestamp in milliseconds
+        timestamp_millis: u64,
+    },
+
     /// Error when transaction validation fails
     ///
     /// This error occurs when individual transactions within the payload
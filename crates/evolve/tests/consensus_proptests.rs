@@ -0,0 +1,501 @@
+//! Property tests for the header rules enforced by `RollkitConsensus`.
+//!
+//! Most headers sit at pre-London mainnet heights and timestamps, where no base fee,
+//! withdrawals or blob gas apply, so each property isolates a single rule. The Evolve
+//! properties run on a London chain spec carrying an Evolve config, with signed headers
+//! carrying the full header extension, and the blob gas property on a Cancun one.
+
+use alloy_consensus::EMPTY_ROOT_HASH;
+use alloy_eips::{eip1559::BaseFeeParams, eip4844::DATA_GAS_PER_BLOB, eip7840::BlobParams};
+use alloy_genesis::Genesis;
+use alloy_primitives::{B256, U256};
+use alloy_signer_local::PrivateKeySigner;
+use evolve_ev_reth::{
+    consensus::RollkitConsensus, genesis::EVOLVE_CONFIG_KEY, sequencer::sign_header, DaBlockInfo,
+    EvolveExtraData,
+};
+use proptest::prelude::*;
+use reth_chainspec::{ChainSpec, MAINNET};
+use reth_consensus::{ConsensusError, HeaderValidator};
+use reth_primitives::{Header, SealedHeader};
+use std::sync::Arc;
+
+/// Minimum gas limit of a block
+const MINIMUM_GAS_LIMIT: u64 = 5_000;
+
+/// Bound divisor of the gas limit change between a block and its parent
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1_024;
+
+/// Last parent height before London on mainnet, keeping children pre-London
+const MAX_PARENT_NUMBER: u64 = 12_964_998;
+
+/// Last timestamp before Shanghai on mainnet
+const MAX_TIMESTAMP: u64 = 1_681_338_454;
+
+fn consensus() -> RollkitConsensus {
+    RollkitConsensus::try_new(MAINNET.clone()).unwrap()
+}
+
+fn parent(number: u64, timestamp: u64, gas_limit: u64) -> SealedHeader {
+    SealedHeader::seal_slow(Header {
+        number,
+        timestamp,
+        gas_limit,
+        ..Default::default()
+    })
+}
+
+/// Returns a valid child of `parent`, sharing its timestamp and gas limit
+fn child(parent: &SealedHeader) -> Header {
+    Header {
+        number: parent.number + 1,
+        parent_hash: parent.hash(),
+        timestamp: parent.timestamp,
+        gas_limit: parent.gas_limit,
+        ..Default::default()
+    }
+}
+
+/// Whether a block may move from the parent's gas limit to `gas_limit`
+const fn gas_limit_allowed(parent_gas_limit: u64, gas_limit: u64) -> bool {
+    let change = parent_gas_limit.abs_diff(gas_limit);
+    change < parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR && gas_limit >= MINIMUM_GAS_LIMIT
+}
+
+/// A parent gas limit and a child gas limit around the allowed bound
+fn gas_limits() -> impl Strategy<Value = (u64, u64)> {
+    (MINIMUM_GAS_LIMIT..1_000_000_000).prop_flat_map(|parent_gas_limit| {
+        let bound = parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR;
+        (
+            Just(parent_gas_limit),
+            parent_gas_limit.saturating_sub(2 * bound)..=parent_gas_limit + 2 * bound,
+        )
+    })
+}
+
+/// Builds a child of `parent` recording the millisecond timestamp `timestamp_millis`
+fn child_with_millis(parent: &SealedHeader, timestamp_millis: u64) -> SealedHeader {
+    let timestamp = timestamp_millis / 1000;
+    let extension = EvolveExtraData {
+        timestamp_run: EvolveExtraData::next_timestamp_run(parent.header(), timestamp),
+        timestamp_millis: Some(timestamp_millis),
+        ..Default::default()
+    };
+    SealedHeader::seal_slow(Header {
+        timestamp,
+        extra_data: extension.encode(),
+        ..child(parent)
+    })
+}
+
+/// Key of the sequencer of the Evolve chain spec
+fn sequencer() -> PrivateKeySigner {
+    PrivateKeySigner::from_bytes(&B256::repeat_byte(0x11)).unwrap()
+}
+
+/// Consensus of a chain spec activating London at genesis, with an Evolve config scheduling
+/// `sequencer()` and optionally pinning the base fee at zero
+fn evolve_consensus(zero_fee: bool) -> RollkitConsensus {
+    RollkitConsensus::try_new(Arc::new(ChainSpec::from(evolve_genesis(zero_fee)))).unwrap()
+}
+
+/// Consensus of `evolve_consensus`, with the merge, Shanghai and Cancun active at genesis too
+fn evolve_cancun_consensus(zero_fee: bool) -> RollkitConsensus {
+    let mut genesis = evolve_genesis(zero_fee);
+    let config = &mut genesis.config;
+    config.merge_netsplit_block = Some(0);
+    config.terminal_total_difficulty = Some(U256::ZERO);
+    config.terminal_total_difficulty_passed = true;
+    config.shanghai_time = Some(0);
+    config.cancun_time = Some(0);
+    RollkitConsensus::try_new(Arc::new(ChainSpec::from(genesis))).unwrap()
+}
+
+/// Genesis of `evolve_consensus`
+fn evolve_genesis(zero_fee: bool) -> Genesis {
+    let mut genesis = Genesis::default();
+    let config = &mut genesis.config;
+    for block in [
+        &mut config.homestead_block,
+        &mut config.eip150_block,
+        &mut config.eip155_block,
+        &mut config.eip158_block,
+        &mut config.byzantium_block,
+        &mut config.constantinople_block,
+        &mut config.petersburg_block,
+        &mut config.istanbul_block,
+        &mut config.berlin_block,
+        &mut config.london_block,
+    ] {
+        *block = Some(0);
+    }
+    config.extra_fields.insert(
+        EVOLVE_CONFIG_KEY.to_string(),
+        serde_json::json!({
+            "zeroFee": zero_fee,
+            "sequencers": [{ "activationHeight": 0, "address": sequencer().address() }],
+        }),
+    );
+    genesis
+}
+
+/// DA inclusion info of a header extension
+fn da_infos() -> impl Strategy<Value = Option<DaBlockInfo>> {
+    proptest::option::of(
+        (any::<u64>(), any::<[u8; 32]>(), any::<u64>())
+            .prop_map(|(height, hash, timestamp)| DaBlockInfo::new(height, hash.into(), timestamp)),
+    )
+}
+
+/// London parent recording the millisecond timestamp `timestamp_millis`
+fn evolve_parent(
+    number: u64,
+    timestamp_millis: u64,
+    gas_limit: u64,
+    gas_used: u64,
+    base_fee: u64,
+) -> SealedHeader {
+    let extension = EvolveExtraData {
+        timestamp_millis: Some(timestamp_millis),
+        ..Default::default()
+    };
+    SealedHeader::seal_slow(Header {
+        number,
+        timestamp: timestamp_millis / 1000,
+        gas_limit,
+        gas_used,
+        base_fee_per_gas: Some(base_fee),
+        extra_data: extension.encode(),
+        ..Default::default()
+    })
+}
+
+/// Child of `parent` carrying a full header extension, signed by `signer`
+fn evolve_child(
+    parent: &SealedHeader,
+    timestamp_millis: u64,
+    base_fee: u64,
+    da_info: Option<DaBlockInfo>,
+    signer: &PrivateKeySigner,
+) -> SealedHeader {
+    let header = Header {
+        base_fee_per_gas: Some(base_fee),
+        ..evolve_child_header(parent, timestamp_millis, da_info)
+    };
+    seal_signed(header, signer)
+}
+
+/// Unsigned child of `parent` carrying a full header extension, with no base fee
+fn evolve_child_header(
+    parent: &SealedHeader,
+    timestamp_millis: u64,
+    da_info: Option<DaBlockInfo>,
+) -> Header {
+    let timestamp = timestamp_millis / 1000;
+    let extension = EvolveExtraData {
+        da_info,
+        timestamp_run: EvolveExtraData::next_timestamp_run(parent.header(), timestamp),
+        timestamp_millis: Some(timestamp_millis),
+        ..Default::default()
+    };
+    Header {
+        timestamp,
+        extra_data: extension.encode(),
+        ..child(parent)
+    }
+}
+
+fn seal_signed(mut header: Header, signer: &PrivateKeySigner) -> SealedHeader {
+    sign_header(&mut header, signer).unwrap();
+    SealedHeader::seal_slow(header)
+}
+
+/// Validates a header on its own and against its parent, as the engine does
+fn validate(
+    consensus: &RollkitConsensus,
+    header: &SealedHeader,
+    parent: &SealedHeader,
+) -> Result<(), ConsensusError> {
+    consensus.validate_header(header)?;
+    consensus.validate_header_against_parent(header, parent)
+}
+
+proptest! {
+    /// A block may share its parent's timestamp or follow it, never precede it
+    #[test]
+    fn timestamps_are_equal_or_later(
+        number in 0..=MAX_PARENT_NUMBER,
+        parent_timestamp in 0..=MAX_TIMESTAMP,
+        timestamp in 0..=MAX_TIMESTAMP,
+    ) {
+        let parent = parent(number, parent_timestamp, 30_000_000);
+        let header = SealedHeader::seal_slow(Header { timestamp, ..child(&parent) });
+
+        let result = consensus().validate_header_against_parent(&header, &parent);
+        if timestamp >= parent_timestamp {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else {
+            prop_assert!(
+                matches!(
+                    result,
+                    Err(ConsensusError::TimestampIsInPast {
+                        parent_timestamp: reported_parent,
+                        timestamp: reported,
+                    }) if reported_parent == parent_timestamp && reported == timestamp
+                ),
+                "{result:?}"
+            );
+        }
+    }
+
+    /// Millisecond timestamps strictly increase, even within a second
+    #[test]
+    fn millisecond_timestamps_increase(
+        parent_millis in 1_000..=MAX_TIMESTAMP * 1000,
+        offset in -2_000i64..2_000,
+    ) {
+        let parent = child_with_millis(&parent(1, parent_millis / 1000, 30_000_000), parent_millis);
+        let timestamp_millis = parent_millis.saturating_add_signed(offset);
+        let header = child_with_millis(&parent, timestamp_millis);
+
+        let result = consensus().validate_header_against_parent(&header, &parent);
+        prop_assert_eq!(result.is_ok(), timestamp_millis > parent_millis, "{:?}", result);
+    }
+
+    /// A block must reference its parent's hash
+    #[test]
+    fn parent_hash_must_match(
+        number in 0..=MAX_PARENT_NUMBER,
+        timestamp in 0..=MAX_TIMESTAMP,
+        parent_hash in any::<[u8; 32]>().prop_map(B256::from),
+    ) {
+        let parent = parent(number, timestamp, 30_000_000);
+        prop_assume!(parent_hash != parent.hash());
+        let header = SealedHeader::seal_slow(Header { parent_hash, ..child(&parent) });
+
+        let result = consensus().validate_header_against_parent(&header, &parent);
+        prop_assert!(
+            matches!(result, Err(ConsensusError::ParentHashMismatch(_))),
+            "{result:?}"
+        );
+    }
+
+    /// A block's number must follow its parent's
+    #[test]
+    fn number_must_follow_parent(
+        parent_number in 0..=MAX_PARENT_NUMBER,
+        number in 0..=MAX_PARENT_NUMBER + 1,
+        timestamp in 0..=MAX_TIMESTAMP,
+    ) {
+        let parent = parent(parent_number, timestamp, 30_000_000);
+        let header = SealedHeader::seal_slow(Header { number, ..child(&parent) });
+
+        let result = consensus().validate_header_against_parent(&header, &parent);
+        if number == parent_number + 1 {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else {
+            prop_assert!(
+                matches!(
+                    result,
+                    Err(ConsensusError::ParentBlockNumberMismatch {
+                        parent_block_number,
+                        block_number,
+                    }) if parent_block_number == parent_number && block_number == number
+                ),
+                "{result:?}"
+            );
+        }
+    }
+
+    /// A block's gas limit stays within 1/1024 of its parent's and above the minimum,
+    /// including when it shares the parent's timestamp
+    #[test]
+    fn gas_limit_is_bounded_by_parent(
+        (parent_gas_limit, gas_limit) in gas_limits(),
+        number in 0..=MAX_PARENT_NUMBER,
+        timestamp in 1..=MAX_TIMESTAMP,
+        same_timestamp in any::<bool>(),
+    ) {
+        let parent = parent(number, timestamp, parent_gas_limit);
+        let header = SealedHeader::seal_slow(Header {
+            gas_limit,
+            timestamp: if same_timestamp { timestamp } else { timestamp + 1 },
+            ..child(&parent)
+        });
+
+        let result = consensus().validate_header_against_parent(&header, &parent);
+        if gas_limit_allowed(parent_gas_limit, gas_limit) {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else {
+            prop_assert!(
+                matches!(
+                    result,
+                    Err(ConsensusError::GasLimitInvalidIncrease { .. }
+                        | ConsensusError::GasLimitInvalidDecrease { .. }
+                        | ConsensusError::GasLimitInvalidMinimum { .. })
+                ),
+                "{result:?}"
+            );
+        }
+    }
+
+    /// A block uses no more gas than its gas limit
+    #[test]
+    fn gas_used_is_bounded_by_gas_limit(
+        gas_limit in MINIMUM_GAS_LIMIT..1_000_000_000,
+        gas_used in 0u64..1_000_000_000,
+        timestamp in 0..=MAX_TIMESTAMP,
+    ) {
+        let header = SealedHeader::seal_slow(Header {
+            number: 1,
+            timestamp,
+            gas_limit,
+            gas_used,
+            ..Default::default()
+        });
+
+        let result = consensus().validate_header(&header);
+        if gas_used <= gas_limit {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else {
+            prop_assert!(
+                matches!(
+                    result,
+                    Err(ConsensusError::HeaderGasUsedExceedsGasLimit {
+                        gas_used: reported_used,
+                        gas_limit: reported_limit,
+                    }) if reported_used == gas_used && reported_limit == gas_limit
+                ),
+                "{result:?}"
+            );
+        }
+    }
+
+    /// On an Evolve chain a block's base fee follows EIP-1559 from its parent's, or stays at
+    /// zero on zero-fee chains, whether or not it shares its parent's timestamp
+    #[test]
+    fn evolve_base_fee_follows_parent(
+        zero_fee in any::<bool>(),
+        number in 0u64..1_000_000,
+        parent_millis in 1_000..=MAX_TIMESTAMP * 1000,
+        millis_offset in 1u64..2_000,
+        (gas_limit, gas_used) in (MINIMUM_GAS_LIMIT..100_000_000)
+            .prop_flat_map(|gas_limit| (Just(gas_limit), 0..=gas_limit)),
+        parent_base_fee in 0u64..1_000_000_000_000,
+        base_fee_offset in prop_oneof![Just(0i64), -1_000i64..1_000],
+        da_info in da_infos(),
+    ) {
+        let parent_base_fee = if zero_fee { 0 } else { parent_base_fee };
+        let parent = evolve_parent(number, parent_millis, gas_limit, gas_used, parent_base_fee);
+        let expected = if zero_fee {
+            0
+        } else {
+            parent.next_block_base_fee(BaseFeeParams::ethereum()).unwrap()
+        };
+        let base_fee = expected.saturating_add_signed(base_fee_offset);
+        let header = evolve_child(
+            &parent,
+            parent_millis + millis_offset,
+            base_fee,
+            da_info,
+            &sequencer(),
+        );
+
+        let result = validate(&evolve_consensus(zero_fee), &header, &parent);
+        if base_fee == expected {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else if zero_fee {
+            prop_assert!(matches!(result, Err(ConsensusError::Custom(_))), "{result:?}");
+        } else {
+            prop_assert!(matches!(result, Err(ConsensusError::BaseFeeDiff(_))), "{result:?}");
+        }
+    }
+
+    /// On an Evolve chain a block carrying any DA info is valid when its scheduled sequencer
+    /// signed it and its millisecond timestamp follows its parent's
+    #[test]
+    fn evolve_header_extension_is_checked(
+        zero_fee in any::<bool>(),
+        number in 0u64..1_000_000,
+        parent_millis in 1_000..=MAX_TIMESTAMP * 1000,
+        millis_offset in -2_000i64..2_000,
+        signed_by_sequencer in any::<bool>(),
+        da_info in da_infos(),
+    ) {
+        let parent = evolve_parent(number, parent_millis, 30_000_000, 15_000_000, 0);
+        let base_fee = if zero_fee {
+            0
+        } else {
+            parent.next_block_base_fee(BaseFeeParams::ethereum()).unwrap()
+        };
+        let signer = if signed_by_sequencer {
+            sequencer()
+        } else {
+            PrivateKeySigner::from_bytes(&B256::repeat_byte(0x22)).unwrap()
+        };
+        let timestamp_millis = parent_millis.saturating_add_signed(millis_offset);
+        let header = evolve_child(&parent, timestamp_millis, base_fee, da_info, &signer);
+        prop_assert_eq!(EvolveExtraData::decode(&header.extra_data).unwrap().da_info, da_info);
+
+        let result = validate(&evolve_consensus(zero_fee), &header, &parent);
+        prop_assert_eq!(
+            result.is_ok(),
+            signed_by_sequencer && timestamp_millis > parent_millis,
+            "{:?}",
+            result
+        );
+    }
+
+    /// On a Cancun Evolve chain a block's excess blob gas follows its parent's blob gas,
+    /// including on zero-fee chains, where the base fee does not follow the parent's
+    #[test]
+    fn evolve_excess_blob_gas_follows_parent(
+        zero_fee in any::<bool>(),
+        number in 0u64..1_000_000,
+        parent_millis in 1_000..=MAX_TIMESTAMP * 1000,
+        millis_offset in 1u64..2_000,
+        parent_base_fee in 1u64..1_000_000_000_000,
+        parent_blobs in 0u64..=6,
+        parent_excess_blob_gas in prop_oneof![Just(0u64), 0u64..10_000_000],
+        excess_blob_gas_offset in prop_oneof![Just(0i64), -1_000_000i64..1_000_000],
+    ) {
+        let blob_params = BlobParams::cancun();
+        let parent = SealedHeader::seal_slow(Header {
+            blob_gas_used: Some(parent_blobs * DATA_GAS_PER_BLOB),
+            excess_blob_gas: Some(parent_excess_blob_gas),
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..evolve_parent(number, parent_millis, 30_000_000, 15_000_000, parent_base_fee)
+                .header()
+                .clone()
+        });
+        let base_fee = if zero_fee {
+            0
+        } else {
+            parent.next_block_base_fee(BaseFeeParams::ethereum()).unwrap()
+        };
+        let expected = parent.next_block_excess_blob_gas(blob_params).unwrap();
+        let excess_blob_gas = expected.saturating_add_signed(excess_blob_gas_offset);
+        let header = seal_signed(
+            Header {
+                base_fee_per_gas: Some(base_fee),
+                blob_gas_used: Some(0),
+                excess_blob_gas: Some(excess_blob_gas),
+                withdrawals_root: Some(EMPTY_ROOT_HASH),
+                parent_beacon_block_root: Some(B256::ZERO),
+                ..evolve_child_header(&parent, parent_millis + millis_offset, None)
+            },
+            &sequencer(),
+        );
+
+        let result = validate(&evolve_cancun_consensus(zero_fee), &header, &parent);
+        if excess_blob_gas == expected {
+            prop_assert!(result.is_ok(), "{result:?}");
+        } else {
+            prop_assert!(
+                matches!(result, Err(ConsensusError::ExcessBlobGasDiff { .. })),
+                "{result:?}"
+            );
+        }
+    }
+}
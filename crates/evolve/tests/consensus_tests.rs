@@ -1,9 +1,19 @@
 //! Tests for Rollkit consensus implementation
 
-use evolve_ev_reth::consensus::RollkitConsensus;
-use reth_chainspec::MAINNET;
-use reth_consensus::{ConsensusError, HeaderValidator};
-use reth_primitives::{Header, SealedHeader};
+use alloy_genesis::Genesis;
+use alloy_signer_local::PrivateKeySigner;
+use evolve_ev_reth::{
+    consensus::{EvolveConsensusError, RollkitConsensus, RollkitConsensusBuilder},
+    genesis::EVOLVE_CONFIG_KEY,
+    sequencer::sign_header,
+    DaBlockInfo, EvolveChainConfig, EvolveExtraData, ProposerRotation, SequencerKey,
+    TimestampRules,
+};
+use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+use reth_consensus::{Consensus, ConsensusError, HeaderValidator};
+use reth_ethereum_primitives::{Block, BlockBody};
+use reth_primitives::{Header, SealedBlock, SealedHeader};
+use std::sync::Arc;
 
 fn create_test_header(number: u64, parent_hash: [u8; 32], timestamp: u64) -> SealedHeader {
     let header = Header {
@@ -21,7 +31,7 @@ fn create_test_header(number: u64, parent_hash: [u8; 32], timestamp: u64) -> Sea
 #[test]
 fn test_rollkit_consensus_allows_same_timestamp() {
     let chain_spec = MAINNET.clone();
-    let consensus = RollkitConsensus::new(chain_spec);
+    let consensus = RollkitConsensus::try_new(chain_spec).unwrap();
 
     // Create parent block
     let parent = create_test_header(1, [0u8; 32], 1000);
@@ -51,7 +61,7 @@ fn test_rollkit_consensus_allows_same_timestamp() {
 #[test]
 fn test_rollkit_consensus_rejects_past_timestamp() {
     let chain_spec = MAINNET.clone();
-    let consensus = RollkitConsensus::new(chain_spec);
+    let consensus = RollkitConsensus::try_new(chain_spec).unwrap();
 
     // Create parent block
     let parent = create_test_header(1, [0u8; 32], 1000);
@@ -89,7 +99,7 @@ fn test_rollkit_consensus_rejects_past_timestamp() {
 #[test]
 fn test_rollkit_consensus_allows_future_timestamp() {
     let chain_spec = MAINNET.clone();
-    let consensus = RollkitConsensus::new(chain_spec);
+    let consensus = RollkitConsensus::try_new(chain_spec).unwrap();
 
     // Create parent block
     let parent = create_test_header(1, [0u8; 32], 1000);
@@ -119,7 +129,7 @@ fn test_rollkit_consensus_allows_future_timestamp() {
 #[test]
 fn test_rollkit_consensus_validates_parent_hash() {
     let chain_spec = MAINNET.clone();
-    let consensus = RollkitConsensus::new(chain_spec);
+    let consensus = RollkitConsensus::try_new(chain_spec).unwrap();
 
     // Create parent block
     let parent = create_test_header(1, [0u8; 32], 1000);
@@ -146,7 +156,7 @@ fn test_rollkit_consensus_validates_parent_hash() {
 #[test]
 fn test_rollkit_consensus_validates_block_number() {
     let chain_spec = MAINNET.clone();
-    let consensus = RollkitConsensus::new(chain_spec);
+    let consensus = RollkitConsensus::try_new(chain_spec).unwrap();
 
     // Create parent block
     let parent = create_test_header(1, [0u8; 32], 1000);
@@ -169,3 +179,606 @@ fn test_rollkit_consensus_validates_block_number() {
         "Rollkit consensus should validate block number"
     );
 }
+
+fn sequencer_consensus(keys: &[(u64, &PrivateKeySigner)]) -> RollkitConsensus {
+    let config = EvolveChainConfig {
+        sequencers: keys
+            .iter()
+            .map(|(activation_height, signer)| SequencerKey {
+                activation_height: *activation_height,
+                address: signer.address(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    RollkitConsensus::with_config(MAINNET.clone(), config).unwrap()
+}
+
+fn signed_header(number: u64, signer: Option<&PrivateKeySigner>) -> Header {
+    let mut header = Header {
+        number,
+        timestamp: 1000,
+        gas_limit: 30_000_000,
+        extra_data: DaBlockInfo::new(7, [5u8; 32].into(), 900).encode_extra_data(),
+        ..Default::default()
+    };
+    if let Some(signer) = signer {
+        sign_header(&mut header, signer).unwrap();
+    }
+    header
+}
+
+/// Validates a signed block against its parent, as the engine does after `validate_header`
+fn validate_signed_block(
+    consensus: &RollkitConsensus,
+    number: u64,
+    signer: &PrivateKeySigner,
+) -> Result<(), ConsensusError> {
+    let parent = create_test_header(number - 1, [0u8; 32], 1000);
+    let mut header = signed_header(number, None);
+    header.parent_hash = parent.hash();
+    sign_header(&mut header, signer).unwrap();
+    let header = SealedHeader::seal_slow(header);
+
+    consensus.validate_header(&header)?;
+    consensus.validate_header_against_parent(&header, &parent)
+}
+
+fn evolve_error(result: Result<(), ConsensusError>) -> String {
+    match result {
+        Err(ConsensusError::Custom(err)) => {
+            assert!(err.downcast_ref::<EvolveConsensusError>().is_some());
+            err.to_string()
+        }
+        other => panic!("Expected an Evolve consensus error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_rollkit_consensus_accepts_sequencer_signature() {
+    let sequencer = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(0, &sequencer)]);
+
+    let header = signed_header(2, Some(&sequencer));
+    let result = consensus.validate_header(&SealedHeader::seal_slow(header));
+    assert!(result.is_ok(), "signed block should be valid: {result:?}");
+
+    let result = validate_signed_block(&consensus, 2, &sequencer);
+    assert!(result.is_ok(), "signed block should be valid: {result:?}");
+}
+
+#[test]
+fn test_rollkit_consensus_rejects_unsigned_block() {
+    let sequencer = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(0, &sequencer)]);
+
+    let header = signed_header(2, None);
+    let err = evolve_error(consensus.validate_header(&SealedHeader::seal_slow(header)));
+    assert!(err.contains(&sequencer.address().to_string()), "{err}");
+}
+
+#[test]
+fn test_rollkit_consensus_rejects_wrong_sequencer() {
+    let sequencer = PrivateKeySigner::random();
+    let other = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(0, &sequencer)]);
+
+    let err = evolve_error(validate_signed_block(&consensus, 2, &other));
+    assert!(err.contains(&sequencer.address().to_string()), "{err}");
+    assert!(err.contains(&other.address().to_string()), "{err}");
+}
+
+#[test]
+fn test_rollkit_consensus_rejects_tampered_block() {
+    let sequencer = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(0, &sequencer)]);
+
+    let parent = create_test_header(1, [0u8; 32], 1000);
+    let mut header = signed_header(2, None);
+    header.parent_hash = parent.hash();
+    sign_header(&mut header, &sequencer).unwrap();
+    header.gas_used = 21_000;
+    let header = SealedHeader::seal_slow(header);
+    let result = consensus
+        .validate_header(&header)
+        .and_then(|()| consensus.validate_header_against_parent(&header, &parent));
+    assert!(matches!(result, Err(ConsensusError::Custom(_))));
+}
+
+#[test]
+fn test_rollkit_consensus_rotates_sequencer_keys() {
+    let first = PrivateKeySigner::random();
+    let second = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(5, &first), (10, &second)]);
+
+    // Blocks before the first activation need no signature
+    let header = signed_header(4, None);
+    assert!(consensus
+        .validate_header(&SealedHeader::seal_slow(header))
+        .is_ok());
+
+    assert!(validate_signed_block(&consensus, 9, &first).is_ok());
+
+    // The rotated key takes over at its activation height
+    let err = evolve_error(validate_signed_block(&consensus, 10, &first));
+    assert!(err.contains(&second.address().to_string()), "{err}");
+
+    assert!(validate_signed_block(&consensus, 10, &second).is_ok());
+}
+
+#[test]
+fn test_rollkit_consensus_round_robin_proposers() {
+    let sequencer = PrivateKeySigner::random();
+    let proposers = [PrivateKeySigner::random(), PrivateKeySigner::random()];
+    let config = EvolveChainConfig {
+        sequencers: vec![SequencerKey {
+            activation_height: 1,
+            address: sequencer.address(),
+        }],
+        proposer_rotation: Some(ProposerRotation {
+            activation_height: 10,
+            proposers: proposers.iter().map(PrivateKeySigner::address).collect(),
+            blocks_per_proposer: 2,
+        }),
+        ..Default::default()
+    };
+    let consensus = RollkitConsensus::with_config(MAINNET.clone(), config).unwrap();
+
+    // The single sequencer proposes until the rotation activates
+    assert!(validate_signed_block(&consensus, 9, &sequencer).is_ok());
+    let err = evolve_error(validate_signed_block(&consensus, 10, &sequencer));
+    assert!(err.contains(&proposers[0].address().to_string()), "{err}");
+
+    // Each proposer then takes two consecutive blocks in turn
+    for (number, turn) in [(10, 0), (11, 0), (12, 1), (13, 1), (14, 0)] {
+        let expected = &proposers[turn];
+        let other = &proposers[1 - turn];
+        assert!(
+            validate_signed_block(&consensus, number, expected).is_ok(),
+            "block {number} should be proposed by {}",
+            expected.address()
+        );
+
+        let err = evolve_error(validate_signed_block(&consensus, number, other));
+        assert!(err.contains(&expected.address().to_string()), "{err}");
+    }
+}
+
+#[test]
+fn test_rollkit_consensus_rejects_invalid_proposer_rotation() {
+    for rotation in [
+        ProposerRotation {
+            activation_height: 10,
+            proposers: Vec::new(),
+            blocks_per_proposer: 1,
+        },
+        ProposerRotation {
+            activation_height: 10,
+            proposers: vec![PrivateKeySigner::random().address()],
+            blocks_per_proposer: 0,
+        },
+    ] {
+        let config = EvolveChainConfig {
+            proposer_rotation: Some(rotation),
+            ..Default::default()
+        };
+        assert!(RollkitConsensus::with_config(MAINNET.clone(), config).is_err());
+    }
+}
+
+#[test]
+fn test_rollkit_consensus_builder_rejects_invalid_chain_config() {
+    let chain_spec = |evolve: serde_json::Value| {
+        let mut genesis = Genesis::default();
+        genesis
+            .config
+            .extra_fields
+            .insert(EVOLVE_CONFIG_KEY.to_string(), evolve);
+        Arc::new(ChainSpec::from(genesis))
+    };
+
+    let valid = chain_spec(serde_json::json!({ "zeroFee": true }));
+    let consensus = RollkitConsensusBuilder::build(valid).unwrap();
+    assert!(consensus.config().zero_fee);
+
+    let invalid = chain_spec(serde_json::json!({
+        "proposerRotation": { "activationHeight": 1, "proposers": [] }
+    }));
+    assert!(RollkitConsensusBuilder::build(invalid.clone()).is_err());
+    assert!(RollkitConsensus::try_new(invalid).is_err());
+}
+
+#[test]
+fn test_rollkit_consensus_checks_proposer_in_validate_header() {
+    let sequencer = PrivateKeySigner::random();
+    let other = PrivateKeySigner::random();
+    let consensus = sequencer_consensus(&[(0, &sequencer)]);
+
+    // The signer is checked against the schedule with the signature, before the parent
+    let header = SealedHeader::seal_slow(signed_header(2, Some(&other)));
+    let err = evolve_error(consensus.validate_header(&header));
+    assert!(err.contains(&sequencer.address().to_string()), "{err}");
+    assert!(err.contains(&other.address().to_string()), "{err}");
+}
+
+fn timestamp_consensus(rules: TimestampRules) -> RollkitConsensus {
+    let config = EvolveChainConfig {
+        timestamp_rules: rules,
+        ..Default::default()
+    };
+    RollkitConsensus::with_config(MAINNET.clone(), config).unwrap()
+}
+
+/// Builds a child of `parent` recording the equal-timestamp run `run`
+fn child_with_run(parent: &SealedHeader, timestamp: u64, run: u64) -> SealedHeader {
+    let extension = EvolveExtraData {
+        timestamp_run: run,
+        ..Default::default()
+    };
+    SealedHeader::seal_slow(Header {
+        number: parent.number + 1,
+        parent_hash: parent.hash(),
+        timestamp,
+        gas_limit: 30_000_000,
+        extra_data: extension.encode(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_rollkit_consensus_limits_equal_timestamp_runs() {
+    let consensus = timestamp_consensus(TimestampRules {
+        max_equal_timestamp_run: Some(3),
+        ..Default::default()
+    });
+
+    // Three consecutive blocks may share a timestamp, the fourth may not
+    let first = create_test_header(1, [0u8; 32], 1000);
+    let second = child_with_run(&first, 1000, 1);
+    let third = child_with_run(&second, 1000, 2);
+    let fourth = child_with_run(&third, 1000, 3);
+    assert!(consensus
+        .validate_header_against_parent(&second, &first)
+        .is_ok());
+    assert!(consensus
+        .validate_header_against_parent(&third, &second)
+        .is_ok());
+    let err = evolve_error(consensus.validate_header_against_parent(&fourth, &third));
+    assert!(err.contains("4 consecutive blocks"), "{err}");
+
+    // Moving time forward resets the run
+    let next = child_with_run(&third, 1001, 0);
+    assert!(consensus
+        .validate_header_against_parent(&next, &third)
+        .is_ok());
+
+    // The recorded run must match the parent's
+    let understated = child_with_run(&third, 1000, 0);
+    let err = evolve_error(consensus.validate_header_against_parent(&understated, &third));
+    assert!(
+        err.contains("recorded equal-timestamp run 0, expected 3"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_rollkit_consensus_limits_timestamp_jumps() {
+    let consensus = timestamp_consensus(TimestampRules {
+        max_timestamp_jump: Some(60),
+        ..Default::default()
+    });
+    let parent = create_test_header(1, [0u8; 32], 1000);
+
+    let child = child_with_run(&parent, 1060, 0);
+    assert!(consensus
+        .validate_header_against_parent(&child, &parent)
+        .is_ok());
+
+    let child = child_with_run(&parent, 1061, 0);
+    let err = evolve_error(consensus.validate_header_against_parent(&child, &parent));
+    assert!(
+        err.contains("more than 60s after parent timestamp 1000"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_rollkit_consensus_limits_future_drift() {
+    let consensus = timestamp_consensus(TimestampRules {
+        max_future_drift: Some(30),
+        ..Default::default()
+    });
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let header = |timestamp| {
+        SealedHeader::seal_slow(Header {
+            number: 2,
+            timestamp,
+            gas_limit: 30_000_000,
+            ..Default::default()
+        })
+    };
+    // Mainnet's timestamp-activated forks reject the bare header for other reasons, so only
+    // check that the drift rule accepts it
+    assert!(!matches!(
+        consensus.validate_header(&header(now)),
+        Err(ConsensusError::Custom(_))
+    ));
+
+    let err = evolve_error(consensus.validate_header(&header(now + 3600)));
+    assert!(err.contains("ahead of the local clock"), "{err}");
+}
+
+/// Builds a child of `parent` recording the millisecond timestamp `timestamp_millis`
+fn child_with_millis(parent: &SealedHeader, timestamp_millis: Option<u64>) -> SealedHeader {
+    let timestamp = timestamp_millis.map_or(parent.timestamp + 1, |millis| millis / 1000);
+    let extension = EvolveExtraData {
+        timestamp_run: EvolveExtraData::next_timestamp_run(parent.header(), timestamp),
+        timestamp_millis,
+        ..Default::default()
+    };
+    SealedHeader::seal_slow(Header {
+        number: parent.number + 1,
+        parent_hash: parent.hash(),
+        timestamp,
+        gas_limit: 30_000_000,
+        extra_data: extension.encode(),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_rollkit_consensus_orders_millisecond_timestamps() {
+    let consensus = timestamp_consensus(TimestampRules::default());
+    let parent = create_test_header(1, [0u8; 32], 1000);
+
+    // Blocks sharing a second are ordered by their millisecond timestamps
+    let first = child_with_millis(&parent, Some(1_000_400));
+    let second = child_with_millis(&first, Some(1_000_900));
+    assert!(consensus
+        .validate_header_against_parent(&first, &parent)
+        .is_ok());
+    assert!(consensus
+        .validate_header_against_parent(&second, &first)
+        .is_ok());
+
+    let repeated = child_with_millis(&first, Some(1_000_400));
+    let err = evolve_error(consensus.validate_header_against_parent(&repeated, &first));
+    assert!(err.contains("is not after parent's 1000400"), "{err}");
+
+    // Once recorded, every descendant must record a millisecond timestamp
+    let missing = child_with_millis(&first, None);
+    let err = evolve_error(consensus.validate_header_against_parent(&missing, &first));
+    assert!(err.contains("missing millisecond timestamp"), "{err}");
+}
+
+#[test]
+fn test_rollkit_consensus_pins_zero_base_fee() {
+    let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).london_activated().build());
+    let config = EvolveChainConfig {
+        zero_fee: true,
+        ..Default::default()
+    };
+    let consensus = RollkitConsensus::with_config(chain_spec.clone(), config).unwrap();
+
+    let parent = SealedHeader::seal_slow(Header {
+        number: 1,
+        timestamp: 1000,
+        gas_limit: 30_000_000,
+        base_fee_per_gas: Some(1_000_000_000),
+        ..Default::default()
+    });
+    let child = |base_fee| {
+        SealedHeader::seal_slow(Header {
+            number: 2,
+            parent_hash: parent.hash(),
+            timestamp: 1001,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(base_fee),
+            ..Default::default()
+        })
+    };
+
+    // The base fee stays at zero whatever the parent's EIP-1559 base fee
+    assert!(consensus
+        .validate_header_against_parent(&child(0), &parent)
+        .is_ok());
+    let err = evolve_error(consensus.validate_header(&child(7)));
+    assert!(err.contains("base fee 7 on a zero-fee chain"), "{err}");
+
+    // Other chains follow EIP-1559
+    let consensus =
+        RollkitConsensus::with_config(chain_spec, EvolveChainConfig::default()).unwrap();
+    assert!(matches!(
+        consensus.validate_header_against_parent(&child(0), &parent),
+        Err(ConsensusError::BaseFeeDiff(_))
+    ));
+}
+
+#[test]
+fn test_rollkit_consensus_checks_blob_gas_on_zero_fee_chains() {
+    let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).cancun_activated().build());
+    let config = EvolveChainConfig {
+        zero_fee: true,
+        ..Default::default()
+    };
+    let consensus = RollkitConsensus::with_config(chain_spec, config).unwrap();
+
+    // A parent following EIP-1559, so that its zero-fee child fails the base fee comparison
+    let parent = SealedHeader::seal_slow(Header {
+        number: 1,
+        timestamp: 1000,
+        gas_limit: 30_000_000,
+        base_fee_per_gas: Some(1_000_000_000),
+        blob_gas_used: Some(0),
+        excess_blob_gas: Some(0),
+        ..Default::default()
+    });
+    let child = |excess_blob_gas| {
+        SealedHeader::seal_slow(Header {
+            number: 2,
+            parent_hash: parent.hash(),
+            timestamp: 1001,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(0),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(excess_blob_gas),
+            ..Default::default()
+        })
+    };
+
+    assert!(consensus
+        .validate_header_against_parent(&child(0), &parent)
+        .is_ok());
+    // Skipping the base fee comparison still checks the blob gas following it
+    let result = consensus.validate_header_against_parent(&child(131_072), &parent);
+    assert!(
+        matches!(result, Err(ConsensusError::ExcessBlobGasDiff { .. })),
+        "{result:?}"
+    );
+}
+
+/// London parent at its gas target, so that its child keeps its base fee
+fn london_parent(timestamp: u64, base_fee: u64) -> SealedHeader {
+    SealedHeader::seal_slow(Header {
+        number: 1,
+        timestamp,
+        gas_limit: 30_000_000,
+        gas_used: 15_000_000,
+        base_fee_per_gas: Some(base_fee),
+        ..Default::default()
+    })
+}
+
+/// Child of `parent` sharing its timestamp
+fn equal_timestamp_child(parent: &SealedHeader, gas_limit: u64, base_fee: u64) -> SealedHeader {
+    SealedHeader::seal_slow(Header {
+        number: parent.number + 1,
+        parent_hash: parent.hash(),
+        timestamp: parent.timestamp,
+        gas_limit,
+        base_fee_per_gas: Some(base_fee),
+        ..Default::default()
+    })
+}
+
+fn london_consensus(zero_fee: bool) -> RollkitConsensus {
+    let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).london_activated().build());
+    let config = EvolveChainConfig {
+        zero_fee,
+        ..Default::default()
+    };
+    RollkitConsensus::with_config(chain_spec, config).unwrap()
+}
+
+#[test]
+fn test_rollkit_consensus_checks_gas_limit_on_equal_timestamps() {
+    let consensus = london_consensus(false);
+    let base_fee = 1_000_000_000;
+
+    // Timestamp zero has no older parent to check against
+    for timestamp in [0, 1000] {
+        let parent = london_parent(timestamp, base_fee);
+        let bound = parent.gas_limit / 1024;
+        for gas_limit in [parent.gas_limit - bound + 1, parent.gas_limit + bound - 1] {
+            let result = consensus.validate_header_against_parent(
+                &equal_timestamp_child(&parent, gas_limit, base_fee),
+                &parent,
+            );
+            assert!(result.is_ok(), "{result:?}");
+        }
+
+        let result = consensus.validate_header_against_parent(
+            &equal_timestamp_child(&parent, 2 * parent.gas_limit, base_fee),
+            &parent,
+        );
+        assert!(
+            matches!(result, Err(ConsensusError::GasLimitInvalidIncrease { .. })),
+            "{result:?}"
+        );
+        let result = consensus.validate_header_against_parent(
+            &equal_timestamp_child(&parent, parent.gas_limit / 2, base_fee),
+            &parent,
+        );
+        assert!(
+            matches!(result, Err(ConsensusError::GasLimitInvalidDecrease { .. })),
+            "{result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_rollkit_consensus_checks_base_fee_on_equal_timestamps() {
+    let base_fee = 1_000_000_000;
+    let parent = london_parent(1000, base_fee);
+    let consensus = london_consensus(false);
+
+    assert!(consensus
+        .validate_header_against_parent(
+            &equal_timestamp_child(&parent, parent.gas_limit, base_fee),
+            &parent,
+        )
+        .is_ok());
+    let result = consensus.validate_header_against_parent(
+        &equal_timestamp_child(&parent, parent.gas_limit, 2 * base_fee),
+        &parent,
+    );
+    assert!(
+        matches!(result, Err(ConsensusError::BaseFeeDiff(_))),
+        "{result:?}"
+    );
+
+    // Zero-fee chains keep the base fee at zero on equal timestamps too
+    let parent = london_parent(1000, 0);
+    let consensus = london_consensus(true);
+    assert!(consensus
+        .validate_header_against_parent(
+            &equal_timestamp_child(&parent, parent.gas_limit, 0),
+            &parent
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_rollkit_consensus_still_rejects_past_timestamps_on_london() {
+    let parent = london_parent(1000, 1_000_000_000);
+    let child = SealedHeader::seal_slow(Header {
+        timestamp: 999,
+        ..equal_timestamp_child(&parent, parent.gas_limit, 1_000_000_000)
+            .header()
+            .clone()
+    });
+
+    let result = london_consensus(false).validate_header_against_parent(&child, &parent);
+    assert!(
+        matches!(result, Err(ConsensusError::TimestampIsInPast { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn test_rollkit_consensus_rejects_oversized_blocks() {
+    let config = EvolveChainConfig {
+        max_block_bytes: Some(2048),
+        ..Default::default()
+    };
+    let consensus = RollkitConsensus::with_config(MAINNET.clone(), config).unwrap();
+    let block = |extra_data_len: usize| {
+        SealedBlock::seal_slow(Block {
+            header: Header {
+                number: 1,
+                timestamp: 1000,
+                extra_data: vec![0u8; extra_data_len].into(),
+                ..Default::default()
+            },
+            body: BlockBody::default(),
+        })
+    };
+
+    assert!(consensus.validate_block_pre_execution(&block(0)).is_ok());
+    let err = evolve_error(consensus.validate_block_pre_execution(&block(4096)));
+    assert!(err.contains("above the maximum of 2048"), "{err}");
+}
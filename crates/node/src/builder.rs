@@ -1,24 +1,101 @@
 use alloy_consensus::transaction::Transaction;
-use evolve_ev_reth::RollkitPayloadAttributes;
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Log, Signature, TxHash, B256, U256};
+use alloy_signer_local::PrivateKeySigner;
+use evolve_ev_reth::{
+    sequencer::sign_header, BuildAttributes, BuildAttributesRecorder, EvolveExtraData,
+    ExcludedTransaction, Preconfirmation, PreconfirmationStatus, Preconfirmations,
+    RollkitPayloadAttributes, BLOCK_OVERHEAD_BYTES,
+};
 use reth_errors::RethError;
 use reth_evm::{
     execute::{BlockBuilder, BlockBuilderOutcome},
     ConfigureEvm, NextBlockEnvAttributes,
 };
-use reth_evm_ethereum::EthEvmConfig;
 use reth_payload_builder_primitives::PayloadBuilderError;
-use reth_primitives::{transaction::SignedTransaction, Header, SealedBlock, SealedHeader};
+use reth_primitives::{
+    transaction::SignedTransaction, Block, BlockBody, Header, SealedBlock, SealedHeader,
+};
 use reth_provider::{HeaderProvider, StateProviderFactory};
-use reth_revm::{database::StateProviderDatabase, State};
+use reth_revm::{database::StateProviderDatabase, db::BundleState, State};
 use std::sync::Arc;
 
+use crate::{config::RollkitPayloadBuilderConfig, evm::RollkitEvmConfig};
+
 /// Payload builder for Rollkit Reth node
 #[derive(Debug)]
 pub struct RollkitPayloadBuilder<Client> {
     /// The client for state access
     pub client: Arc<Client>,
     /// EVM configuration
-    pub evm_config: EthEvmConfig,
+    pub evm_config: RollkitEvmConfig,
+    /// Sequencer key signing built blocks, if any
+    pub sequencer_signer: Option<PrivateKeySigner>,
+    /// Limits on the transactions included in built blocks
+    pub config: RollkitPayloadBuilderConfig,
+    /// Publisher of the results of executed transactions before the block is sealed, if any
+    pub preconfirmations: Option<Preconfirmations>,
+    /// Recorder of the attributes each block was built from, if any
+    pub build_attributes: Option<BuildAttributesRecorder>,
+}
+
+/// Block built by [`RollkitPayloadBuilder`] along with the transactions it left out
+#[derive(Debug, Clone)]
+pub struct RollkitBuiltPayload {
+    /// Built block
+    pub block: SealedBlock,
+    /// Transactions of the payload attributes left out of the block
+    pub skipped: Vec<SkippedTransaction>,
+    /// Outcome of each transaction of the payload attributes, in order
+    pub outcomes: Vec<TransactionOutcome>,
+    /// State changes of the block, on top of its parent's state
+    pub bundle_state: BundleState,
+}
+
+/// Outcome of a transaction of the payload attributes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    /// Executed and included in the block
+    Included {
+        /// Whether the transaction succeeded, rather than reverted or halted
+        success: bool,
+        /// Gas used by the transaction
+        gas_used: u64,
+        /// Logs emitted by the transaction
+        logs: Vec<Log>,
+    },
+    /// Rejected by the EVM, for instance for a bad nonce or insufficient funds
+    Invalid(String),
+    /// Left out under the builder's limits
+    Skipped(SkipReason),
+}
+
+/// Transaction of the payload attributes left out of a built block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedTransaction {
+    /// Transaction hash
+    pub hash: TxHash,
+    /// Why the transaction was left out
+    pub reason: SkipReason,
+}
+
+/// Reason for leaving a transaction out of a built block
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SkipReason {
+    /// The effective gas price is below the configured minimum
+    #[error("effective gas price {effective_gas_price} is below the minimum of {min_gas_price}")]
+    GasPriceTooLow {
+        /// Effective gas price of the transaction at the block's base fee
+        effective_gas_price: u128,
+        /// Configured minimum gas price
+        min_gas_price: u128,
+    },
+    /// The block already holds the maximum number of transactions
+    #[error("block already holds the maximum of {0} transactions")]
+    MaxTransactions(usize),
+    /// Including the transaction would take the encoded block above the maximum size
+    #[error("block would exceed the maximum of {0} bytes")]
+    MaxBlockBytes(u64),
 }
 
 impl<Client> RollkitPayloadBuilder<Client>
@@ -26,8 +103,39 @@ where
     Client: StateProviderFactory + HeaderProvider<Header = Header> + Send + Sync + 'static,
 {
     /// Creates a new instance of `RollkitPayloadBuilder`
-    pub const fn new(client: Arc<Client>, evm_config: EthEvmConfig) -> Self {
-        Self { client, evm_config }
+    pub const fn new(client: Arc<Client>, evm_config: RollkitEvmConfig) -> Self {
+        Self {
+            client,
+            evm_config,
+            sequencer_signer: None,
+            config: RollkitPayloadBuilderConfig::new(),
+            preconfirmations: None,
+            build_attributes: None,
+        }
+    }
+
+    /// Publishes the result of every executed transaction before the block is sealed
+    pub fn with_preconfirmations(mut self, preconfirmations: Preconfirmations) -> Self {
+        self.preconfirmations = Some(preconfirmations);
+        self
+    }
+
+    /// Records the attributes of every built block along with the transactions left out
+    pub fn with_build_attributes(mut self, recorder: BuildAttributesRecorder) -> Self {
+        self.build_attributes = Some(recorder);
+        self
+    }
+
+    /// Applies the given limits to built blocks
+    pub const fn with_config(mut self, config: RollkitPayloadBuilderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Signs built blocks with the given sequencer key
+    pub fn with_sequencer_signer(mut self, signer: PrivateKeySigner) -> Self {
+        self.sequencer_signer = Some(signer);
+        self
     }
 
     /// Builds a payload using the provided attributes
@@ -35,13 +143,25 @@ where
         &self,
         attributes: RollkitPayloadAttributes,
     ) -> Result<SealedBlock, PayloadBuilderError> {
+        Ok(self.build_payload_with_report(attributes).await?.block)
+    }
+
+    /// Builds a payload using the provided attributes and reports the transactions left out
+    /// of it under the configured limits
+    pub async fn build_payload_with_report(
+        &self,
+        attributes: RollkitPayloadAttributes,
+    ) -> Result<RollkitBuiltPayload, PayloadBuilderError> {
         // Validate attributes
         attributes
             .validate()
             .map_err(|e| PayloadBuilderError::Internal(RethError::Other(Box::new(e))))?;
 
-        // Get the latest state provider
-        let state_provider = self.client.latest().map_err(PayloadBuilderError::other)?;
+        // Execute on top of the parent's state, which need not be the latest one
+        let state_provider = self
+            .client
+            .state_by_block_hash(attributes.parent_hash)
+            .map_err(PayloadBuilderError::other)?;
 
         // Create a database from the state provider
         let db = StateProviderDatabase::new(&state_provider);
@@ -76,11 +196,22 @@ where
             withdrawals: None,
         };
 
-        // Create block builder using the EVM config
-        let mut builder = self
+        // Create block builder using the EVM config, carrying the DA block info so that the
+        // executor applies the DA info system call and records it in the header
+        let evm_env = self
             .evm_config
-            .builder_for_next_block(&mut state_db, &sealed_parent, next_block_attrs)
+            .next_evm_env(&sealed_parent, &next_block_attrs)
             .map_err(PayloadBuilderError::other)?;
+        let base_fee = evm_env.block_env.basefee;
+        let evm = self.evm_config.evm_with_env(&mut state_db, evm_env);
+        let mut ctx = self
+            .evm_config
+            .context_for_next_block(&sealed_parent, next_block_attrs);
+        ctx.da_info = attributes.da_info;
+        ctx.timestamp_millis = attributes.timestamp_millis;
+        let mut builder = self
+            .evm_config
+            .create_block_builder(evm, &sealed_parent, ctx);
 
         // Apply pre-execution changes
         builder
@@ -92,7 +223,61 @@ where
             transaction_count = attributes.transactions.len(),
             "Rollkit payload builder: executing transactions"
         );
+        let mut included = 0;
+        let mut cumulative_gas_used = 0;
+        let mut skipped = Vec::new();
+        let mut outcomes = Vec::with_capacity(attributes.transactions.len());
+        if let Some(preconfirmations) = &self.preconfirmations {
+            let transactions = attributes
+                .transactions
+                .iter()
+                .map(|tx| *tx.hash())
+                .collect();
+            preconfirmations.start_block(
+                attributes.block_number,
+                attributes.parent_hash,
+                &transactions,
+            );
+        }
+        // Header and list headers are bounded by the overhead, transactions are counted exactly.
+        // The overhead covers the widest header the block can get, extension and signature
+        // included, so the built block never ends up above the maximum.
+        let mut block_bytes = BLOCK_OVERHEAD_BYTES.max(max_block_overhead(
+            &attributes,
+            self.sequencer_signer.is_some(),
+        ));
+        let mut block_full = false;
         for (i, tx) in attributes.transactions.iter().enumerate() {
+            let effective_gas_price = tx.effective_gas_price(Some(base_fee));
+            let tx_bytes = tx.network_len() as u64;
+            let skip_reason = if included >= self.config.max_transactions {
+                Some(SkipReason::MaxTransactions(self.config.max_transactions))
+            } else if let Some(max) = self
+                .config
+                .max_block_bytes
+                .filter(|max| block_full || block_bytes + tx_bytes > *max)
+            {
+                // Transactions are included in order, so the block ends at the first overflow
+                block_full = true;
+                Some(SkipReason::MaxBlockBytes(max))
+            } else if effective_gas_price < self.config.min_gas_price {
+                Some(SkipReason::GasPriceTooLow {
+                    effective_gas_price,
+                    min_gas_price: self.config.min_gas_price,
+                })
+            } else {
+                None
+            };
+            if let Some(reason) = skip_reason {
+                tracing::warn!(index = i, hash = ?tx.hash(), %reason, "Skipping transaction");
+                outcomes.push(TransactionOutcome::Skipped(reason.clone()));
+                skipped.push(SkippedTransaction {
+                    hash: *tx.hash(),
+                    reason,
+                });
+                continue;
+            }
+
             tracing::debug!(
             index = i,
             hash = ?tx.hash(),
@@ -110,13 +295,42 @@ where
             })?;
 
             // Execute the transaction
-            match builder.execute_transaction(recovered_tx) {
+            let from = recovered_tx.signer();
+            let mut outcome = None;
+            match builder.execute_transaction_with_result_closure(recovered_tx, |result| {
+                outcome = Some((result.is_success(), result.logs().to_vec()));
+            }) {
                 Ok(gas_used) => {
+                    cumulative_gas_used += gas_used;
+                    let (success, logs) = outcome.unwrap_or_default();
+                    if let Some(preconfirmations) = &self.preconfirmations {
+                        preconfirmations.publish(Preconfirmation {
+                            status: PreconfirmationStatus::Preconfirmed,
+                            transaction_hash: *tx.hash(),
+                            transaction_index: included as u64,
+                            block_number: attributes.block_number,
+                            parent_hash: attributes.parent_hash,
+                            from,
+                            to: tx.to(),
+                            success,
+                            gas_used,
+                            cumulative_gas_used,
+                            logs: logs.clone(),
+                        });
+                    }
+                    outcomes.push(TransactionOutcome::Included {
+                        success,
+                        gas_used,
+                        logs,
+                    });
+                    included += 1;
+                    block_bytes += tx_bytes;
                     tracing::debug!(index = i, gas_used, "Transaction executed successfully");
                 }
                 Err(err) => {
                     // Log the error but continue with other transactions
                     tracing::warn!(index = i, error = ?err, "Transaction execution failed");
+                    outcomes.push(TransactionOutcome::Invalid(err.to_string()));
                 }
             }
         }
@@ -131,24 +345,105 @@ where
             .finish(&state_provider)
             .map_err(PayloadBuilderError::other)?;
 
-        let sealed_block = block.sealed_block().clone();
+        let mut sealed_block = block.sealed_block().clone();
+        let bundle_state = state_db.take_bundle();
+        if let Some(signer) = &self.sequencer_signer {
+            // The signature covers the whole header, so sign the assembled block and reseal it
+            let mut block = sealed_block.into_block();
+            sign_header(&mut block.header, signer).map_err(PayloadBuilderError::other)?;
+            sealed_block = SealedBlock::seal_slow(block);
+        }
+        if let Some(max) = self.config.max_block_bytes {
+            let size = sealed_block.rlp_length() as u64;
+            if size > max {
+                return Err(PayloadBuilderError::Internal(RethError::Other(
+                    format!("built block is {size} bytes, above the maximum of {max}").into(),
+                )));
+            }
+        }
+        if let Some(recorder) = &self.build_attributes {
+            let excluded = attributes
+                .transactions
+                .iter()
+                .zip(&outcomes)
+                .filter_map(|(tx, outcome)| {
+                    let reason = match outcome {
+                        TransactionOutcome::Included { .. } => return None,
+                        TransactionOutcome::Invalid(err) => err.clone(),
+                        TransactionOutcome::Skipped(reason) => reason.to_string(),
+                    };
+                    Some(ExcludedTransaction {
+                        hash: *tx.hash(),
+                        reason,
+                    })
+                })
+                .collect();
+            recorder.record(BuildAttributes::new(
+                &attributes,
+                sealed_block.hash(),
+                excluded,
+            ));
+        }
         tracing::info!(
                     block_number = sealed_block.number,
                     block_hash = ?sealed_block.hash(),
                     transaction_count = sealed_block.transaction_count(),
                     gas_used = sealed_block.gas_used,
+                    skipped_count = skipped.len(),
                     "Rollkit payload builder: built block"
         );
 
         // Return the sealed block
-        Ok(sealed_block)
+        Ok(RollkitBuiltPayload {
+            block: sealed_block,
+            skipped,
+            outcomes,
+            bundle_state,
+        })
     }
 }
 
+/// Returns the largest number of bytes the header and RLP list headers of a block built from
+/// `attributes` can take, measured on a header with every field at its widest and the
+/// header extension the block will carry
+fn max_block_overhead(attributes: &RollkitPayloadAttributes, signed: bool) -> u64 {
+    let extension = EvolveExtraData {
+        da_info: attributes.da_info,
+        signature: signed.then(|| Signature::new(U256::MAX, U256::MAX, true)),
+        timestamp_run: u64::MAX,
+        timestamp_millis: attributes.timestamp_millis,
+    };
+    let header = Header {
+        difficulty: U256::MAX,
+        number: u64::MAX,
+        gas_limit: u64::MAX,
+        gas_used: u64::MAX,
+        timestamp: u64::MAX,
+        extra_data: extension.encode(),
+        base_fee_per_gas: Some(u64::MAX),
+        withdrawals_root: Some(B256::ZERO),
+        blob_gas_used: Some(u64::MAX),
+        excess_blob_gas: Some(u64::MAX),
+        parent_beacon_block_root: Some(B256::ZERO),
+        requests_hash: Some(B256::ZERO),
+        ..Default::default()
+    };
+    let block = Block {
+        header,
+        body: BlockBody {
+            withdrawals: Some(Default::default()),
+            ..Default::default()
+        },
+    };
+    // The block and transaction list headers grow with the transactions, to at most 9 bytes
+    // each
+    SealedBlock::seal_slow(block).rlp_length() as u64 + 16
+}
+
 /// Creates a new payload builder service
 pub const fn create_payload_builder_service<Client>(
     client: Arc<Client>,
-    evm_config: EthEvmConfig,
+    evm_config: RollkitEvmConfig,
 ) -> Option<RollkitPayloadBuilder<Client>>
 where
     Client: StateProviderFactory + HeaderProvider<Header = Header> + Send + Sync + 'static,
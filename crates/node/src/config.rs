@@ -1,17 +1,78 @@
+use evolve_ev_reth::{EvolveChainConfig, BLOCK_OVERHEAD_BYTES};
 use serde::{Deserialize, Serialize};
 
+/// Default maximum number of transactions per block
+pub const DEFAULT_MAX_TRANSACTIONS: usize = 1000;
+
+/// Default minimum effective gas price of included transactions, 1 gwei
+pub const DEFAULT_MIN_GAS_PRICE: u128 = 1_000_000_000;
+
 /// Configuration for the Rollkit payload builder
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct RollkitPayloadBuilderConfig {}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollkitPayloadBuilderConfig {
+    /// Maximum number of transactions per block, further transactions are skipped
+    pub max_transactions: usize,
+    /// Minimum effective gas price in wei, cheaper transactions are skipped
+    pub min_gas_price: u128,
+    /// Maximum size of the RLP-encoded block, unbounded when unset
+    pub max_block_bytes: Option<u64>,
+}
+
+impl Default for RollkitPayloadBuilderConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl RollkitPayloadBuilderConfig {
-    /// Creates a new instance of `RollkitPayloadBuilderConfig`
+    /// Creates a new instance of `RollkitPayloadBuilderConfig` with the default limits
     pub const fn new() -> Self {
-        Self {}
+        Self {
+            max_transactions: DEFAULT_MAX_TRANSACTIONS,
+            min_gas_price: DEFAULT_MIN_GAS_PRICE,
+            max_block_bytes: None,
+        }
+    }
+
+    /// Creates the default configuration for a chain, bounding blocks by the chain's maximum
+    /// block size. Zero-fee chains have no minimum gas price.
+    pub const fn for_chain(chain_config: &EvolveChainConfig) -> Self {
+        let config = Self::new().with_max_block_bytes(chain_config.max_block_bytes);
+        if chain_config.zero_fee {
+            config.with_min_gas_price(0)
+        } else {
+            config
+        }
+    }
+
+    /// Sets the maximum number of transactions per block
+    pub const fn with_max_transactions(mut self, max_transactions: usize) -> Self {
+        self.max_transactions = max_transactions;
+        self
+    }
+
+    /// Sets the minimum effective gas price of included transactions
+    pub const fn with_min_gas_price(mut self, min_gas_price: u128) -> Self {
+        self.min_gas_price = min_gas_price;
+        self
+    }
+
+    /// Sets the maximum size of the RLP-encoded block
+    pub const fn with_max_block_bytes(mut self, max_block_bytes: Option<u64>) -> Self {
+        self.max_block_bytes = max_block_bytes;
+        self
     }
 
     /// Validates the configuration
     pub const fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_transactions == 0 {
+            return Err(ConfigError::ZeroMaxTransactions);
+        }
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            if max_block_bytes <= BLOCK_OVERHEAD_BYTES {
+                return Err(ConfigError::MaxBlockBytesTooSmall(max_block_bytes));
+            }
+        }
         Ok(())
     }
 }
@@ -22,4 +83,18 @@ pub enum ConfigError {
     /// Invalid configuration provided
     #[error("Invalid config")]
     InvalidConfig,
+    /// Blocks would not hold any transaction
+    #[error("max_transactions must be at least 1")]
+    ZeroMaxTransactions,
+    /// Blocks would not hold any transaction next to the block overhead
+    #[error("max_block_bytes of {0} leaves no room for transactions")]
+    MaxBlockBytesTooSmall(u64),
+    /// Blocks could exceed the maximum block size of the chain
+    #[error("max_block_bytes of {max_block_bytes} exceeds the chain's maximum of {chain_max}")]
+    MaxBlockBytesAboveChain {
+        /// Configured maximum block size
+        max_block_bytes: u64,
+        /// Maximum block size of the chain
+        chain_max: u64,
+    },
 }
@@ -0,0 +1,377 @@
+use crate::evm_factory::RollkitEvmFactory;
+use alloy_primitives::{Address, Bytes, B256};
+use evolve_ev_reth::{
+    predeploys::{BLOCK_TIME_PREDEPLOY_ADDRESS, DA_INFO_PREDEPLOY_ADDRESS, SYSTEM_ADDRESS},
+    DaBlockInfo, EvolveChainConfig, EvolveChainConfigError, EvolveExtraData, RollkitConsensus,
+    TimestampRules,
+};
+use reth_chainspec::ChainSpec;
+use reth_ethereum_primitives::{Block, EthPrimitives, Receipt, TransactionSigned};
+use reth_evm::{
+    block::{
+        BlockExecutionError, BlockExecutor, BlockExecutorFactory, BlockExecutorFor, CommitChanges,
+        ExecutableTx,
+    },
+    eth::{EthBlockExecutionCtx, EthBlockExecutorFactory},
+    execute::{BlockAssembler, BlockAssemblerInput},
+    ConfigureEvm, Database, Evm, EvmEnvFor, EvmFactory, NextBlockEnvAttributes, OnStateHook,
+};
+use reth_evm_ethereum::{EthBlockAssembler, EthEvmConfig, RethReceiptBuilder};
+use reth_execution_types::BlockExecutionResult;
+use reth_node_api::{FullNodeTypes, NodeTypes};
+use reth_node_builder::{components::ExecutorBuilder, BuilderContext};
+use reth_primitives::{Header, SealedBlock, SealedHeader};
+use reth_revm::{context::result::ExecutionResult, DatabaseCommit, Inspector, State};
+use std::{convert::Infallible, sync::Arc};
+
+/// Block executor factory of the wrapped Ethereum EVM configuration
+type EthExecutorFactory =
+    EthBlockExecutorFactory<RethReceiptBuilder, Arc<ChainSpec>, RollkitEvmFactory>;
+
+/// Ethereum EVM configuration creating EVMs with [`RollkitEvmFactory`]
+pub type RollkitEthEvmConfig = EthEvmConfig<ChainSpec, RollkitEvmFactory>;
+
+/// EVM configuration for ev-reth.
+///
+/// Wraps [`EthEvmConfig`] and applies the Evolve system calls before the transactions of
+/// every block. Blocks built by the payload builder and blocks re-executed by followers go
+/// through the same executor, so both sides produce the same state. Its EVMs come from
+/// [`RollkitEvmFactory`], which enforces the execution policies of the chain spec.
+#[derive(Debug, Clone)]
+pub struct RollkitEvmConfig {
+    /// Inner Ethereum EVM configuration
+    inner: RollkitEthEvmConfig,
+    /// Block executor factory applying the Evolve system calls
+    executor_factory: RollkitBlockExecutorFactory,
+    /// Block assembler recording Evolve header extensions
+    block_assembler: RollkitBlockAssembler,
+    /// Whether blocks are built with a zero base fee
+    zero_fee: bool,
+}
+
+impl RollkitEvmConfig {
+    /// Creates a new `RollkitEvmConfig`, reading the Evolve parameters of the chain spec
+    pub fn try_new(chain_spec: Arc<ChainSpec>) -> Result<Self, EvolveChainConfigError> {
+        let config = EvolveChainConfig::from_chain_spec(&chain_spec)?;
+        let evm_factory = RollkitEvmFactory::new(config.deploy_allowlist);
+        Ok(
+            Self::from_inner(EthEvmConfig::new_with_evm_factory(chain_spec, evm_factory))
+                .with_zero_fee(config.zero_fee)
+                .with_timestamp_rules(config.timestamp_rules),
+        )
+    }
+
+    /// Creates a new `RollkitEvmConfig` wrapping the given Ethereum EVM configuration
+    pub fn from_inner(inner: RollkitEthEvmConfig) -> Self {
+        let executor_factory = RollkitBlockExecutorFactory {
+            inner: inner.block_executor_factory().clone(),
+        };
+        let block_assembler = RollkitBlockAssembler {
+            inner: inner.block_assembler().clone(),
+            timestamp_rules: TimestampRules::default(),
+        };
+        Self {
+            inner,
+            executor_factory,
+            block_assembler,
+            zero_fee: false,
+        }
+    }
+
+    /// Sets whether blocks are built with a zero base fee instead of the EIP-1559 one
+    pub const fn with_zero_fee(mut self, zero_fee: bool) -> Self {
+        self.zero_fee = zero_fee;
+        self
+    }
+
+    /// Sets the timestamp rules of the chain, built blocks only record their equal-timestamp
+    /// run where runs are bounded
+    pub const fn with_timestamp_rules(mut self, timestamp_rules: TimestampRules) -> Self {
+        self.block_assembler.timestamp_rules = timestamp_rules;
+        self
+    }
+
+    /// Returns the wrapped Ethereum EVM configuration
+    pub const fn inner(&self) -> &RollkitEthEvmConfig {
+        &self.inner
+    }
+
+    /// Returns the chain spec associated with this configuration
+    pub fn chain_spec(&self) -> &Arc<ChainSpec> {
+        self.inner.chain_spec()
+    }
+}
+
+impl ConfigureEvm for RollkitEvmConfig {
+    type Primitives = EthPrimitives;
+    type Error = Infallible;
+    type NextBlockEnvCtx = NextBlockEnvAttributes;
+    type BlockExecutorFactory = RollkitBlockExecutorFactory;
+    type BlockAssembler = RollkitBlockAssembler;
+
+    fn block_executor_factory(&self) -> &Self::BlockExecutorFactory {
+        &self.executor_factory
+    }
+
+    fn block_assembler(&self) -> &Self::BlockAssembler {
+        &self.block_assembler
+    }
+
+    fn evm_env(&self, header: &Header) -> EvmEnvFor<Self> {
+        self.inner.evm_env(header)
+    }
+
+    fn next_evm_env(
+        &self,
+        parent: &Header,
+        attributes: &NextBlockEnvAttributes,
+    ) -> Result<EvmEnvFor<Self>, Self::Error> {
+        let mut evm_env = self.inner.next_evm_env(parent, attributes)?;
+        if self.zero_fee {
+            // The assembler copies the base fee into the header
+            evm_env.block_env.basefee = 0;
+        }
+        Ok(evm_env)
+    }
+
+    fn context_for_block<'a>(&self, block: &'a SealedBlock<Block>) -> RollkitBlockExecutionCtx<'a> {
+        let extension = EvolveExtraData::decode(&block.header().extra_data).unwrap_or_default();
+        RollkitBlockExecutionCtx {
+            inner: self.inner.context_for_block(block),
+            da_info: extension.da_info,
+            timestamp_millis: extension.timestamp_millis,
+        }
+    }
+
+    fn context_for_next_block(
+        &self,
+        parent: &SealedHeader,
+        attributes: NextBlockEnvAttributes,
+    ) -> RollkitBlockExecutionCtx<'_> {
+        RollkitBlockExecutionCtx {
+            inner: self.inner.context_for_next_block(parent, attributes),
+            da_info: None,
+            timestamp_millis: None,
+        }
+    }
+}
+
+/// Execution context of a Rollkit block
+#[derive(Debug, Clone)]
+pub struct RollkitBlockExecutionCtx<'a> {
+    /// Ethereum execution context
+    pub inner: EthBlockExecutionCtx<'a>,
+    /// DA block info written into the DA info predeploy before executing transactions
+    pub da_info: Option<DaBlockInfo>,
+    /// Millisecond timestamp written into the block time predeploy before executing
+    /// transactions
+    pub timestamp_millis: Option<u64>,
+}
+
+/// Block executor factory creating [`RollkitBlockExecutor`]s
+#[derive(Debug, Clone)]
+pub struct RollkitBlockExecutorFactory {
+    inner: EthExecutorFactory,
+}
+
+impl BlockExecutorFactory for RollkitBlockExecutorFactory {
+    type EvmFactory = RollkitEvmFactory;
+    type ExecutionCtx<'a> = RollkitBlockExecutionCtx<'a>;
+    type Transaction = TransactionSigned;
+    type Receipt = Receipt;
+
+    fn evm_factory(&self) -> &Self::EvmFactory {
+        self.inner.evm_factory()
+    }
+
+    fn create_executor<'a, DB, I>(
+        &'a self,
+        evm: <Self::EvmFactory as EvmFactory>::Evm<&'a mut State<DB>, I>,
+        ctx: Self::ExecutionCtx<'a>,
+    ) -> impl BlockExecutorFor<'a, Self, DB, I>
+    where
+        DB: Database + 'a,
+        I: Inspector<<Self::EvmFactory as EvmFactory>::Context<&'a mut State<DB>>> + 'a,
+    {
+        RollkitBlockExecutor {
+            inner: self.inner.create_executor(evm, ctx.inner),
+            da_info: ctx.da_info,
+            timestamp_millis: ctx.timestamp_millis,
+        }
+    }
+}
+
+/// Block executor applying the Evolve system calls on top of an inner executor
+#[derive(Debug)]
+pub struct RollkitBlockExecutor<E> {
+    inner: E,
+    da_info: Option<DaBlockInfo>,
+    timestamp_millis: Option<u64>,
+}
+
+impl<E> BlockExecutor for RollkitBlockExecutor<E>
+where
+    E: BlockExecutor<Evm: Evm<DB: DatabaseCommit>>,
+{
+    type Transaction = E::Transaction;
+    type Receipt = E::Receipt;
+    type Evm = E::Evm;
+
+    fn apply_pre_execution_changes(&mut self) -> Result<(), BlockExecutionError> {
+        self.inner.apply_pre_execution_changes()?;
+
+        if let Some(da_info) = &self.da_info {
+            apply_system_call(
+                self.inner.evm_mut(),
+                DA_INFO_PREDEPLOY_ADDRESS,
+                da_info.system_call_input(),
+                "DA info",
+            )?;
+        }
+        if let Some(timestamp_millis) = self.timestamp_millis {
+            apply_system_call(
+                self.inner.evm_mut(),
+                BLOCK_TIME_PREDEPLOY_ADDRESS,
+                B256::left_padding_from(&timestamp_millis.to_be_bytes()).into(),
+                "block time",
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn execute_transaction_with_commit_condition(
+        &mut self,
+        tx: impl ExecutableTx<Self>,
+        f: impl FnOnce(&ExecutionResult<<Self::Evm as Evm>::HaltReason>) -> CommitChanges,
+    ) -> Result<Option<u64>, BlockExecutionError> {
+        self.inner.execute_transaction_with_commit_condition(tx, f)
+    }
+
+    fn finish(
+        self,
+    ) -> Result<(Self::Evm, BlockExecutionResult<Self::Receipt>), BlockExecutionError> {
+        self.inner.finish()
+    }
+
+    fn set_state_hook(&mut self, hook: Option<Box<dyn OnStateHook>>) {
+        self.inner.set_state_hook(hook)
+    }
+
+    fn evm_mut(&mut self) -> &mut Self::Evm {
+        self.inner.evm_mut()
+    }
+
+    fn evm(&self) -> &Self::Evm {
+        self.inner.evm()
+    }
+}
+
+/// Calls a predeploy from the system address and commits the resulting state
+fn apply_system_call<EVM>(
+    evm: &mut EVM,
+    predeploy: Address,
+    input: Bytes,
+    name: &str,
+) -> Result<(), BlockExecutionError>
+where
+    EVM: Evm<DB: DatabaseCommit>,
+{
+    let result_and_state = evm
+        .transact_system_call(SYSTEM_ADDRESS, predeploy, input)
+        .map_err(BlockExecutionError::other)?;
+
+    if !result_and_state.result.is_success() {
+        return Err(BlockExecutionError::msg(format!(
+            "{name} system call failed: {:?}",
+            result_and_state.result
+        )));
+    }
+
+    evm.db_mut().commit(result_and_state.state);
+    Ok(())
+}
+
+/// Block assembler recording the Evolve header extensions in `extra_data`
+#[derive(Debug, Clone)]
+pub struct RollkitBlockAssembler {
+    inner: EthBlockAssembler<ChainSpec>,
+    /// Timestamp rules deciding whether blocks record their equal-timestamp run
+    timestamp_rules: TimestampRules,
+}
+
+impl BlockAssembler<RollkitBlockExecutorFactory> for RollkitBlockAssembler {
+    type Block = Block;
+
+    fn assemble_block(
+        &self,
+        input: BlockAssemblerInput<'_, '_, RollkitBlockExecutorFactory>,
+    ) -> Result<Self::Block, BlockExecutionError> {
+        let BlockAssemblerInput {
+            evm_env,
+            execution_ctx,
+            parent,
+            transactions,
+            output,
+            bundle_state,
+            state_provider,
+            state_root,
+        } = input;
+        let da_info = execution_ctx.da_info;
+        let timestamp_millis = execution_ctx.timestamp_millis;
+
+        let mut block = BlockAssembler::<EthExecutorFactory>::assemble_block(
+            &self.inner,
+            BlockAssemblerInput {
+                evm_env,
+                execution_ctx: execution_ctx.inner,
+                parent,
+                transactions,
+                output,
+                bundle_state,
+                state_provider,
+                state_root,
+            },
+        )?;
+
+        let extension = EvolveExtraData {
+            da_info,
+            timestamp_run: self
+                .timestamp_rules
+                .timestamp_run(parent, block.header.timestamp),
+            timestamp_millis,
+            ..Default::default()
+        };
+        if !extension.is_empty() {
+            block.header.extra_data = extension.encode();
+        }
+
+        Ok(block)
+    }
+}
+
+/// Executor builder that installs [`RollkitEvmConfig`] into the node
+#[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
+pub struct RollkitExecutorBuilder;
+
+impl<Node> ExecutorBuilder<Node> for RollkitExecutorBuilder
+where
+    Node: FullNodeTypes<Types: NodeTypes<ChainSpec = ChainSpec, Primitives = EthPrimitives>>,
+{
+    type EVM = RollkitEvmConfig;
+
+    async fn build_evm(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::EVM> {
+        Ok(RollkitEvmConfig::try_new(ctx.chain_spec())?)
+    }
+}
+
+/// Returns the EVM configuration and consensus of the reth commands that execute or validate
+/// blocks outside of a running node, such as `import` and `stage run`, failing if the Evolve
+/// parameters of the chain spec are invalid
+pub fn rollkit_cli_components(
+    chain_spec: Arc<ChainSpec>,
+) -> Result<(RollkitEvmConfig, RollkitConsensus), EvolveChainConfigError> {
+    let consensus = RollkitConsensus::try_new(chain_spec.clone())?;
+    Ok((RollkitEvmConfig::try_new(chain_spec)?, consensus))
+}
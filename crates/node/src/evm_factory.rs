@@ -0,0 +1,333 @@
+//! EVM factory enforcing the Evolve execution policies.
+//!
+//! Every EVM of the node comes from [`RollkitEvmFactory`]: block building, follower
+//! re-execution and the `eth_call` family alike. Policies hook into execution through
+//! [`DeployGuard`], an inspector wrapping the caller's inspector, so they apply whether or not
+//! the caller inspects.
+//!
+//! Running in inspector mode slows execution down, so [`RollkitEvm`] only turns it on for
+//! transactions the guard has to watch: those from origins that may not deploy contracts at
+//! the current height. Other transactions run in inspector mode only if the caller inspects.
+
+use alloy_primitives::{Address, Bytes, Log, U256};
+use evolve_ev_reth::DeployAllowlist;
+use reth_evm::{
+    eth::{EthEvm, EthEvmContext},
+    precompiles::PrecompilesMap,
+    Database, EthEvmFactory, Evm, EvmEnv, EvmFactory,
+};
+use reth_revm::{
+    context::{
+        result::{EVMError, HaltReason, ResultAndState},
+        BlockEnv, TxEnv,
+    },
+    context_interface::{Block as _, ContextTr, Transaction as _},
+    inspector::NoOpInspector,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Gas, InstructionResult, Interpreter,
+        InterpreterResult,
+    },
+    primitives::hardfork::SpecId,
+    Database as _, Inspector,
+};
+use std::{fmt, sync::Arc};
+
+/// Selector of the Solidity `Error(string)` revert reason
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Revert reason of contract creations by origins missing from the allow-list
+pub const DEPLOYER_NOT_ALLOWED: &str = "deployer not allow-listed";
+
+/// Factory creating [`RollkitEvm`]s
+#[derive(Debug, Clone, Default)]
+pub struct RollkitEvmFactory {
+    /// Ethereum EVM factory
+    inner: EthEvmFactory,
+    /// Contract deployment allow-list, if any
+    deploy_allowlist: Option<Arc<DeployAllowlist>>,
+}
+
+impl RollkitEvmFactory {
+    /// Creates a new factory enforcing the given deployment allow-list
+    pub fn new(deploy_allowlist: Option<DeployAllowlist>) -> Self {
+        Self {
+            inner: EthEvmFactory::default(),
+            deploy_allowlist: deploy_allowlist.map(Arc::new),
+        }
+    }
+
+    /// Returns the deployment allow-list enforced by the factory
+    pub fn deploy_allowlist(&self) -> Option<&DeployAllowlist> {
+        self.deploy_allowlist.as_deref()
+    }
+}
+
+impl EvmFactory for RollkitEvmFactory {
+    type Evm<DB: Database, I: Inspector<EthEvmContext<DB>>> = RollkitEvm<DB, I>;
+    type Context<DB: Database> = EthEvmContext<DB>;
+    type Tx = TxEnv;
+    type Error<DBError: core::error::Error + Send + Sync + 'static> = EVMError<DBError>;
+    type HaltReason = HaltReason;
+    type Spec = SpecId;
+    type Precompiles = PrecompilesMap;
+
+    fn create_evm<DB: Database>(&self, db: DB, input: EvmEnv) -> Self::Evm<DB, NoOpInspector> {
+        let mut evm = self.create_evm_with_inspector(db, input, NoOpInspector {});
+        evm.set_inspector_enabled(false);
+        evm
+    }
+
+    fn create_evm_with_inspector<DB: Database, I: Inspector<Self::Context<DB>>>(
+        &self,
+        db: DB,
+        input: EvmEnv,
+        inspector: I,
+    ) -> Self::Evm<DB, I> {
+        let guard = DeployGuard {
+            deploy_allowlist: self.deploy_allowlist.clone(),
+            inner: inspector,
+            inspect_inner: true,
+        };
+        RollkitEvm {
+            inner: self.inner.create_evm_with_inspector(db, input, guard),
+            inspect: true,
+        }
+    }
+}
+
+/// Ethereum EVM running every transaction under a [`DeployGuard`]
+pub struct RollkitEvm<DB: Database, I> {
+    inner: EthEvm<DB, DeployGuard<I>, PrecompilesMap>,
+    /// Whether the caller inspects
+    inspect: bool,
+}
+
+impl<DB, I> RollkitEvm<DB, I>
+where
+    DB: Database,
+    I: Inspector<EthEvmContext<DB>>,
+{
+    /// Runs the next transaction in inspector mode if the caller inspects or if the guard has
+    /// to watch `caller`
+    fn enable_inspector_for(&mut self, caller: Address) {
+        let inspect = self.inspect || self.guards(caller);
+        self.inner.set_inspector_enabled(inspect);
+    }
+
+    /// Returns `true` if `caller` may not create contracts at the current height, so that
+    /// the guard has to watch its transactions
+    fn guards(&mut self, caller: Address) -> bool {
+        let Some(allowlist) = self.inner.inspector().deploy_allowlist.clone() else {
+            return false;
+        };
+        let number = self.inner.block().number.saturating_to::<u64>();
+        let db = self.inner.db_mut();
+        // Read as the guard does, failed reads keep the guard on
+        !allowlist
+            .allows(number, caller, |contract, slot| {
+                db.storage(contract, U256::from_be_bytes(slot.0))
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl<DB: Database, I> fmt::Debug for RollkitEvm<DB, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RollkitEvm").finish_non_exhaustive()
+    }
+}
+
+impl<DB, I> Evm for RollkitEvm<DB, I>
+where
+    DB: Database,
+    I: Inspector<EthEvmContext<DB>>,
+{
+    type DB = DB;
+    type Tx = TxEnv;
+    type Error = EVMError<DB::Error>;
+    type HaltReason = HaltReason;
+    type Spec = SpecId;
+    type Precompiles = PrecompilesMap;
+    type Inspector = I;
+
+    fn block(&self) -> &BlockEnv {
+        self.inner.block()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.inner.chain_id()
+    }
+
+    fn transact_raw(
+        &mut self,
+        tx: Self::Tx,
+    ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
+        self.enable_inspector_for(tx.caller);
+        self.inner.transact_raw(tx)
+    }
+
+    fn transact_system_call(
+        &mut self,
+        caller: Address,
+        contract: Address,
+        data: Bytes,
+    ) -> Result<ResultAndState<Self::HaltReason>, Self::Error> {
+        self.enable_inspector_for(caller);
+        self.inner.transact_system_call(caller, contract, data)
+    }
+
+    fn db_mut(&mut self) -> &mut Self::DB {
+        self.inner.db_mut()
+    }
+
+    fn finish(self) -> (Self::DB, EvmEnv<Self::Spec>) {
+        self.inner.finish()
+    }
+
+    fn set_inspector_enabled(&mut self, enabled: bool) {
+        // Whether the guard itself inspects is decided per transaction
+        self.inspect = enabled;
+        self.inner.inspector_mut().inspect_inner = enabled;
+        self.inner.set_inspector_enabled(enabled);
+    }
+
+    fn precompiles(&self) -> &Self::Precompiles {
+        self.inner.precompiles()
+    }
+
+    fn precompiles_mut(&mut self) -> &mut Self::Precompiles {
+        self.inner.precompiles_mut()
+    }
+
+    fn inspector(&self) -> &Self::Inspector {
+        &self.inner.inspector().inner
+    }
+
+    fn inspector_mut(&mut self) -> &mut Self::Inspector {
+        &mut self.inner.inspector_mut().inner
+    }
+}
+
+/// Inspector enforcing the Evolve execution policies on top of the caller's inspector.
+///
+/// Contract creations by origins missing from the deployment allow-list are reverted with
+/// [`DEPLOYER_NOT_ALLOWED`] before any init code runs. This covers creation transactions as
+/// well as `CREATE` and `CREATE2` from contracts called by the origin.
+#[derive(Debug)]
+pub struct DeployGuard<I> {
+    /// Contract deployment allow-list, if any
+    deploy_allowlist: Option<Arc<DeployAllowlist>>,
+    /// Caller's inspector
+    inner: I,
+    /// Whether the caller's inspector receives the hooks
+    inspect_inner: bool,
+}
+
+impl<I> DeployGuard<I> {
+    /// Returns `true` if the origin of the current transaction may create contracts
+    fn deployment_allowed<CTX: ContextTr>(&self, context: &mut CTX) -> bool {
+        let Some(allowlist) = &self.deploy_allowlist else {
+            return true;
+        };
+        let number = context.block().number().saturating_to::<u64>();
+        let origin = context.tx().caller();
+        // The governance contract is read as of the start of the transaction, failed reads
+        // deny the deployment
+        allowlist
+            .allows(number, origin, |contract, slot| {
+                context
+                    .db_mut()
+                    .storage(contract, U256::from_be_bytes(slot.0))
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl<CTX, I> Inspector<CTX> for DeployGuard<I>
+where
+    CTX: ContextTr,
+    I: Inspector<CTX>,
+{
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        if self.inspect_inner {
+            self.inner.initialize_interp(interp, context);
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        if self.inspect_inner {
+            self.inner.step(interp, context);
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        if self.inspect_inner {
+            self.inner.step_end(interp, context);
+        }
+    }
+
+    fn log(&mut self, interp: &mut Interpreter, context: &mut CTX, log: Log) {
+        if self.inspect_inner {
+            self.inner.log(interp, context, log);
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if self.inspect_inner {
+            return self.inner.call(context, inputs);
+        }
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        if self.inspect_inner {
+            self.inner.call_end(context, inputs, outcome);
+        }
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        if !self.deployment_allowed(context) {
+            return Some(CreateOutcome::new(
+                InterpreterResult::new(
+                    InstructionResult::Revert,
+                    revert_reason(DEPLOYER_NOT_ALLOWED),
+                    Gas::new(inputs.gas_limit),
+                ),
+                None,
+            ));
+        }
+        if self.inspect_inner {
+            return self.inner.create(context, inputs);
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        if self.inspect_inner {
+            self.inner.create_end(context, inputs, outcome);
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.inspect_inner {
+            self.inner.selfdestruct(contract, target, value);
+        }
+    }
+}
+
+/// ABI-encodes `reason` as a Solidity `Error(string)` revert reason
+fn revert_reason(reason: &str) -> Bytes {
+    let padded_len = reason.len().div_ceil(32) * 32;
+    let mut out = Vec::with_capacity(4 + 64 + padded_len);
+    out.extend_from_slice(&ERROR_SELECTOR);
+    out.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+    out.extend_from_slice(&U256::from(reason.len()).to_be_bytes::<32>());
+    out.extend_from_slice(reason.as_bytes());
+    out.resize(4 + 64 + padded_len, 0);
+    out.into()
+}
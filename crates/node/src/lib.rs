@@ -2,6 +2,7 @@
 //!
 //! This crate provides the core node functionality for ev-reth, including:
 //! - Payload builder implementation
+//! - EVM configuration
 //! - Node configuration
 //! - RPC interfaces
 
@@ -9,7 +10,27 @@
 pub mod builder;
 /// Configuration types and validation for the Rollkit payload builder
 pub mod config;
+/// EVM configuration applying the Evolve system calls.
+pub mod evm;
+/// EVM factory enforcing the Evolve execution policies.
+pub mod evm_factory;
+/// Re-execution of existing blocks through the payload builder.
+pub mod replay;
+/// Evolve RPC served by the node.
+pub mod rpc;
+/// Transaction pool enforcing the Evolve admission policies.
+pub mod txpool;
 
 // Re-export public types
-pub use builder::{create_payload_builder_service, RollkitPayloadBuilder};
+pub use builder::{
+    create_payload_builder_service, RollkitBuiltPayload, RollkitPayloadBuilder, SkipReason,
+    SkippedTransaction, TransactionOutcome,
+};
 pub use config::{ConfigError, RollkitPayloadBuilderConfig};
+pub use evm::{rollkit_cli_components, RollkitEvmConfig, RollkitExecutorBuilder};
+pub use evm_factory::{RollkitEvm, RollkitEvmFactory};
+pub use replay::{
+    attributes_from_block, compare_headers, diff_state, AccountDiff, HeaderMismatch, StorageDiff,
+};
+pub use rpc::{EvolveSimulateApiImpl, SimulateBatchRequest, SimulatedBatch};
+pub use txpool::{RollkitPoolBuilder, RollkitTransactionPool, RollkitTransactionValidator};
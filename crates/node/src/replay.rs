@@ -0,0 +1,170 @@
+//! Re-execution of existing blocks through the payload builder.
+//!
+//! Replaying a block rebuilds it from the payload attributes it implies, on top of its
+//! parent's state, so that the result can be compared with the block as it was produced.
+//! When the roots differ, [`diff_state`] narrows the divergence down to accounts and
+//! storage slots.
+
+use alloy_primitives::{Address, B256, U256};
+use evolve_ev_reth::{EvolveExtraData, RollkitPayloadAttributes};
+use reth_primitives::{Header, SealedBlock};
+use reth_primitives_traits::Account;
+use reth_provider::{ProviderResult, StateProvider};
+use reth_revm::db::BundleState;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Returns the payload attributes a block was built from.
+///
+/// Transactions left out by the sequencer are not part of the block, so the attributes
+/// only hold the included ones.
+pub fn attributes_from_block(block: &SealedBlock) -> RollkitPayloadAttributes {
+    let extension = EvolveExtraData::decode(&block.extra_data).unwrap_or_default();
+    let mut attributes = RollkitPayloadAttributes::new(
+        block.body().transactions.clone(),
+        Some(block.gas_limit),
+        block.timestamp,
+        block.mix_hash,
+        block.beneficiary,
+        block.parent_hash,
+        block.number,
+    );
+    attributes.da_info = extension.da_info;
+    attributes.timestamp_millis = extension.timestamp_millis;
+    attributes
+}
+
+/// Header field of a replayed block differing from the expected block
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HeaderMismatch {
+    /// The state roots differ
+    #[error("state root {actual}, expected {expected}")]
+    StateRoot {
+        /// State root of the expected block
+        expected: B256,
+        /// State root of the replayed block
+        actual: B256,
+    },
+    /// The receipts roots differ
+    #[error("receipts root {actual}, expected {expected}")]
+    ReceiptsRoot {
+        /// Receipts root of the expected block
+        expected: B256,
+        /// Receipts root of the replayed block
+        actual: B256,
+    },
+    /// The blocks used different amounts of gas
+    #[error("gas used {actual}, expected {expected}")]
+    GasUsed {
+        /// Gas used by the expected block
+        expected: u64,
+        /// Gas used by the replayed block
+        actual: u64,
+    },
+}
+
+/// Compares the execution results recorded in the headers of an expected and a replayed
+/// block
+pub fn compare_headers(expected: &Header, actual: &Header) -> Vec<HeaderMismatch> {
+    let mut mismatches = Vec::new();
+    if expected.state_root != actual.state_root {
+        mismatches.push(HeaderMismatch::StateRoot {
+            expected: expected.state_root,
+            actual: actual.state_root,
+        });
+    }
+    if expected.receipts_root != actual.receipts_root {
+        mismatches.push(HeaderMismatch::ReceiptsRoot {
+            expected: expected.receipts_root,
+            actual: actual.receipts_root,
+        });
+    }
+    if expected.gas_used != actual.gas_used {
+        mismatches.push(HeaderMismatch::GasUsed {
+            expected: expected.gas_used,
+            actual: actual.gas_used,
+        });
+    }
+    mismatches
+}
+
+/// Account whose state after a replayed block differs from the expected state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// Address of the account
+    pub address: Address,
+    /// Expected account, `None` if it should not exist
+    pub expected: Option<Account>,
+    /// Account after the replayed block, `None` if it does not exist
+    pub actual: Option<Account>,
+    /// Storage slots of the account holding different values
+    pub storage: Vec<StorageDiff>,
+}
+
+/// Storage slot whose value after a replayed block differs from the expected value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageDiff {
+    /// Storage slot
+    pub slot: B256,
+    /// Expected value
+    pub expected: U256,
+    /// Value after the replayed block
+    pub actual: U256,
+}
+
+/// Diffs the state after a replayed block against the expected state after that block.
+///
+/// `changes` are the state changes of the replayed block on top of `parent`. Accounts and
+/// slots the expected block changed are listed in `expected_changes`, so that changes the
+/// replay missed are reported along with those it made differently.
+pub fn diff_state(
+    changes: &BundleState,
+    parent: &dyn StateProvider,
+    expected: &dyn StateProvider,
+    expected_changes: &BTreeMap<Address, BTreeSet<B256>>,
+) -> ProviderResult<Vec<AccountDiff>> {
+    let mut touched = expected_changes.clone();
+    for (address, account) in &changes.state {
+        touched
+            .entry(*address)
+            .or_default()
+            .extend(account.storage.keys().map(|slot| B256::from(*slot)));
+    }
+
+    let mut diffs = Vec::new();
+    for (address, slots) in touched {
+        let changed = changes.account(&address);
+        let actual = match changed {
+            Some(account) => account.info.clone().map(Account::from),
+            None => parent.basic_account(&address)?,
+        };
+        let expected_account = expected.basic_account(&address)?;
+
+        let mut storage = Vec::new();
+        for slot in slots {
+            let actual_value = match changed
+                .and_then(|account| account.storage_slot(U256::from_be_bytes(slot.0)))
+            {
+                Some(value) => value,
+                None => parent.storage(address, slot)?.unwrap_or_default(),
+            };
+            let expected_value = expected.storage(address, slot)?.unwrap_or_default();
+            if actual_value != expected_value {
+                storage.push(StorageDiff {
+                    slot,
+                    expected: expected_value,
+                    actual: actual_value,
+                });
+            }
+        }
+
+        if actual != expected_account || !storage.is_empty() {
+            diffs.push(AccountDiff {
+                address,
+                expected: expected_account,
+                actual,
+                storage,
+            });
+        }
+    }
+    Ok(diffs)
+}
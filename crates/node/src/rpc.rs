@@ -0,0 +1,228 @@
+//! Evolve RPC served by the node, dry-running batches through the payload builder.
+
+use crate::builder::{RollkitPayloadBuilder, TransactionOutcome};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Address, Bytes, Log, TxHash, B256};
+use async_trait::async_trait;
+use evolve_ev_reth::{
+    chain_config::validate_timestamp_millis, DaBlockInfo, RollkitPayloadAttributes,
+};
+use jsonrpsee::types::{
+    error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+    ErrorObjectOwned,
+};
+use jsonrpsee_core::RpcResult;
+use jsonrpsee_proc_macros::rpc;
+use reth_ethereum_primitives::TransactionSigned;
+use reth_primitives::{transaction::SignedTransaction, Header};
+use reth_provider::{HeaderProvider, StateProviderFactory};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Batch of transactions to execute on top of a parent block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBatchRequest {
+    /// EIP-2718 encoded transactions, in execution order
+    pub transactions: Vec<Bytes>,
+    /// Hash of the parent block
+    pub parent_hash: B256,
+    /// Timestamp of the simulated block
+    pub timestamp: u64,
+    /// Millisecond timestamp of the simulated block, within the second of `timestamp`
+    #[serde(default)]
+    pub timestamp_millis: Option<u64>,
+    /// Gas limit of the simulated block, at most and by default the parent's
+    #[serde(default)]
+    pub gas_limit: Option<u64>,
+    /// Fee recipient of the simulated block
+    #[serde(default)]
+    pub fee_recipient: Address,
+    /// DA layer block info applied by the simulated block
+    #[serde(default)]
+    pub da_info: Option<DaBlockInfo>,
+}
+
+/// Block a batch would produce, with the outcome of each of its transactions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBatch {
+    /// Hash of the block
+    pub block_hash: B256,
+    /// Header of the block, including its state root
+    pub header: Header,
+    /// Outcome of each transaction of the batch, in order
+    pub transactions: Vec<SimulatedTransaction>,
+}
+
+/// Outcome of a simulated transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTransaction {
+    /// Transaction hash
+    pub hash: TxHash,
+    /// Whether and how the transaction made it into the block
+    pub status: SimulatedTransactionStatus,
+    /// Gas used by the transaction, zero if it was left out of the block
+    pub gas_used: u64,
+    /// Logs emitted by the transaction
+    pub logs: Vec<Log>,
+    /// Why the transaction was left out of the block, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Status of a simulated transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimulatedTransactionStatus {
+    /// Included and succeeded
+    Success,
+    /// Included but reverted or halted
+    Reverted,
+    /// Rejected by the EVM and left out of the block
+    Invalid,
+    /// Left out under the payload builder's limits
+    Skipped,
+}
+
+impl SimulatedTransaction {
+    fn new(hash: TxHash, outcome: TransactionOutcome) -> Self {
+        let (status, gas_used, logs, error) = match outcome {
+            TransactionOutcome::Included {
+                success,
+                gas_used,
+                logs,
+            } => {
+                let status = if success {
+                    SimulatedTransactionStatus::Success
+                } else {
+                    SimulatedTransactionStatus::Reverted
+                };
+                (status, gas_used, logs, None)
+            }
+            TransactionOutcome::Invalid(error) => (
+                SimulatedTransactionStatus::Invalid,
+                0,
+                Vec::new(),
+                Some(error),
+            ),
+            TransactionOutcome::Skipped(reason) => (
+                SimulatedTransactionStatus::Skipped,
+                0,
+                Vec::new(),
+                Some(reason.to_string()),
+            ),
+        };
+        Self {
+            hash,
+            status,
+            gas_used,
+            logs,
+            error,
+        }
+    }
+}
+
+/// Evolve batch simulation RPC API trait
+#[rpc(server, namespace = "evolve")]
+pub trait EvolveSimulateApi {
+    /// Executes a batch on top of its parent the way the payload builder would, without
+    /// inserting the resulting block
+    #[method(name = "simulateBatch")]
+    async fn simulate_batch(&self, request: SimulateBatchRequest) -> RpcResult<SimulatedBatch>;
+}
+
+/// Implementation of the Evolve batch simulation RPC API
+#[derive(Debug)]
+pub struct EvolveSimulateApiImpl<Client> {
+    /// Payload builder executing the batches
+    builder: Arc<RollkitPayloadBuilder<Client>>,
+}
+
+impl<Client> EvolveSimulateApiImpl<Client> {
+    /// Creates a new instance of `EvolveSimulateApi`.
+    pub const fn new(builder: Arc<RollkitPayloadBuilder<Client>>) -> Self {
+        Self { builder }
+    }
+}
+
+#[async_trait]
+impl<Client> EvolveSimulateApiServer for EvolveSimulateApiImpl<Client>
+where
+    Client: StateProviderFactory + HeaderProvider<Header = Header> + Send + Sync + 'static,
+{
+    async fn simulate_batch(&self, request: SimulateBatchRequest) -> RpcResult<SimulatedBatch> {
+        let transactions = request
+            .transactions
+            .iter()
+            .map(|raw| {
+                TransactionSigned::decode_2718(&mut raw.as_ref())
+                    .map_err(|err| invalid_params(format!("invalid transaction: {err}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let parent = self
+            .builder
+            .client
+            .header(&request.parent_hash)
+            .map_err(|err| internal_error(err.to_string()))?
+            .ok_or_else(|| invalid_params(format!("unknown parent {}", request.parent_hash)))?;
+
+        // The block must be one the chain could accept, so its gas limit may not exceed the
+        // parent's, which also bounds the work a single call can cause
+        let gas_limit = request.gas_limit.unwrap_or(parent.gas_limit);
+        if gas_limit > parent.gas_limit {
+            return Err(invalid_params(format!(
+                "gas limit {gas_limit} exceeds the parent's gas limit {}",
+                parent.gas_limit
+            )));
+        }
+        validate_timestamp_millis(request.timestamp, request.timestamp_millis, &parent)
+            .map_err(|err| invalid_params(err.to_string()))?;
+
+        let hashes: Vec<TxHash> = transactions.iter().map(|tx| *tx.hash()).collect();
+        let mut attributes = RollkitPayloadAttributes::new(
+            transactions,
+            Some(gas_limit),
+            request.timestamp,
+            B256::ZERO,
+            request.fee_recipient,
+            request.parent_hash,
+            parent.number + 1,
+        );
+        attributes.da_info = request.da_info;
+        attributes.timestamp_millis = request.timestamp_millis;
+        attributes
+            .validate()
+            .map_err(|err| invalid_params(err.to_string()))?;
+
+        // Executing the batch is blocking work, keep it off the RPC server's async tasks
+        let builder = Arc::clone(&self.builder);
+        let runtime = tokio::runtime::Handle::current();
+        let built = tokio::task::spawn_blocking(move || {
+            runtime.block_on(builder.build_payload_with_report(attributes))
+        })
+        .await
+        .map_err(|err| internal_error(err.to_string()))?
+        .map_err(|err| internal_error(err.to_string()))?;
+        Ok(SimulatedBatch {
+            block_hash: built.block.hash(),
+            header: built.block.header().clone(),
+            transactions: hashes
+                .into_iter()
+                .zip(built.outcomes)
+                .map(|(hash, outcome)| SimulatedTransaction::new(hash, outcome))
+                .collect(),
+        })
+    }
+}
+
+/// Creates an invalid params JSON-RPC error
+fn invalid_params(message: String) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INVALID_PARAMS_CODE, message, None::<()>)
+}
+
+/// Creates an internal JSON-RPC error
+fn internal_error(message: String) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, message, None::<()>)
+}
@@ -0,0 +1,228 @@
+//! Transaction pool enforcing the Evolve admission policies.
+//!
+//! The pool is the Ethereum pool with [`RollkitTransactionValidator`] in front of the
+//! Ethereum validator, so transactions the EVM would refuse never reach a block.
+
+use crate::config::RollkitPayloadBuilderConfig;
+use alloy_consensus::{BlockHeader, Transaction as _};
+use alloy_primitives::{Address, BlockNumber};
+use evolve_ev_reth::{DeployAllowlist, EvolveChainConfig};
+use reth_chainspec::ChainSpec;
+use reth_ethereum_primitives::EthPrimitives;
+use reth_node_api::{FullNodeTypes, NodeTypes};
+use reth_node_builder::{
+    components::{PoolBuilder, TxPoolBuilder},
+    BuilderContext,
+};
+use reth_primitives_traits::{Block, SealedBlock};
+use reth_provider::{ProviderError, StateProviderFactory};
+use reth_transaction_pool::{
+    blobstore::DiskFileBlobStore,
+    error::{InvalidPoolTransactionError, PoolTransactionError},
+    CoinbaseTipOrdering, EthPooledTransaction, EthTransactionValidator, Pool, PoolTransaction,
+    TransactionOrigin, TransactionValidationOutcome, TransactionValidationTaskExecutor,
+    TransactionValidator,
+};
+use std::{
+    any::Any,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::info;
+
+/// Transaction pool of ev-reth
+pub type RollkitTransactionPool<Client, S> = Pool<
+    TransactionValidationTaskExecutor<
+        RollkitTransactionValidator<Client, EthTransactionValidator<Client, EthPooledTransaction>>,
+    >,
+    CoinbaseTipOrdering<EthPooledTransaction>,
+    S,
+>;
+
+/// Validator applying the Evolve admission policies before the wrapped validator
+#[derive(Debug)]
+pub struct RollkitTransactionValidator<Client, V> {
+    /// Wrapped validator
+    inner: V,
+    /// Provider of the latest state
+    client: Client,
+    /// Contract deployment allow-list, if any
+    deploy_allowlist: Option<DeployAllowlist>,
+    /// Number of the canonical head
+    head_number: AtomicU64,
+}
+
+impl<Client, V> RollkitTransactionValidator<Client, V> {
+    /// Creates a new validator wrapping `inner`, with the canonical head at `head_number`
+    pub const fn new(
+        inner: V,
+        client: Client,
+        deploy_allowlist: Option<DeployAllowlist>,
+        head_number: BlockNumber,
+    ) -> Self {
+        Self {
+            inner,
+            client,
+            deploy_allowlist,
+            head_number: AtomicU64::new(head_number),
+        }
+    }
+
+    /// Returns the wrapped validator
+    pub const fn inner(&self) -> &V {
+        &self.inner
+    }
+}
+
+impl<Client, V> RollkitTransactionValidator<Client, V>
+where
+    Client: StateProviderFactory,
+    V: TransactionValidator,
+{
+    /// Returns `true` if `transaction` may be included in the next block under the
+    /// deployment allow-list
+    fn deployment_allowed(&self, transaction: &V::Transaction) -> Result<bool, ProviderError> {
+        let Some(allowlist) = &self.deploy_allowlist else {
+            return Ok(true);
+        };
+        if !transaction.is_create() {
+            return Ok(true);
+        }
+
+        let next_block = self.head_number.load(Ordering::Relaxed) + 1;
+        allowlist.allows(next_block, transaction.sender(), |contract, slot| {
+            Ok(self
+                .client
+                .latest()?
+                .storage(contract, slot)?
+                .unwrap_or_default())
+        })
+    }
+}
+
+impl<Client, V> TransactionValidator for RollkitTransactionValidator<Client, V>
+where
+    Client: StateProviderFactory + std::fmt::Debug + Send + Sync,
+    V: TransactionValidator,
+{
+    type Transaction = V::Transaction;
+
+    async fn validate_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        match self.deployment_allowed(&transaction) {
+            Ok(true) => {}
+            Ok(false) => {
+                let err = DeployNotAllowed {
+                    deployer: transaction.sender(),
+                };
+                return TransactionValidationOutcome::Invalid(
+                    transaction,
+                    InvalidPoolTransactionError::other(err),
+                );
+            }
+            Err(err) => {
+                return TransactionValidationOutcome::Error(*transaction.hash(), Box::new(err))
+            }
+        }
+
+        self.inner.validate_transaction(origin, transaction).await
+    }
+
+    fn on_new_head_block<B>(&self, new_tip_block: &SealedBlock<B>)
+    where
+        B: Block,
+    {
+        self.head_number
+            .store(new_tip_block.header().number(), Ordering::Relaxed);
+        self.inner.on_new_head_block(new_tip_block);
+    }
+}
+
+/// A contract creation sent by an origin missing from the deployment allow-list
+#[derive(Debug, thiserror::Error)]
+#[error("contract deployment by {deployer} is not allow-listed")]
+pub struct DeployNotAllowed {
+    /// Origin of the transaction
+    pub deployer: Address,
+}
+
+impl PoolTransactionError for DeployNotAllowed {
+    fn is_bad_transaction(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Pool builder installing [`RollkitTransactionValidator`] into the node
+#[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
+pub struct RollkitPoolBuilder {
+    /// Minimum gas price of the payload builder, the chain's default when unset
+    min_gas_price: Option<u128>,
+}
+
+impl RollkitPoolBuilder {
+    /// Aligns the pool's minimum priority fee with the payload builder's minimum gas price
+    pub const fn with_min_gas_price(mut self, min_gas_price: Option<u128>) -> Self {
+        self.min_gas_price = min_gas_price;
+        self
+    }
+}
+
+impl<Node> PoolBuilder<Node> for RollkitPoolBuilder
+where
+    Node: FullNodeTypes<Types: NodeTypes<ChainSpec = ChainSpec, Primitives = EthPrimitives>>,
+{
+    type Pool = RollkitTransactionPool<Node::Provider, DiskFileBlobStore>;
+
+    async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
+        let config = EvolveChainConfig::from_chain_spec(&ctx.chain_spec())?;
+        let mut pool_config = ctx.pool_config();
+        if config.zero_fee {
+            // Admit zero-priced transactions, which the protocol minimum fee cap would reject
+            pool_config.minimal_protocol_basefee = 0;
+        }
+        // Transactions the payload builder would skip should not linger in the pool
+        let min_gas_price = self
+            .min_gas_price
+            .unwrap_or(RollkitPayloadBuilderConfig::for_chain(&config).min_gas_price);
+        if min_gas_price > 0 {
+            pool_config.minimum_priority_fee = Some(
+                pool_config
+                    .minimum_priority_fee
+                    .map_or(min_gas_price, |fee| fee.max(min_gas_price)),
+            );
+        }
+        let blob_store =
+            DiskFileBlobStore::open(ctx.config().datadir().blobstore(), Default::default())?;
+
+        let client = ctx.provider().clone();
+        let head_number = ctx.head().number;
+        let validator = TransactionValidationTaskExecutor::eth_builder(ctx.provider().clone())
+            .with_head_timestamp(ctx.head().timestamp)
+            .kzg_settings(ctx.kzg_settings()?)
+            .with_local_transactions_config(pool_config.local_transactions_config.clone())
+            .set_tx_fee_cap(ctx.config().rpc.rpc_tx_fee_cap)
+            .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
+            .build_with_tasks(ctx.task_executor().clone(), blob_store.clone())
+            .map(|inner| {
+                RollkitTransactionValidator::new(
+                    inner,
+                    client,
+                    config.deploy_allowlist,
+                    head_number,
+                )
+            });
+
+        let pool = TxPoolBuilder::new(ctx)
+            .with_validator(validator)
+            .build_and_spawn_maintenance_task(blob_store, pool_config)?;
+        info!(target: "reth::cli", "Transaction pool initialized");
+        Ok(pool)
+    }
+}
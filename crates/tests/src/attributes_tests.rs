@@ -0,0 +1,62 @@
+//! Tests of the Engine API payload attributes accepted by ev-reth.
+
+use alloy_primitives::{Address, B256};
+use alloy_rpc_types::engine::{PayloadAttributes as EthPayloadAttributes, PayloadId};
+use ev_reth::attributes::{RollkitEnginePayloadAttributes, RollkitEnginePayloadBuilderAttributes};
+use evolve_ev_reth::DaBlockInfo;
+use reth_ethereum::node::api::payload::PayloadBuilderAttributes;
+
+use crate::common::{TEST_GAS_LIMIT, TEST_TIMESTAMP};
+
+fn attributes(
+    da_info: Option<DaBlockInfo>,
+    timestamp_millis: Option<u64>,
+) -> RollkitEnginePayloadAttributes {
+    RollkitEnginePayloadAttributes {
+        inner: EthPayloadAttributes {
+            timestamp: TEST_TIMESTAMP,
+            prev_randao: B256::ZERO,
+            suggested_fee_recipient: Address::ZERO,
+            withdrawals: Some(Vec::new()),
+            parent_beacon_block_root: Some(B256::ZERO),
+        },
+        transactions: None,
+        gas_limit: Some(TEST_GAS_LIMIT),
+        da_info,
+        timestamp_millis,
+    }
+}
+
+fn payload_id(attributes: RollkitEnginePayloadAttributes) -> PayloadId {
+    RollkitEnginePayloadBuilderAttributes::try_new(B256::repeat_byte(1), attributes, 3)
+        .unwrap()
+        .payload_id()
+}
+
+/// Attributes differing only in their DA info start separate payload jobs
+#[test]
+fn test_payload_id_covers_da_info() {
+    let da_info = |da_height| Some(DaBlockInfo::new(da_height, B256::repeat_byte(0xda), 1));
+
+    let id = payload_id(attributes(da_info(1), None));
+    assert_eq!(payload_id(attributes(da_info(1), None)), id);
+    assert_ne!(payload_id(attributes(da_info(2), None)), id);
+    assert_ne!(payload_id(attributes(None, None)), id);
+}
+
+/// Attributes differing only in their millisecond timestamp start separate payload jobs
+#[test]
+fn test_payload_id_covers_timestamp_millis() {
+    let millis = |offset| Some(TEST_TIMESTAMP * 1000 + offset);
+
+    let id = payload_id(attributes(None, millis(250)));
+    assert_eq!(payload_id(attributes(None, millis(250))), id);
+    assert_ne!(payload_id(attributes(None, millis(500))), id);
+    assert_ne!(payload_id(attributes(None, None)), id);
+
+    let da_info = Some(DaBlockInfo::new(1, B256::repeat_byte(0xda), 1));
+    assert_ne!(
+        payload_id(attributes(da_info, millis(250))),
+        payload_id(attributes(da_info, millis(500)))
+    );
+}
@@ -0,0 +1,175 @@
+//! Tests for `ev-reth export-batches` and `ev-reth import-batches`.
+//!
+//! The fixtures in `fixtures/batches` were encoded field by field from the `evnode.v1`
+//! protobuf definitions and length-delimited the way Go's `protodelim` writes them, without
+//! going through prost, so they pin the wire format ev-node reads and writes.
+
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{b256, Address, B256, U256};
+use clap::Parser;
+use ev_reth::commands::batches::{
+    block_from_batch, data_hash, import_batches, read_batches, read_headers, write_batches,
+    BatchFormat, ExportBatchesCommand,
+};
+use evolve_ev_reth::EvolveExtraData;
+use eyre::Result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::TEST_CHAIN_ID,
+    node::{encode_transactions, transfer, TestNode},
+};
+
+/// Hashes of the fixture batches, computed with SHA-256 outside of ev-reth
+const FIXTURE_HASHES: [B256; 2] = [
+    b256!("aa867cbbbe8f5ae5d00ce9ade786bfef342cbc15e977898dfd6aababb5724427"),
+    b256!("a80f0d7ad48d46fa3882eb7bead886399f66d2a9071bd80565f717ddf05f90b6"),
+];
+
+/// Returns the path of a batch fixture
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/batches")
+        .join(name)
+}
+
+/// Batches written in Go's format decode to the blocks they were made from, chain through
+/// their data hashes and encode back to the same bytes
+#[test]
+fn test_batch_fixtures() -> Result<()> {
+    let batches = read_batches(&fixture("data.bin"), BatchFormat::Data)?;
+    assert_eq!(batches.len(), 2);
+
+    let first = batches[0].metadata.as_ref().expect("metadata");
+    assert_eq!(first.chain_id, "evolve-test");
+    assert_eq!(first.height, 1);
+    assert_eq!(first.time, 1_700_000_000_123_000_000);
+    assert!(first.last_data_hash.is_empty());
+    assert_eq!(
+        batches[0].txs,
+        vec![vec![0x02, 0xaa], vec![0x01, 0xbb, 0xcc]]
+    );
+
+    let second = batches[1].metadata.as_ref().expect("metadata");
+    assert_eq!(second.height, 2);
+    assert_eq!(second.time, 1_700_000_000_456_000_000);
+    assert!(batches[1].txs.is_empty());
+
+    // Each batch links to the hash of the one before it
+    let hashes: Vec<_> = batches.iter().map(data_hash).collect();
+    assert_eq!(hashes, FIXTURE_HASHES);
+    assert_eq!(second.last_data_hash, hashes[0].as_slice());
+
+    // Signed batches posted to DA carry the same data, the signature and signer are skipped
+    let signed = read_batches(&fixture("signed_data.bin"), BatchFormat::SignedData)?;
+    assert_eq!(signed, batches);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("data.bin");
+    write_batches(&path, &batches)?;
+    assert_eq!(fs::read(path)?, fs::read(fixture("data.bin"))?);
+    Ok(())
+}
+
+/// Exported blocks import into a fresh node as the same blocks, with the sequencer's fee
+/// recipient rather than the importing node's, and batches that do not match their headers
+/// are refused
+#[tokio::test]
+async fn test_export_import_batches_round_trip() -> Result<()> {
+    const BLOCKS: u64 = 3;
+    let node = TestNode::launch().await?;
+    let sequencer = node.execution_service(Address::repeat_byte(0xaa));
+    let signer = node.signer(0);
+    let to = Address::with_last_byte(1);
+
+    let (mut state_root, _) = sequencer.init_chain(1, &TEST_CHAIN_ID.to_string(), None)?;
+    let genesis_millis = EvolveExtraData::timestamp_millis_of(node.chain_spec().genesis_header());
+    let mut hashes = Vec::new();
+    for number in 1..=BLOCKS {
+        let tx = transfer(&signer, number - 1, to, U256::from(number))?;
+        let executed = sequencer
+            .execute_txs(
+                encode_transactions([&tx]),
+                number,
+                genesis_millis + number * 1_000,
+                state_root,
+            )
+            .await?;
+        state_root = executed.state_root;
+        hashes.push(executed.hash);
+    }
+    drop(sequencer);
+
+    let dir = tempfile::tempdir()?;
+    let batches = dir.path().join("batches.bin");
+    let headers = dir.path().join("headers.rlp");
+    let (output, headers_output) = (batches.clone(), headers.clone());
+    let node = node
+        .restart_after(move |datadir| {
+            ExportBatchesCommand::try_parse_from([
+                "export-batches",
+                "--chain",
+                datadir.join("genesis.json").to_str().unwrap(),
+                "--datadir",
+                datadir.to_str().unwrap(),
+                "--from",
+                "1",
+                "--to",
+                &BLOCKS.to_string(),
+                "--output",
+                output.to_str().unwrap(),
+                "--headers",
+                headers_output.to_str().unwrap(),
+            ])?
+            .execute()
+        })
+        .await?;
+
+    // The exported batches chain through their data hashes
+    let exported = read_batches(&batches, BatchFormat::Data)?;
+    assert_eq!(exported.len() as u64, BLOCKS);
+    assert!(exported[0]
+        .metadata
+        .as_ref()
+        .unwrap()
+        .last_data_hash
+        .is_empty());
+    for pair in exported.windows(2) {
+        let next = pair[1].metadata.as_ref().unwrap();
+        assert_eq!(next.last_data_hash, data_hash(&pair[0]).as_slice());
+    }
+
+    // A batch missing a transaction of its block is refused
+    let exported_headers = read_headers(&headers)?;
+    let mut tampered = exported[1].clone();
+    tampered.txs.clear();
+    let err = block_from_batch(exported_headers[1].clone(), &tampered).unwrap_err();
+    assert!(err.to_string().contains("do not match"), "{err}");
+
+    // The follower has another fee recipient, which the imported blocks do not pick up
+    let follower = TestNode::launch().await?;
+    let service = follower.execution_service(Address::repeat_byte(0xbb));
+    import_batches(&service, &batches, &headers, BatchFormat::Data).await?;
+    assert_eq!(follower.block_number().await?, BLOCKS);
+    for (number, hash) in (1..=BLOCKS).zip(&hashes) {
+        let block = follower
+            .block_by_number(BlockNumberOrTag::Number(number), false)
+            .await?
+            .expect("imported block should exist");
+        assert_eq!(block.header.hash, *hash);
+        assert_eq!(block.header.beneficiary, Address::repeat_byte(0xaa));
+    }
+    assert_eq!(
+        follower.balance(to).await?,
+        node.balance(to).await?,
+        "state should match the exporting node"
+    );
+
+    // Importing again is a no-op, the follower already holds the same blocks
+    import_batches(&service, &batches, &headers, BatchFormat::Data).await?;
+    assert_eq!(follower.block_number().await?, BLOCKS);
+    Ok(())
+}
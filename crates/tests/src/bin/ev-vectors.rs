@@ -0,0 +1,89 @@
+//! `ev-vectors` generates and checks golden vectors of the blocks ev-reth builds from
+//! payload attributes.
+//!
+//! Vectors are produced on an in-process node, from a YAML or JSON [`VectorSpec`]:
+//!
+//! ```text
+//! ev-vectors generate crates/tests/vectors/specs/transfers.yaml --output crates/tests/vectors/transfers.json
+//! ev-vectors check crates/tests/vectors/*.json
+//! ev-vectors bless
+//! ```
+
+use clap::{Parser, Subcommand};
+use ev_tests::golden::{specs_dir, vectors_dir, GoldenVector, VectorSpec};
+use eyre::WrapErr;
+use std::path::PathBuf;
+
+/// Generate and check golden vectors of attributes-to-block determinism
+#[derive(Debug, Parser)]
+#[command(name = "ev-vectors")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Build the blocks of a spec and write their attributes and commitments
+    Generate {
+        /// YAML or JSON spec of the vector
+        spec: PathBuf,
+        /// Output file, the vector is printed to stdout if omitted
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Rebuild the blocks of vectors and compare them with the expected ones
+    Check {
+        /// Vector files
+        #[arg(required = true)]
+        vectors: Vec<PathBuf>,
+    },
+    /// Regenerate the vector of every spec of `crates/tests/vectors/specs`, after an intended
+    /// consensus change
+    Bless,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    match Cli::parse().command {
+        Command::Generate { spec, output } => {
+            let vector = VectorSpec::from_file(&spec)
+                .wrap_err_with(|| format!("{}", spec.display()))?
+                .generate()
+                .await?;
+            match output {
+                Some(output) => {
+                    vector.write(&output)?;
+                    eprintln!(
+                        "wrote {} blocks of {} to {}",
+                        vector.blocks.len(),
+                        vector.name,
+                        output.display()
+                    );
+                }
+                None => println!("{}", serde_json::to_string_pretty(&vector)?),
+            }
+        }
+        Command::Check { vectors } => {
+            for path in vectors {
+                GoldenVector::from_file(&path)
+                    .wrap_err_with(|| format!("{}", path.display()))?
+                    .check()
+                    .await
+                    .wrap_err_with(|| format!("{}", path.display()))?;
+                eprintln!("{}: ok", path.display());
+            }
+        }
+        Command::Bless => {
+            for (path, spec) in VectorSpec::from_dir(&specs_dir())? {
+                let output = vectors_dir().join(format!("{}.json", spec.name));
+                spec.generate()
+                    .await
+                    .wrap_err_with(|| format!("{}", path.display()))?
+                    .write(&output)?;
+                eprintln!("wrote {}", output.display());
+            }
+        }
+    }
+    Ok(())
+}
@@ -10,12 +10,11 @@ use alloy_primitives::{Address, Bytes, ChainId, Signature, TxKind, B256, U256};
 use eyre::Result;
 use reth_chainspec::{ChainSpecBuilder, MAINNET};
 use reth_ethereum_primitives::TransactionSigned;
-use reth_evm_ethereum::EthEvmConfig;
 use reth_primitives::{Header, Transaction};
 use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
 use tempfile::TempDir;
 
-use ev_node::RollkitPayloadBuilder;
+use ev_node::{RollkitEvmConfig, RollkitPayloadBuilder, RollkitPayloadBuilderConfig};
 use evolve_ev_reth::RollkitPayloadAttributes;
 
 // Test constants
@@ -77,9 +76,11 @@ impl RollkitTestFixture {
             .chain(reth_chainspec::Chain::from_id(TEST_CHAIN_ID))
             .cancun_activated()
             .build();
-        let evm_config = EthEvmConfig::new(Arc::new(test_chainspec));
+        let evm_config = RollkitEvmConfig::try_new(Arc::new(test_chainspec)).unwrap();
 
-        let builder = RollkitPayloadBuilder::new(Arc::new(provider.clone()), evm_config);
+        // Test transactions are unpriced, so include them regardless of their gas price
+        let builder = RollkitPayloadBuilder::new(Arc::new(provider.clone()), evm_config)
+            .with_config(RollkitPayloadBuilderConfig::new().with_min_gas_price(0));
 
         let fixture = Self {
             builder,
@@ -119,6 +120,30 @@ impl RollkitTestFixture {
         }
     }
 
+    /// Creates a transaction to `to` at `gas_price` from a fresh, funded sender
+    pub fn create_funded_transaction(&self, to: Address, gas_price: u128) -> TransactionSigned {
+        let transaction = TransactionSigned::new_unhashed(
+            Transaction::Legacy(TxLegacy {
+                chain_id: Some(ChainId::from(TEST_CHAIN_ID)),
+                nonce: 0,
+                gas_price,
+                gas_limit: 21_000,
+                to: TxKind::Call(to),
+                value: U256::ZERO,
+                input: Bytes::default(),
+            }),
+            Signature::test_signature(),
+        );
+        let sender = transaction
+            .recover_signer()
+            .expect("test signature should recover");
+        self.provider.add_account(
+            sender,
+            ExtendedAccount::new(0, U256::from(1_000_000_000_000_000_000u64)),
+        );
+        transaction
+    }
+
     /// Adds a mock header to the provider for proper parent lookups
     pub fn add_mock_header(&self, hash: B256, number: u64, state_root: B256, timestamp: u64) {
         let header = Header {
@@ -0,0 +1,51 @@
+//! Tests of the DA inclusion RPCs served by an in-process node.
+
+use alloy_eips::BlockId;
+use alloy_primitives::Bytes;
+use evolve_ev_reth::{da_inclusion::DaInclusionEntry, DaInclusion};
+use eyre::Result;
+use jsonrpsee::rpc_params;
+
+use crate::node::TestNode;
+
+/// `evolve_setDAInclusion` is only served on the authenticated Engine API endpoint, while the
+/// getters are served on both
+#[tokio::test]
+async fn test_set_da_inclusion_requires_authentication() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let payload = node.build_block(Vec::new()).await?;
+    let inclusion = DaInclusion {
+        da_height: 1_000,
+        namespace: Bytes::from_static(&[0x01; 29]),
+        commitment: Bytes::from_static(&[0x02; 32]),
+    };
+
+    assert!(node
+        .request::<DaInclusionEntry>("evolve_setDAInclusion", rpc_params![1u64, &inclusion])
+        .await
+        .is_err());
+    let entry: Option<DaInclusionEntry> = node
+        .request("evolve_getDAInclusion", rpc_params![BlockId::number(1)])
+        .await?;
+    assert_eq!(entry, None);
+
+    let entry: DaInclusionEntry = node
+        .engine_request("evolve_setDAInclusion", rpc_params![1u64, &inclusion])
+        .await?;
+    assert_eq!(entry.block_number, 1);
+    assert_eq!(
+        entry.block_hash,
+        payload.payload_inner.payload_inner.block_hash
+    );
+    assert_eq!(entry.inclusion, inclusion);
+
+    let public: Option<DaInclusionEntry> = node
+        .request("evolve_getDAInclusion", rpc_params![BlockId::number(1)])
+        .await?;
+    assert_eq!(public.as_ref(), Some(&entry));
+    let range: Vec<DaInclusionEntry> = node
+        .request("evolve_getDAInclusionRange", rpc_params![0u64, 1u64])
+        .await?;
+    assert_eq!(range, vec![entry]);
+    Ok(())
+}
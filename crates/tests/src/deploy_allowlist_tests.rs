@@ -0,0 +1,209 @@
+//! Tests for the contract deployment allow-list enforced by the Rollkit EVM factory.
+
+use alloy_primitives::{address, bytes, Address, Bytes, TxKind, B256, U256};
+use ev_node::{evm_factory::DEPLOYER_NOT_ALLOWED, RollkitEvmFactory};
+use evolve_ev_reth::DeployAllowlist;
+use reth_evm::{Evm, EvmEnv, EvmFactory};
+use reth_revm::{
+    context::{result::ExecutionResult, TxEnv},
+    db::{CacheDB, EmptyDB},
+    inspector::NoOpInspector,
+    state::{AccountInfo, Bytecode},
+};
+use std::time::{Duration, Instant};
+
+const DEPLOYER: Address = address!("0x00000000000000000000000000000000000000d1");
+const OUTSIDER: Address = address!("0x00000000000000000000000000000000000000d2");
+const FACTORY: Address = address!("0x00000000000000000000000000000000000000fa");
+const GOVERNANCE: Address = address!("0x0000000000000000000000000000000000000c0d");
+const LOOP: Address = address!("0x00000000000000000000000000000000000000f0");
+
+/// Init code of an empty contract
+const INIT_CODE: Bytes = bytes!("60006000f3");
+
+/// Runtime code creating an empty contract with `CREATE` and returning its address
+const FACTORY_CODE: Bytes = bytes!("600060006000f060005260206000f3");
+
+/// Runtime code counting down from 30000 in a loop, as a stand-in for compute-heavy calls
+const LOOP_CODE: Bytes = bytes!("6175305b600190038060035700");
+
+fn allowlist(activation_height: u64) -> DeployAllowlist {
+    DeployAllowlist {
+        activation_height,
+        deployers: vec![DEPLOYER],
+        governance_contract: Some(GOVERNANCE),
+    }
+}
+
+fn test_db() -> CacheDB<EmptyDB> {
+    let mut db = CacheDB::new(EmptyDB::default());
+    for (address, code) in [(FACTORY, FACTORY_CODE), (LOOP, LOOP_CODE)] {
+        let code = Bytecode::new_raw(code);
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+    }
+    db
+}
+
+/// Runs a transaction from `caller` at block `number` and returns its result
+fn transact(
+    factory: &RollkitEvmFactory,
+    db: CacheDB<EmptyDB>,
+    number: u64,
+    caller: Address,
+    kind: TxKind,
+    data: Bytes,
+) -> ExecutionResult {
+    let mut env = EvmEnv::default();
+    env.block_env.number = U256::from(number);
+    let mut evm = factory.create_evm(db, env);
+    evm.transact(TxEnv {
+        caller,
+        kind,
+        data,
+        gas_limit: 1_000_000,
+        ..Default::default()
+    })
+    .expect("transaction should execute")
+    .result
+}
+
+/// Returns the address created by the factory contract, zero if the creation failed
+fn created_by_factory(result: &ExecutionResult) -> Address {
+    match result {
+        ExecutionResult::Success { output, .. } => {
+            Address::from_word(B256::from_slice(output.data()))
+        }
+        other => panic!("factory call should succeed, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_allowlist_blocks_creation_transactions() {
+    let factory = RollkitEvmFactory::new(Some(allowlist(0)));
+
+    let result = transact(&factory, test_db(), 1, DEPLOYER, TxKind::Create, INIT_CODE);
+    assert!(result.is_success(), "{result:?}");
+
+    let result = transact(&factory, test_db(), 1, OUTSIDER, TxKind::Create, INIT_CODE);
+    match result {
+        // Error(string) selector, offset and length precede the reason
+        ExecutionResult::Revert { output, .. } => assert_eq!(
+            &output[68..68 + DEPLOYER_NOT_ALLOWED.len()],
+            DEPLOYER_NOT_ALLOWED.as_bytes()
+        ),
+        other => panic!("deployment should revert, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_allowlist_blocks_nested_creations() {
+    let factory = RollkitEvmFactory::new(Some(allowlist(0)));
+    let call = TxKind::Call(FACTORY);
+
+    let result = transact(&factory, test_db(), 1, DEPLOYER, call, Bytes::new());
+    assert_ne!(created_by_factory(&result), Address::ZERO);
+
+    // The call itself succeeds, only the creation inside it fails
+    let result = transact(&factory, test_db(), 1, OUTSIDER, call, Bytes::new());
+    assert_eq!(created_by_factory(&result), Address::ZERO);
+}
+
+#[test]
+fn test_allowlist_activation_and_governance() {
+    let factory = RollkitEvmFactory::new(Some(allowlist(10)));
+
+    // Deployments are unrestricted before the activation height
+    let result = transact(&factory, test_db(), 9, OUTSIDER, TxKind::Create, INIT_CODE);
+    assert!(result.is_success(), "{result:?}");
+
+    // The governance contract allows further origins
+    let mut db = test_db();
+    let slot = DeployAllowlist::governance_slot(OUTSIDER);
+    db.insert_account_storage(GOVERNANCE, U256::from_be_bytes(slot.0), U256::from(1))
+        .unwrap();
+    let result = transact(&factory, db, 10, OUTSIDER, TxKind::Create, INIT_CODE);
+    assert!(result.is_success(), "{result:?}");
+
+    // Factories without an allow-list leave deployments unrestricted
+    let result = transact(
+        &RollkitEvmFactory::default(),
+        test_db(),
+        10,
+        OUTSIDER,
+        TxKind::Create,
+        INIT_CODE,
+    );
+    assert!(result.is_success(), "{result:?}");
+}
+
+/// The guard is turned on and off per transaction, and applies whether or not the caller
+/// inspects
+#[test]
+fn test_allowlist_applies_per_transaction() {
+    let factory = RollkitEvmFactory::new(Some(allowlist(0)));
+    let mut env = EvmEnv::default();
+    env.block_env.number = U256::from(1);
+    let create = |caller| TxEnv {
+        caller,
+        kind: TxKind::Create,
+        data: INIT_CODE,
+        gas_limit: 1_000_000,
+        ..Default::default()
+    };
+
+    let mut evm = factory.create_evm(test_db(), env.clone());
+    for (caller, allowed) in [(DEPLOYER, true), (OUTSIDER, false), (DEPLOYER, true)] {
+        let result = evm.transact(create(caller)).unwrap().result;
+        assert_eq!(result.is_success(), allowed, "{result:?}");
+    }
+
+    let mut evm = factory.create_evm_with_inspector(test_db(), env, NoOpInspector {});
+    let result = evm.transact(create(OUTSIDER)).unwrap().result;
+    assert!(
+        matches!(result, ExecutionResult::Revert { .. }),
+        "{result:?}"
+    );
+    evm.set_inspector_enabled(false);
+    let result = evm.transact(create(OUTSIDER)).unwrap().result;
+    assert!(
+        matches!(result, ExecutionResult::Revert { .. }),
+        "{result:?}"
+    );
+}
+
+/// Measures the cost of running transactions the guard watches in inspector mode, run with
+/// `cargo test -p ev-tests --release -- --ignored --nocapture bench_allowlist_overhead`
+#[test]
+#[ignore = "benchmark"]
+fn bench_allowlist_overhead() {
+    const RUNS: u32 = 500;
+    let average = |factory: &RollkitEvmFactory, caller: Address| -> Duration {
+        let dbs = (0..RUNS).map(|_| test_db()).collect::<Vec<_>>();
+        let start = Instant::now();
+        for db in dbs {
+            let result = transact(factory, db, 1, caller, TxKind::Call(LOOP), Bytes::new());
+            assert!(result.is_success(), "{result:?}");
+        }
+        start.elapsed() / RUNS
+    };
+
+    let guarded = RollkitEvmFactory::new(Some(allowlist(0)));
+    let unrestricted = average(&RollkitEvmFactory::default(), OUTSIDER);
+    let allowed = average(&guarded, DEPLOYER);
+    let before_activation = average(&RollkitEvmFactory::new(Some(allowlist(10))), OUTSIDER);
+    let watched = average(&guarded, OUTSIDER);
+    println!("no allow-list:              {unrestricted:?} per transaction");
+    println!("allow-listed origin:        {allowed:?} per transaction");
+    println!("before activation:          {before_activation:?} per transaction");
+    println!(
+        "origin watched by the guard: {watched:?} per transaction ({:.2}x)",
+        watched.as_secs_f64() / unrestricted.as_secs_f64()
+    );
+}
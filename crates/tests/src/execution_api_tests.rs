@@ -0,0 +1,364 @@
+//! Tests of the Evolve execution API served to ev-node, driving the execution service of an
+//! in-process node and its Connect server.
+
+use alloy_eips::{eip2718::Encodable2718, BlockNumberOrTag};
+use alloy_primitives::{Address, U256};
+use clap::Parser;
+use ev_reth::{
+    builder::RollkitArgs,
+    execution::{
+        proto::{InitChainResponse, EXECUTOR_SERVICE},
+        router, ExecutionApiError,
+    },
+};
+use evolve_ev_reth::EvolveExtraData;
+use eyre::Result;
+use tokio::net::TcpListener;
+
+use crate::{
+    common::{TEST_CHAIN_ID, TEST_TIMESTAMP},
+    node::{encode_transactions, transfer, TestNode},
+};
+
+/// Block time the tests produce blocks at
+const BLOCK_TIME_MILLIS: u64 = 1_000;
+
+/// `InitChain` returns the genesis state root and the transaction byte budget, and only
+/// accepts the first height, the chain's ID and its genesis time
+#[tokio::test]
+async fn test_init_chain() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+    let chain_id = TEST_CHAIN_ID.to_string();
+
+    let (state_root, max_bytes) = service.init_chain(1, &chain_id, Some(TEST_TIMESTAMP))?;
+    assert_eq!(state_root, node.chain_spec().genesis_header().state_root);
+    assert_eq!(max_bytes, service.max_bytes());
+    // The genesis time is optional
+    assert!(service.init_chain(1, &chain_id, None).is_ok());
+
+    assert!(matches!(
+        service.init_chain(0, &chain_id, None),
+        Err(ExecutionApiError::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        service.init_chain(2, &chain_id, None),
+        Err(ExecutionApiError::InvalidArgument(_))
+    ));
+    for chain_id in [
+        String::new(),
+        "evolve".to_string(),
+        (TEST_CHAIN_ID + 1).to_string(),
+    ] {
+        assert!(matches!(
+            service.init_chain(1, &chain_id, None),
+            Err(ExecutionApiError::FailedPrecondition(_))
+        ));
+    }
+    assert!(matches!(
+        service.init_chain(1, &chain_id, Some(TEST_TIMESTAMP + 1)),
+        Err(ExecutionApiError::FailedPrecondition(_))
+    ));
+    Ok(())
+}
+
+/// `GetTxs` returns the pending transactions of the txpool, and none once they are included
+#[tokio::test]
+async fn test_get_txs() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+    assert!(service.get_txs().is_empty());
+
+    let signer = node.signer(0);
+    let txs = [
+        transfer(&signer, 0, Address::with_last_byte(1), U256::from(1))?,
+        transfer(&signer, 1, Address::with_last_byte(1), U256::from(1))?,
+    ];
+    for tx in &txs {
+        node.send_raw_transaction(tx.encoded_2718().into()).await?;
+    }
+    assert_eq!(service.get_txs(), encode_transactions(&txs));
+
+    let (state_root, _) = service.init_chain(1, &TEST_CHAIN_ID.to_string(), None)?;
+    let timestamp_millis = genesis_timestamp_millis(&node) + BLOCK_TIME_MILLIS;
+    service
+        .execute_txs(service.get_txs(), 1, timestamp_millis, state_root)
+        .await?;
+    assert!(service.get_txs().is_empty());
+    Ok(())
+}
+
+/// `ExecuteTxs` makes the block canonical and answers a retry at the same height with the
+/// block it executed, rejecting a retry with other transactions or another timestamp
+#[tokio::test]
+async fn test_execute_txs() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+    let signer = node.signer(0);
+    let to = Address::with_last_byte(1);
+    let first = transfer(&signer, 0, to, U256::from(1))?;
+    let other = transfer(&signer, 0, to, U256::from(2))?;
+
+    let (state_root, _) = service.init_chain(1, &TEST_CHAIN_ID.to_string(), None)?;
+    let timestamp_millis = genesis_timestamp_millis(&node) + BLOCK_TIME_MILLIS;
+    let txs = encode_transactions([&first]);
+    let executed = service
+        .execute_txs(txs.clone(), 1, timestamp_millis, state_root)
+        .await?;
+    assert_eq!(node.block_number().await?, 1);
+    assert_eq!(node.balance(to).await?, U256::from(1));
+    let latest = node
+        .block_by_number(BlockNumberOrTag::Latest, false)
+        .await?
+        .expect("latest block should exist");
+    assert_eq!(latest.header.hash, executed.hash);
+    assert_eq!(latest.header.state_root, executed.state_root);
+
+    // The same request returns the block already executed
+    assert_eq!(
+        service
+            .execute_txs(txs, 1, timestamp_millis, state_root)
+            .await?,
+        executed
+    );
+
+    // Other transactions at the same height are rejected and leave the chain unchanged
+    let err = service
+        .execute_txs(
+            encode_transactions([&other]),
+            1,
+            timestamp_millis,
+            state_root,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExecutionApiError::FailedPrecondition(_)),
+        "{err}"
+    );
+    let err = service
+        .execute_txs(Vec::new(), 1, timestamp_millis, state_root)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExecutionApiError::FailedPrecondition(_)),
+        "{err}"
+    );
+
+    // So is another timestamp
+    let err = service
+        .execute_txs(
+            encode_transactions([&first]),
+            1,
+            timestamp_millis + 1,
+            state_root,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExecutionApiError::FailedPrecondition(_)),
+        "{err}"
+    );
+
+    assert_eq!(node.block_number().await?, 1);
+    assert_eq!(node.balance(to).await?, U256::from(1));
+
+    // The next block must build on the state root of the head
+    let err = service
+        .execute_txs(
+            Vec::new(),
+            2,
+            timestamp_millis + BLOCK_TIME_MILLIS,
+            state_root,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExecutionApiError::FailedPrecondition(_)),
+        "{err}"
+    );
+    service
+        .execute_txs(
+            Vec::new(),
+            2,
+            timestamp_millis + BLOCK_TIME_MILLIS,
+            executed.state_root,
+        )
+        .await?;
+    assert_eq!(node.block_number().await?, 2);
+    Ok(())
+}
+
+/// A retry may repeat the transactions the builder skipped, but not drop the ones it
+/// included
+#[tokio::test]
+async fn test_execute_txs_retry_with_skipped_transactions() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+    let signer = node.signer(0);
+    let to = Address::with_last_byte(1);
+    let included = transfer(&signer, 0, to, U256::from(1))?;
+    // Reuses the included transaction's nonce, so it fails once the first one executed
+    let skipped = transfer(&signer, 0, to, U256::from(2))?;
+
+    let (state_root, _) = service.init_chain(1, &TEST_CHAIN_ID.to_string(), None)?;
+    let timestamp_millis = genesis_timestamp_millis(&node) + BLOCK_TIME_MILLIS;
+    let txs = encode_transactions([&included, &skipped]);
+    let executed = service
+        .execute_txs(txs.clone(), 1, timestamp_millis, state_root)
+        .await?;
+    let block = node
+        .block_by_number(BlockNumberOrTag::Number(1), false)
+        .await?
+        .expect("block should exist");
+    assert_eq!(block.transactions.len(), 1);
+
+    assert_eq!(
+        service
+            .execute_txs(txs, 1, timestamp_millis, state_root)
+            .await?,
+        executed
+    );
+    let err = service
+        .execute_txs(
+            encode_transactions([&skipped]),
+            1,
+            timestamp_millis,
+            state_root,
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ExecutionApiError::FailedPrecondition(_)),
+        "{err}"
+    );
+    Ok(())
+}
+
+/// `SetFinal` moves the safe and finalized heads forward and ignores heights at or below the
+/// finalized head
+#[tokio::test]
+async fn test_set_final() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+
+    let (mut state_root, _) = service.init_chain(1, &TEST_CHAIN_ID.to_string(), None)?;
+    let mut timestamp_millis = genesis_timestamp_millis(&node);
+    for height in 1..=3 {
+        timestamp_millis += BLOCK_TIME_MILLIS;
+        state_root = service
+            .execute_txs(Vec::new(), height, timestamp_millis, state_root)
+            .await?
+            .state_root;
+    }
+
+    service.set_final(2).await?;
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, Some(2));
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Finalized).await?,
+        Some(2)
+    );
+
+    // Replayed heights are no-ops
+    service.set_final(1).await?;
+    service.set_final(2).await?;
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Finalized).await?,
+        Some(2)
+    );
+
+    assert!(matches!(
+        service.set_final(4).await,
+        Err(ExecutionApiError::FailedPrecondition(_) | ExecutionApiError::BlockNotFound(4))
+    ));
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Finalized).await?,
+        Some(2)
+    );
+
+    service.set_final(3).await?;
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, Some(3));
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Finalized).await?,
+        Some(3)
+    );
+    Ok(())
+}
+
+/// The Connect server answers over cleartext HTTP/2 with prior knowledge, as ev-node's
+/// client connects, and reports the byte budget as `max_bytes`
+#[tokio::test]
+async fn test_server_speaks_h2c() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let service = node.execution_service(Address::ZERO);
+    let max_bytes = service.max_bytes();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router(service)).await });
+
+    let url = format!("http://{addr}/{EXECUTOR_SERVICE}/InitChain");
+    let body = serde_json::json!({ "initialHeight": "1", "chainId": TEST_CHAIN_ID.to_string() });
+    for client in [
+        reqwest::Client::builder().http2_prior_knowledge().build()?,
+        reqwest::Client::new(),
+    ] {
+        let response = client.post(&url).json(&body).send().await?;
+        assert!(response.status().is_success(), "{}", response.status());
+        let response: InitChainResponse = response.json().await?;
+        assert_eq!(
+            response.state_root,
+            node.chain_spec().genesis_header().state_root.to_vec()
+        );
+        assert_eq!(response.max_bytes, max_bytes);
+    }
+
+    let response = reqwest::Client::builder()
+        .http2_prior_knowledge()
+        .build()?
+        .post(&url)
+        .json(&body)
+        .send()
+        .await?;
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    Ok(())
+}
+
+/// The unauthenticated execution API is only served on loopback addresses unless remote
+/// access is allowed
+#[test]
+fn test_execution_api_listen_addr() -> Result<()> {
+    let args = |extra: &[&str]| {
+        RollkitArgs::try_parse_from(["ev-reth"].iter().chain(extra))
+            .expect("arguments should parse")
+    };
+
+    assert_eq!(args(&[]).execution_api_listen_addr()?, None);
+    for addr in ["127.0.0.1:7980", "[::1]:7980"] {
+        assert_eq!(
+            args(&["--ev-reth.execution-api.addr", addr]).execution_api_listen_addr()?,
+            Some(addr.parse()?)
+        );
+    }
+    let remote = ["--ev-reth.execution-api.addr", "0.0.0.0:7980"];
+    assert!(args(&remote).execution_api_listen_addr().is_err());
+    let allowed = args(&[&remote[..], &["--ev-reth.execution-api.allow-remote"]].concat());
+    assert_eq!(
+        allowed.execution_api_listen_addr()?,
+        Some("0.0.0.0:7980".parse()?)
+    );
+    Ok(())
+}
+
+/// Returns the millisecond timestamp of the genesis block
+fn genesis_timestamp_millis(node: &TestNode) -> u64 {
+    EvolveExtraData::timestamp_millis_of(node.chain_spec().genesis_header())
+}
+
+/// Returns the number of the block with the given tag, if any
+async fn head_number(node: &TestNode, tag: BlockNumberOrTag) -> Result<Option<u64>> {
+    Ok(node
+        .block_by_number(tag, false)
+        .await?
+        .map(|block| block.header.number))
+}
@@ -0,0 +1,273 @@
+//! Tests of the safe and finalized heads moved by `EvolveFinalityApiImpl::update` and served
+//! by `evolve_setFinal` and `evolve_getFinal`.
+
+use alloy_eips::BlockNumberOrTag;
+use evolve_ev_reth::rpc::{FinalityError, FinalityStatus};
+use eyre::Result;
+use jsonrpsee::rpc_params;
+
+use crate::node::{TestFinalityApi, TestNode};
+
+/// Launches a node and builds `count` empty blocks on top of genesis
+async fn node_with_blocks(count: u64) -> Result<TestNode> {
+    let node = TestNode::launch().await?;
+    for _ in 0..count {
+        node.build_block(Vec::new()).await?;
+    }
+    assert_eq!(node.block_number().await?, count);
+    Ok(node)
+}
+
+/// Returns the number of the block with the given tag, if any
+async fn head_number(node: &TestNode, tag: BlockNumberOrTag) -> Result<Option<u64>> {
+    Ok(node
+        .block_by_number(tag, false)
+        .await?
+        .map(|block| block.header.number))
+}
+
+/// Returns the heights of the safe and finalized heads
+fn heads(status: &FinalityStatus) -> (Option<u64>, Option<u64>) {
+    (
+        status.safe.map(|block| block.number),
+        status.finalized.map(|block| block.number),
+    )
+}
+
+/// Heads move forward to the requested canonical blocks, and an omitted height leaves its
+/// head unchanged
+#[tokio::test]
+async fn test_finality_update_moves_heads_forward() -> Result<()> {
+    let node = node_with_blocks(3).await?;
+    let finality = node.finality_api();
+
+    let status = finality.update(Some(2), Some(1)).await?;
+    assert_eq!(status.head.number, 3);
+    assert_eq!(heads(&status), (Some(2), Some(1)));
+    let block = node
+        .block_by_number(BlockNumberOrTag::Number(2), false)
+        .await?
+        .expect("block should exist");
+    assert_eq!(status.safe.map(|block| block.hash), Some(block.header.hash));
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, Some(2));
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Finalized).await?,
+        Some(1)
+    );
+
+    let status = finality.update(None, Some(2)).await?;
+    assert_eq!(heads(&status), (Some(2), Some(2)));
+    let status = finality.update(Some(3), None).await?;
+    assert_eq!(heads(&status), (Some(3), Some(2)));
+    assert_eq!(heads(&finality.status()?), (Some(3), Some(2)));
+
+    // Requesting the current heights again is accepted
+    assert_eq!(
+        heads(&finality.update(Some(3), Some(2)).await?),
+        (Some(3), Some(2))
+    );
+    Ok(())
+}
+
+/// Neither head moves backwards, and a rejected update leaves both heads unchanged
+#[tokio::test]
+async fn test_finality_update_rejects_moving_backwards() -> Result<()> {
+    let node = node_with_blocks(3).await?;
+    let finality = node.finality_api();
+    finality.update(Some(2), Some(2)).await?;
+
+    let err = finality.update(Some(1), None).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::Backwards {
+                kind: "safe",
+                number: 1,
+                current: 2
+            }
+        ),
+        "{err}"
+    );
+
+    let err = finality.update(Some(3), Some(1)).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::Backwards {
+                kind: "finalized",
+                number: 1,
+                current: 2
+            }
+        ),
+        "{err}"
+    );
+
+    assert_eq!(heads(&finality.status()?), (Some(2), Some(2)));
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, Some(2));
+    Ok(())
+}
+
+/// Heads never pass the canonical head
+#[tokio::test]
+async fn test_finality_update_rejects_target_ahead_of_head() -> Result<()> {
+    let node = node_with_blocks(2).await?;
+    let finality = node.finality_api();
+
+    let err = finality.update(Some(3), None).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::AheadOfHead {
+                kind: "safe",
+                number: 3,
+                head: 2
+            }
+        ),
+        "{err}"
+    );
+
+    let err = finality.update(Some(2), Some(3)).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::AheadOfHead {
+                kind: "finalized",
+                number: 3,
+                head: 2
+            }
+        ),
+        "{err}"
+    );
+
+    assert_eq!(heads(&finality.status()?), (None, None));
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, None);
+    Ok(())
+}
+
+/// The finalized head never passes the safe head, whether both are requested together or
+/// the safe head is the current one
+#[tokio::test]
+async fn test_finality_update_rejects_finalized_above_safe() -> Result<()> {
+    let node = node_with_blocks(3).await?;
+    let finality = node.finality_api();
+
+    let err = finality.update(Some(1), Some(2)).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::FinalizedAfterSafe {
+                finalized: 2,
+                safe: 1
+            }
+        ),
+        "{err}"
+    );
+    assert_eq!(heads(&finality.status()?), (None, None));
+
+    finality.update(Some(1), None).await?;
+    let err = finality.update(None, Some(2)).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            FinalityError::FinalizedAfterSafe {
+                finalized: 2,
+                safe: 1
+            }
+        ),
+        "{err}"
+    );
+    assert_eq!(heads(&finality.status()?), (Some(1), None));
+    Ok(())
+}
+
+/// `evolve_setFinal` is only served on the authenticated Engine API endpoint, while
+/// `evolve_getFinal` is served on both
+#[tokio::test]
+async fn test_set_final_requires_authentication() -> Result<()> {
+    let node = node_with_blocks(2).await?;
+
+    assert!(node
+        .request::<FinalityStatus>("evolve_setFinal", rpc_params![1u64, 1u64])
+        .await
+        .is_err());
+    assert_eq!(head_number(&node, BlockNumberOrTag::Safe).await?, None);
+
+    let status: FinalityStatus = node
+        .engine_request("evolve_setFinal", rpc_params![2u64, 1u64])
+        .await?;
+    assert_eq!(heads(&status), (Some(2), Some(1)));
+
+    let public: FinalityStatus = node.request("evolve_getFinal", rpc_params![]).await?;
+    let authenticated: FinalityStatus = node
+        .engine_request("evolve_getFinal", rpc_params![])
+        .await?;
+    assert_eq!(public, status);
+    assert_eq!(authenticated, status);
+    Ok(())
+}
+
+/// Moves both heads to `head`, `None` if another update already moved them past it
+async fn update_to_head(
+    finality: &TestFinalityApi,
+    head: u64,
+) -> Result<Option<FinalityStatus>, FinalityError> {
+    match finality.update(Some(head), Some(head)).await {
+        Ok(status) => Ok(Some(status)),
+        Err(FinalityError::Backwards { .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Moves the heads to the current head `count` times while blocks are being built
+async fn race_updates(node: &TestNode, finality: &TestFinalityApi, count: u64) -> Result<()> {
+    for _ in 0..count {
+        let head = node.block_number().await?;
+        update_to_head(finality, head).await?;
+        // The update leaves the canonical head to block production
+        let after = node.block_number().await?;
+        eyre::ensure!(after >= head, "head moved back from {head} to {after}");
+        tokio::task::yield_now().await;
+    }
+    Ok(())
+}
+
+/// Finality updates racing block production, through several instances as the RPC and the
+/// execution service run them, never move the canonical head
+#[tokio::test]
+async fn test_finality_update_races_block_production() -> Result<()> {
+    const BLOCKS: u64 = 20;
+    let node = node_with_blocks(1).await?;
+    let rpc = node.finality_api();
+    let service = node.finality_api();
+
+    let blocks = async {
+        for _ in 0..BLOCKS {
+            node.build_block(Vec::new()).await?;
+        }
+        Ok::<_, eyre::Report>(())
+    };
+    tokio::try_join!(
+        race_updates(&node, &rpc, BLOCKS),
+        race_updates(&node, &service, BLOCKS),
+        blocks
+    )?;
+
+    // Every block built during the race is still canonical, and the heads are at or below it
+    assert_eq!(node.block_number().await?, BLOCKS + 1);
+    let status = rpc.status()?;
+    assert_eq!(status.head.number, BLOCKS + 1);
+    let (safe, finalized) = heads(&status);
+    assert!(safe.is_some_and(|safe| safe <= BLOCKS + 1));
+    assert_eq!(safe, finalized);
+
+    // A final update lands on the head and survives a restart
+    let status = rpc.update(Some(BLOCKS + 1), Some(BLOCKS + 1)).await?;
+    drop((rpc, service));
+    let node = node.restart().await?;
+    assert_eq!(node.finality_api().status()?, status);
+    assert_eq!(
+        head_number(&node, BlockNumberOrTag::Safe).await?,
+        Some(BLOCKS + 1)
+    );
+    Ok(())
+}
@@ -0,0 +1,471 @@
+//! Golden vectors pinning the blocks ev-reth builds from payload attributes.
+//!
+//! A [`GoldenVector`] holds a genesis, the [`RollkitEnginePayloadAttributes`] of a chain of
+//! blocks and the block hash, state root and receipts root each of them must produce.
+//! Anything changing what the payload builder includes, how it executes it or what the
+//! header commits to changes one of them, so a vector failing [`GoldenVector::check`] is a
+//! consensus change. Vectors are generated from a [`VectorSpec`], which describes the
+//! blocks in terms of test accounts and transfers rather than raw attributes.
+
+use alloy_consensus::TxEip1559;
+use alloy_genesis::Genesis;
+use alloy_primitives::{Address, Bytes, TxKind, B256, U256};
+use alloy_rpc_types::engine::{ExecutionPayloadV3, PayloadAttributes};
+use ev_reth::attributes::RollkitEnginePayloadAttributes;
+use evolve_ev_reth::DaBlockInfo;
+use eyre::{Result, WrapErr};
+use reth_ethereum_primitives::TransactionSigned;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    node::{
+        encode_transactions, sign_transaction, test_signer, TestNode, TEST_MAX_FEE_PER_GAS,
+        TEST_MAX_PRIORITY_FEE_PER_GAS,
+    },
+    scenario::DEFAULT_BLOCK_TIME_MILLIS,
+};
+
+/// How to update vectors after an intended consensus change
+pub const REGENERATE_HINT: &str = "if the change is intended, regenerate the vectors with \
+    `cargo run -p ev-tests --bin ev-vectors -- bless` and commit the diff";
+
+/// Returns the directory holding the golden vectors of this crate
+pub fn vectors_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("vectors")
+}
+
+/// Returns the directory holding the specs the golden vectors are generated from
+pub fn specs_dir() -> PathBuf {
+    vectors_dir().join("specs")
+}
+
+/// Description of the chain a golden vector is generated from, as written in YAML or JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct VectorSpec {
+    /// Name of the vector, also its file name
+    pub name: String,
+    /// What the vector pins
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Evolve parameters of the genesis, stored under `config.evolve`
+    #[serde(default)]
+    pub evolve: Option<serde_json::Value>,
+    /// Blocks built in order, each on top of the previous one
+    pub blocks: Vec<BlockSpec>,
+}
+
+/// Block of a [`VectorSpec`], turned into payload attributes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BlockSpec {
+    /// Timestamp in seconds, sent without a millisecond timestamp
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    /// Timestamp in milliseconds, one block time after the parent's when no timestamp is
+    /// given
+    #[serde(default)]
+    pub timestamp_millis: Option<u64>,
+    /// Beneficiary of the block
+    #[serde(default)]
+    pub fee_recipient: Address,
+    /// `prevRandao` of the attributes
+    #[serde(default)]
+    pub prev_randao: B256,
+    /// Gas limit of the block, the parent's when unset as ev-node always sends one
+    #[serde(default)]
+    pub gas_limit: Option<u64>,
+    /// DA layer block written into the DA info predeploy
+    #[serde(default)]
+    pub da_info: Option<DaBlockInfo>,
+    /// Transactions sent in the attributes, in order
+    #[serde(default)]
+    pub transactions: Vec<TxSpec>,
+}
+
+/// EIP-1559 transaction of a [`BlockSpec`], signed by a test account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TxSpec {
+    /// Index of the signing account, derived from the test mnemonic
+    pub from: u32,
+    /// Recipient, the transaction creates a contract when unset
+    #[serde(default)]
+    pub to: Option<Address>,
+    /// Value in wei
+    #[serde(default)]
+    pub value: u128,
+    /// Calldata, or init code of a contract creation
+    #[serde(default)]
+    pub input: Bytes,
+    /// Gas limit
+    #[serde(default = "default_tx_gas_limit")]
+    pub gas_limit: u64,
+    /// Nonce, the sender's next one when unset
+    #[serde(default)]
+    pub nonce: Option<u64>,
+    /// Maximum fee per gas
+    #[serde(default = "default_max_fee_per_gas")]
+    pub max_fee_per_gas: u128,
+    /// Maximum priority fee per gas
+    #[serde(default = "default_max_priority_fee_per_gas")]
+    pub max_priority_fee_per_gas: u128,
+}
+
+const fn default_tx_gas_limit() -> u64 {
+    21_000
+}
+
+const fn default_max_fee_per_gas() -> u128 {
+    TEST_MAX_FEE_PER_GAS
+}
+
+const fn default_max_priority_fee_per_gas() -> u128 {
+    TEST_MAX_PRIORITY_FEE_PER_GAS
+}
+
+/// Golden vector: payload attributes and the blocks they must produce
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GoldenVector {
+    /// Name of the vector
+    pub name: String,
+    /// What the vector pins
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Genesis of the chain
+    pub genesis: Genesis,
+    /// Blocks in order, starting at block 1
+    pub blocks: Vec<GoldenBlock>,
+}
+
+/// Block of a [`GoldenVector`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GoldenBlock {
+    /// Block the attributes are sent on
+    pub parent_hash: B256,
+    /// Attributes of the `engine_forkchoiceUpdatedV3` call building the block
+    pub attributes: RollkitEnginePayloadAttributes,
+    /// Block the attributes must produce
+    pub expected: ExpectedBlock,
+}
+
+/// Commitments of a built block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExpectedBlock {
+    /// Block number
+    pub number: u64,
+    /// Block hash
+    pub block_hash: B256,
+    /// State root
+    pub state_root: B256,
+    /// Receipts root
+    pub receipts_root: B256,
+    /// Gas used by the block
+    pub gas_used: u64,
+    /// Number of transactions included
+    pub transaction_count: usize,
+}
+
+impl VectorSpec {
+    /// Reads a spec from a YAML or JSON file, chosen by its extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let spec = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&contents)?
+        } else {
+            serde_yaml::from_str(&contents)?
+        };
+        Ok(spec)
+    }
+
+    /// Reads the specs of a directory, sorted by file name
+    pub fn from_dir(dir: &Path) -> Result<Vec<(PathBuf, Self)>> {
+        read_dir(dir, &["yaml", "yml", "json"], Self::from_file)
+    }
+
+    /// Builds the blocks of the spec on a fresh node and records their attributes and
+    /// commitments
+    pub async fn generate(&self) -> Result<GoldenVector> {
+        let builder = match &self.evolve {
+            Some(evolve) => TestNode::builder().with_evolve_config(evolve.clone()),
+            None => TestNode::builder(),
+        };
+        let genesis = builder.build_genesis()?;
+        let node = builder.launch_with_genesis(genesis.clone()).await?;
+        let chain_id = genesis.config.chain_id;
+
+        let mut nonces = HashMap::new();
+        let mut parent_hash = node.genesis_hash();
+        let mut parent_millis = genesis.timestamp * 1_000;
+        let mut parent_gas_limit = genesis.gas_limit;
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for (index, block) in self.blocks.iter().enumerate() {
+            let number = index as u64 + 1;
+            let attributes = block
+                .attributes(chain_id, parent_millis, parent_gas_limit, &mut nonces)
+                .wrap_err_with(|| format!("block {number}"))?;
+            let payload = node
+                .build_block_with(parent_hash, attributes.clone())
+                .await
+                .wrap_err_with(|| format!("block {number}"))?;
+            let expected = ExpectedBlock::from_payload(&payload);
+
+            parent_gas_limit = payload.payload_inner.payload_inner.gas_limit;
+            parent_millis = attributes
+                .timestamp_millis
+                .unwrap_or(attributes.inner.timestamp * 1_000);
+            blocks.push(GoldenBlock {
+                parent_hash,
+                attributes,
+                expected: expected.clone(),
+            });
+            parent_hash = expected.block_hash;
+        }
+
+        Ok(GoldenVector {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            genesis,
+            blocks,
+        })
+    }
+}
+
+impl BlockSpec {
+    /// Returns the payload attributes of the block, signing its transactions with the next
+    /// nonces of their senders
+    fn attributes(
+        &self,
+        chain_id: u64,
+        parent_millis: u64,
+        parent_gas_limit: u64,
+        nonces: &mut HashMap<u32, u64>,
+    ) -> Result<RollkitEnginePayloadAttributes> {
+        let (timestamp, timestamp_millis) = match (self.timestamp, self.timestamp_millis) {
+            (Some(_), Some(_)) => {
+                eyre::bail!("a block takes a timestamp or a millisecond timestamp, not both")
+            }
+            (Some(timestamp), None) => (timestamp, None),
+            (None, Some(millis)) => (millis / 1_000, Some(millis)),
+            (None, None) => {
+                let millis = parent_millis + DEFAULT_BLOCK_TIME_MILLIS;
+                (millis / 1_000, Some(millis))
+            }
+        };
+
+        let transactions = self
+            .transactions
+            .iter()
+            .map(|tx| {
+                let next = nonces.entry(tx.from).or_default();
+                let nonce = tx.nonce.unwrap_or(*next);
+                *next = nonce + 1;
+                tx.sign(chain_id, nonce)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RollkitEnginePayloadAttributes {
+            inner: PayloadAttributes {
+                timestamp,
+                prev_randao: self.prev_randao,
+                suggested_fee_recipient: self.fee_recipient,
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: Some(B256::ZERO),
+            },
+            transactions: Some(encode_transactions(&transactions)),
+            gas_limit: Some(self.gas_limit.unwrap_or(parent_gas_limit)),
+            da_info: self.da_info,
+            timestamp_millis,
+        })
+    }
+}
+
+impl TxSpec {
+    /// Signs the transaction with the given nonce
+    fn sign(&self, chain_id: u64, nonce: u64) -> Result<TransactionSigned> {
+        sign_transaction(
+            &test_signer(self.from),
+            TxEip1559 {
+                chain_id,
+                nonce,
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self.max_fee_per_gas,
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+                to: self.to.map_or(TxKind::Create, TxKind::Call),
+                value: U256::from(self.value),
+                input: self.input.clone(),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl GoldenVector {
+    /// Reads a vector from a JSON file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Reads the vectors of a directory, sorted by file name
+    pub fn from_dir(dir: &Path) -> Result<Vec<(PathBuf, Self)>> {
+        read_dir(dir, &["json"], Self::from_file)
+    }
+
+    /// Writes the vector as pretty-printed JSON
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        Ok(fs::write(path, contents)?)
+    }
+
+    /// Checks that `generated`, produced from this vector's spec, has the same genesis and
+    /// attributes, so that the vector is not stale against an edited spec
+    pub fn ensure_same_inputs(&self, generated: &Self) -> Result<()> {
+        if self.genesis != generated.genesis {
+            eyre::bail!(
+                "vector {}: the genesis generated from its spec differs; {REGENERATE_HINT}",
+                self.name
+            );
+        }
+        if self.blocks.len() != generated.blocks.len() {
+            eyre::bail!(
+                "vector {} has {} blocks, its spec {}; {REGENERATE_HINT}",
+                self.name,
+                self.blocks.len(),
+                generated.blocks.len()
+            );
+        }
+        for (block, generated) in self.blocks.iter().zip(&generated.blocks) {
+            if block.attributes != generated.attributes {
+                eyre::bail!(
+                    "vector {}: the attributes of block {} differ from the ones generated from \
+                     its spec; {REGENERATE_HINT}",
+                    self.name,
+                    block.expected.number
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the blocks of the vector on a fresh node from its genesis, failing at the first
+    /// block that differs from the expected one
+    pub async fn check(&self) -> Result<()> {
+        let node = TestNode::builder()
+            .launch_with_genesis(self.genesis.clone())
+            .await?;
+
+        let mut parent_hash = node.genesis_hash();
+        for block in &self.blocks {
+            let number = block.expected.number;
+            if block.parent_hash != parent_hash {
+                eyre::bail!(
+                    "vector {}: block {number} is built on {}, but the chain is at {parent_hash}; \
+                     {REGENERATE_HINT}",
+                    self.name,
+                    block.parent_hash
+                );
+            }
+
+            let payload = node
+                .build_block_with(parent_hash, block.attributes.clone())
+                .await
+                .wrap_err_with(|| format!("vector {}: block {number}", self.name))?;
+            let actual = ExpectedBlock::from_payload(&payload);
+            let diff = block.expected.diff(&actual);
+            if !diff.is_empty() {
+                eyre::bail!(
+                    "vector {}: block {number} differs from the golden vector:\n  {}\n{REGENERATE_HINT}",
+                    self.name,
+                    diff.join("\n  ")
+                );
+            }
+            parent_hash = actual.block_hash;
+        }
+        Ok(())
+    }
+}
+
+impl ExpectedBlock {
+    /// Returns the commitments of a built payload
+    fn from_payload(payload: &ExecutionPayloadV3) -> Self {
+        let block = &payload.payload_inner.payload_inner;
+        Self {
+            number: block.block_number,
+            block_hash: block.block_hash,
+            state_root: block.state_root,
+            receipts_root: block.receipts_root,
+            gas_used: block.gas_used,
+            transaction_count: block.transactions.len(),
+        }
+    }
+
+    /// Describes the fields of `actual` differing from `self`
+    fn diff(&self, actual: &Self) -> Vec<String> {
+        let mut diff = Vec::new();
+        diff_field(&mut diff, "number", self.number, actual.number);
+        diff_field(&mut diff, "block hash", self.block_hash, actual.block_hash);
+        diff_field(&mut diff, "state root", self.state_root, actual.state_root);
+        diff_field(
+            &mut diff,
+            "receipts root",
+            self.receipts_root,
+            actual.receipts_root,
+        );
+        diff_field(&mut diff, "gas used", self.gas_used, actual.gas_used);
+        diff_field(
+            &mut diff,
+            "transaction count",
+            self.transaction_count,
+            actual.transaction_count,
+        );
+        diff
+    }
+}
+
+/// Records a field differing from its expected value
+fn diff_field<T: PartialEq + Display>(diff: &mut Vec<String>, name: &str, expected: T, actual: T) {
+    if expected != actual {
+        diff.push(format!("{name}: expected {expected}, got {actual}"));
+    }
+}
+
+/// Reads the files of a directory with one of the given extensions, sorted by file name
+fn read_dir<T>(
+    dir: &Path,
+    extensions: &[&str],
+    read: impl Fn(&Path) -> Result<T>,
+) -> Result<Vec<(PathBuf, T)>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| {
+        path.is_file()
+            && path.extension().is_some_and(|extension| {
+                extensions
+                    .iter()
+                    .any(|expected| extension == OsStr::new(expected))
+            })
+    });
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let value = read(&path).wrap_err_with(|| format!("{}", path.display()))?;
+            Ok((path, value))
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Checks the golden vectors of `crates/tests/vectors`.
+//!
+//! Every spec of `vectors/specs` has its vector, named after it, committed in `vectors`. The
+//! test only reads them: vectors are written by `ev-vectors generate` or, after an intended
+//! consensus change, regenerated all at once by `ev-vectors bless`.
+
+use alloy_primitives::Address;
+use eyre::{Result, WrapErr};
+
+use crate::{
+    golden::{
+        specs_dir, vectors_dir, BlockSpec, GoldenVector, TxSpec, VectorSpec, REGENERATE_HINT,
+    },
+    node::{TEST_MAX_FEE_PER_GAS, TEST_MAX_PRIORITY_FEE_PER_GAS},
+};
+
+#[tokio::test]
+async fn test_golden_vectors() -> Result<()> {
+    let specs = VectorSpec::from_dir(&specs_dir())?;
+    assert!(!specs.is_empty(), "no vector specs found");
+    for (path, spec) in &specs {
+        let vector_path = vectors_dir().join(format!("{}.json", spec.name));
+        let vector = GoldenVector::from_file(&vector_path).wrap_err_with(|| {
+            format!(
+                "{} has no readable vector at {}, generate it with `ev-vectors generate`",
+                path.display(),
+                vector_path.display()
+            )
+        })?;
+
+        // The committed vector must be the one of the spec as it stands
+        let generated = spec
+            .generate()
+            .await
+            .wrap_err_with(|| format!("{}", path.display()))?;
+        vector
+            .ensure_same_inputs(&generated)
+            .wrap_err_with(|| format!("{}", vector_path.display()))?;
+    }
+
+    for (path, vector) in GoldenVector::from_dir(&vectors_dir())? {
+        vector
+            .check()
+            .await
+            .wrap_err_with(|| format!("{}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// The same spec generates the same vector, as the signatures of the transactions it
+/// describes are deterministic
+#[tokio::test]
+async fn test_golden_vector_generation_is_deterministic() -> Result<()> {
+    let spec = VectorSpec {
+        name: "determinism".to_string(),
+        description: None,
+        evolve: None,
+        blocks: vec![
+            BlockSpec {
+                transactions: vec![TxSpec {
+                    from: 0,
+                    to: Some(Address::with_last_byte(1)),
+                    value: 1,
+                    input: Default::default(),
+                    gas_limit: 21_000,
+                    nonce: None,
+                    max_fee_per_gas: TEST_MAX_FEE_PER_GAS,
+                    max_priority_fee_per_gas: TEST_MAX_PRIORITY_FEE_PER_GAS,
+                }],
+                ..Default::default()
+            },
+            BlockSpec::default(),
+        ],
+    };
+
+    let vector = spec.generate().await?;
+    assert_eq!(vector.blocks.len(), 2);
+    assert_eq!(vector.blocks[0].expected.transaction_count, 1);
+    assert_eq!(
+        vector.blocks[1].parent_hash,
+        vector.blocks[0].expected.block_hash
+    );
+    assert_eq!(spec.generate().await?, vector);
+    vector.check().await?;
+
+    // A vector whose expected block no longer matches fails the check
+    let mut tampered = vector;
+    tampered.blocks[1].expected.gas_used += 1;
+    let err = tampered.check().await.unwrap_err();
+    assert!(err.to_string().contains("gas used"), "{err}");
+    Ok(())
+}
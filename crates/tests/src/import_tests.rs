@@ -0,0 +1,134 @@
+//! Tests for importing chains with the reth commands wired to `RollkitConsensus`.
+
+use alloy_consensus::EMPTY_OMMER_ROOT_HASH;
+use alloy_eips::{eip1559::BaseFeeParams, eip4895::Withdrawals};
+use alloy_genesis::Genesis;
+use alloy_primitives::EMPTY_ROOT_HASH;
+use alloy_rlp::Encodable;
+use clap::Parser;
+use ev_node::rollkit_cli_components;
+use eyre::Result;
+use reth_chainspec::ChainSpec;
+use reth_cli_commands::import::ImportCommand;
+use reth_consensus::HeaderValidator;
+use reth_db::{mdbx::DatabaseArguments, tables, transaction::DbTx, Database};
+use reth_ethereum::node::EthereumNode;
+use reth_ethereum_cli::chainspec::EthereumChainSpecParser;
+use reth_ethereum_consensus::EthBeaconConsensus;
+use reth_ethereum_primitives::{Block, BlockBody};
+use reth_primitives::{Header, SealedBlock};
+use std::{fs, sync::Arc};
+
+use crate::common::{TEST_CHAIN_ID, TEST_GAS_LIMIT, TEST_TIMESTAMP};
+
+/// Returns a Shanghai genesis without accounts
+fn test_genesis() -> Result<Genesis> {
+    Ok(serde_json::from_value(serde_json::json!({
+        "config": {
+            "chainId": TEST_CHAIN_ID,
+            "homesteadBlock": 0,
+            "eip150Block": 0,
+            "eip155Block": 0,
+            "eip158Block": 0,
+            "byzantiumBlock": 0,
+            "constantinopleBlock": 0,
+            "petersburgBlock": 0,
+            "istanbulBlock": 0,
+            "berlinBlock": 0,
+            "londonBlock": 0,
+            "terminalTotalDifficulty": 0,
+            "terminalTotalDifficultyPassed": true,
+            "shanghaiTime": 0
+        },
+        "timestamp": format!("{TEST_TIMESTAMP:#x}"),
+        "gasLimit": format!("{TEST_GAS_LIMIT:#x}"),
+        "baseFeePerGas": "0x3b9aca00",
+        "difficulty": "0x0",
+        "alloc": {}
+    }))?)
+}
+
+/// Returns an empty block on top of `parent`
+fn empty_block(parent: &SealedBlock, timestamp: u64) -> SealedBlock {
+    let header = Header {
+        parent_hash: parent.hash(),
+        ommers_hash: EMPTY_OMMER_ROOT_HASH,
+        state_root: parent.state_root,
+        transactions_root: EMPTY_ROOT_HASH,
+        receipts_root: EMPTY_ROOT_HASH,
+        withdrawals_root: Some(EMPTY_ROOT_HASH),
+        number: parent.number + 1,
+        gas_limit: parent.gas_limit,
+        timestamp,
+        base_fee_per_gas: parent.next_block_base_fee(BaseFeeParams::ethereum()),
+        ..Default::default()
+    };
+    let body = BlockBody {
+        transactions: Vec::new(),
+        ommers: Vec::new(),
+        withdrawals: Some(Withdrawals::default()),
+    };
+    SealedBlock::seal_slow(Block { header, body })
+}
+
+#[tokio::test]
+async fn test_import_chain_with_equal_timestamps() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let genesis = test_genesis()?;
+    let genesis_path = dir.path().join("genesis.json");
+    fs::write(&genesis_path, serde_json::to_vec(&genesis)?)?;
+
+    let chain_spec = Arc::new(ChainSpec::from(genesis));
+    let genesis_block = SealedBlock::seal_slow(Block {
+        header: chain_spec.genesis_header().clone(),
+        body: BlockBody {
+            withdrawals: Some(Withdrawals::default()),
+            ..Default::default()
+        },
+    });
+
+    // Blocks 1 and 2 share a timestamp, as several blocks per second do
+    let mut blocks = vec![genesis_block];
+    for timestamp in [TEST_TIMESTAMP + 1, TEST_TIMESTAMP + 1, TEST_TIMESTAMP + 2] {
+        let block = empty_block(blocks.last().unwrap(), timestamp);
+        blocks.push(block);
+    }
+
+    // Ethereum consensus, which reth's commands use by default, rejects the chain
+    let beacon = EthBeaconConsensus::new(chain_spec.clone());
+    assert!(beacon
+        .validate_header_against_parent(blocks[2].sealed_header(), blocks[1].sealed_header())
+        .is_err());
+
+    let mut rlp = Vec::new();
+    for block in &blocks[1..] {
+        block.clone_block().encode(&mut rlp);
+    }
+    let chain_path = dir.path().join("chain.rlp");
+    fs::write(&chain_path, rlp)?;
+
+    let datadir = dir.path().join("datadir");
+    let command = ImportCommand::<EthereumChainSpecParser>::try_parse_from([
+        "import",
+        "--chain",
+        genesis_path.to_str().unwrap(),
+        "--datadir",
+        datadir.to_str().unwrap(),
+        chain_path.to_str().unwrap(),
+    ])?;
+    command
+        .execute::<EthereumNode, _>(|chain_spec| rollkit_cli_components(chain_spec).unwrap())
+        .await?;
+
+    // Every stage ran through the last block
+    let db = reth_db::open_db_read_only(
+        &datadir.join("db"),
+        DatabaseArguments::new(Default::default()),
+    )?;
+    let tx = db.tx()?;
+    let finish = tx
+        .get::<tables::StageCheckpoints>("Finish".to_string())?
+        .expect("import should checkpoint the finish stage");
+    assert_eq!(finish.block_number, 3);
+    Ok(())
+}
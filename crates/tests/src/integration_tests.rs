@@ -202,3 +202,42 @@ fn test_workspace_integration() {
 
     println!("✓ workspace integration test passed");
 }
+
+/// Tests that the genesis subcommand generates a genesis for the requested chain
+#[test]
+fn test_ev_reth_genesis_command() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "-p",
+            "ev-reth",
+            "--bin",
+            "ev-reth",
+            "--",
+            "genesis",
+            "--chain-id",
+            "1234",
+            "--alloc",
+            "0x944fDcD1c868E3cC566C78023CcB38A32cDA836E=1000000000000000000",
+        ])
+        .output()
+        .expect("Failed to execute ev-reth genesis");
+
+    assert!(
+        output.status.success(),
+        "Genesis command failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let genesis: alloy_genesis::Genesis =
+        serde_json::from_slice(&output.stdout).expect("Genesis command should print a genesis");
+    assert_eq!(genesis.config.chain_id, 1234);
+    assert!(genesis.alloc.contains_key(
+        &"0x944fDcD1c868E3cC566C78023CcB38A32cDA836E"
+            .parse()
+            .unwrap()
+    ));
+
+    println!("✓ ev-reth genesis command test passed");
+}
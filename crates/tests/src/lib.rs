@@ -1,18 +1,48 @@
 //! Integration tests for ev-reth rollkit
 //!
 //! This crate contains integration tests for the ev-reth rollkit implementation,
-//! including payload builder tests, engine API tests, and common test utilities.
+//! including payload builder tests, engine API tests, common test utilities, an
+//! in-process node harness and golden vectors of the blocks built from payload attributes.
 
 pub mod common;
+pub mod golden;
+pub mod node;
+pub mod scenario;
 
+#[cfg(test)]
+mod attributes_tests;
+#[cfg(test)]
+mod batches_tests;
+#[cfg(test)]
+mod da_inclusion_tests;
+#[cfg(test)]
+mod deploy_allowlist_tests;
 #[cfg(test)]
 mod engine_api_tests;
 #[cfg(test)]
+mod execution_api_tests;
+#[cfg(test)]
+mod finality_tests;
+#[cfg(test)]
+mod golden_tests;
+#[cfg(test)]
+mod import_tests;
+#[cfg(test)]
 mod integration_tests;
 #[cfg(test)]
+mod node_tests;
+#[cfg(test)]
 mod payload_builder_tests;
 #[cfg(test)]
-mod test_rollkit_engine_api;
+mod replay_tests;
+#[cfg(test)]
+mod rewind_tests;
+#[cfg(test)]
+mod rollback_tests;
+#[cfg(test)]
+mod scenario_tests;
+#[cfg(test)]
+mod simulate_batch_tests;
 
 // Re-export common test utilities
 pub use common::*;
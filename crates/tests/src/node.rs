@@ -0,0 +1,655 @@
+//! In-process ev-reth node for end-to-end tests.
+//!
+//! [`TestNode`] launches `RollkitNode` with a temporary datadir, a genesis generated from a
+//! [`GenesisConfig`], a random JWT secret and unused ports, and talks to it over its HTTP
+//! and authenticated Engine API endpoints the way ev-node does. Unlike the payload builder
+//! fixtures, blocks are imported and persisted by the engine, so state roots hold over any
+//! number of blocks.
+
+use alloy_consensus::{SignableTransaction, TxEip1559};
+use alloy_eips::{eip2718::Encodable2718, BlockNumberOrTag};
+use alloy_genesis::Genesis;
+use alloy_primitives::{Address, Bytes, TxKind, B256, U256, U64};
+use alloy_rpc_types::{
+    engine::{
+        ExecutionPayloadEnvelopeV3, ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated,
+        JwtSecret, PayloadAttributes, PayloadId, PayloadStatus,
+    },
+    Block, TransactionReceipt,
+};
+use alloy_signer::SignerSync;
+use alloy_signer_local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner};
+use ev_reth::{
+    attributes::RollkitEnginePayloadAttributes, builder::RollkitArgs, configure_engine,
+    execution::EvolveExecutionService, RollkitEngineTypes, RollkitNode, RollkitNodeAddOns,
+};
+use evolve_ev_reth::{
+    config::RollkitConfig,
+    da_inclusion::{DaInclusionStore, EvolveTables},
+    genesis::{GenesisConfig, MnemonicAccounts, DEFAULT_MNEMONIC_BALANCE},
+    rpc::{
+        da_inclusion::{
+            EvolveDaInclusionAdminApiServer, EvolveDaInclusionApiImpl, EvolveDaInclusionApiServer,
+        },
+        finality::{EvolveFinalityApiImpl, EvolveFinalityApiServer, EvolveFinalityStatusApiServer},
+        rollback::{EvolveRollbackApiImpl, EvolveRollbackApiServer},
+        txpool::{RollkitTxpoolApiImpl, RollkitTxpoolApiServer},
+    },
+    BuildAttributesStore, EvolveChainConfig,
+};
+use eyre::Result;
+use jsonrpsee::{
+    core::{client::ClientT, params::ArrayParams},
+    http_client::HttpClient,
+    rpc_params,
+};
+use reth_chainspec::ChainSpec;
+use reth_db::{init_db, mdbx::DatabaseArguments, DatabaseEnv};
+use reth_ethereum_primitives::TransactionSigned;
+use reth_node_api::{FullNodeComponents, FullNodeTypes};
+use reth_node_builder::{FullNode, NodeAdapter, NodeBuilder, NodeHandle, RethFullAdapter};
+use reth_node_core::{
+    args::{DatadirArgs, RpcServerArgs},
+    node_config::NodeConfig,
+};
+use reth_primitives::Transaction;
+use reth_tasks::TaskManager;
+use serde::de::DeserializeOwned;
+use std::{fs, path::Path, sync::Arc, time::Duration};
+use tempfile::TempDir;
+
+use crate::common::{TEST_CHAIN_ID, TEST_GAS_LIMIT, TEST_TIMESTAMP};
+
+/// Mnemonic the prefunded accounts of the test genesis are derived from
+pub const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+/// Number of prefunded accounts derived from [`TEST_MNEMONIC`]
+pub const TEST_ACCOUNTS: u32 = 10;
+
+/// Maximum fee per gas of the transactions built by [`transfer`]
+pub const TEST_MAX_FEE_PER_GAS: u128 = 10_000_000_000;
+
+/// Priority fee per gas of the transactions built by [`transfer`], the default minimum
+/// gas price
+pub const TEST_MAX_PRIORITY_FEE_PER_GAS: u128 = 1_000_000_000;
+
+/// Name of the JWT secret file in the datadir
+const JWT_SECRET_FILE: &str = "jwt.hex";
+
+/// Time given to a node's tasks to exit on restarts
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+type TestNodeAdapter = NodeAdapter<RethFullAdapter<Arc<DatabaseEnv>, RollkitNode>>;
+
+/// Node launched by [`TestNode`], the same type `ev-reth node` runs
+pub type TestFullNode = FullNode<TestNodeAdapter, RollkitNodeAddOns<TestNodeAdapter>>;
+
+/// Execution service of a [`TestNode`]
+pub type TestExecutionService = EvolveExecutionService<
+    <TestNodeAdapter as FullNodeTypes>::Provider,
+    <TestNodeAdapter as FullNodeComponents>::Pool,
+>;
+
+/// Finality API of a [`TestNode`], as served by `evolve_setFinal`
+pub type TestFinalityApi = EvolveFinalityApiImpl<<TestNodeAdapter as FullNodeTypes>::Provider>;
+
+/// Builder of a [`TestNode`]
+#[derive(Debug, Clone)]
+pub struct TestNodeBuilder {
+    /// Description of the chain the genesis is generated from
+    genesis: GenesisConfig,
+    /// ev-reth arguments of the node
+    args: RollkitArgs,
+}
+
+impl Default for TestNodeBuilder {
+    fn default() -> Self {
+        Self {
+            genesis: GenesisConfig {
+                chain_id: TEST_CHAIN_ID,
+                gas_limit: TEST_GAS_LIMIT,
+                timestamp: TEST_TIMESTAMP,
+                mnemonic: Some(MnemonicAccounts {
+                    phrase: TEST_MNEMONIC.to_string(),
+                    count: TEST_ACCOUNTS,
+                    balance: DEFAULT_MNEMONIC_BALANCE,
+                }),
+                ..Default::default()
+            },
+            args: RollkitArgs::default(),
+        }
+    }
+}
+
+impl TestNodeBuilder {
+    /// Generates the genesis from the given configuration instead of the default one,
+    /// which prefunds the [`TEST_MNEMONIC`] accounts
+    pub fn with_genesis(mut self, genesis: GenesisConfig) -> Self {
+        self.genesis = genesis;
+        self
+    }
+
+    /// Sets the Evolve parameters of the genesis, stored under `config.evolve`
+    pub fn with_evolve_config(mut self, evolve: serde_json::Value) -> Self {
+        self.genesis.evolve = Some(evolve);
+        self
+    }
+
+    /// Sets the ev-reth arguments the node is launched with
+    pub fn with_args(mut self, args: RollkitArgs) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Generates the genesis the node is launched with
+    pub fn build_genesis(&self) -> Result<Genesis> {
+        Ok(self.genesis.build()?)
+    }
+
+    /// Launches the node
+    pub async fn launch(self) -> Result<TestNode> {
+        let genesis = self.build_genesis()?;
+        self.launch_with_genesis(genesis).await
+    }
+
+    /// Launches the node on an already generated genesis, ignoring the configuration the
+    /// builder would generate one from
+    pub async fn launch_with_genesis(self, genesis: Genesis) -> Result<TestNode> {
+        let datadir = tempfile::tempdir()?;
+
+        fs::write(
+            datadir.path().join("genesis.json"),
+            serde_json::to_string_pretty(&genesis)?,
+        )?;
+        let chain_spec = Arc::new(ChainSpec::from(genesis));
+
+        let jwt_secret = JwtSecret::random();
+        fs::write(
+            datadir.path().join(JWT_SECRET_FILE),
+            hex::encode(jwt_secret.as_bytes()),
+        )?;
+
+        TestNode::start(datadir, chain_spec, jwt_secret, self.args).await
+    }
+}
+
+/// ev-reth node running in-process, along with typed clients of its RPC endpoints.
+///
+/// The node shuts down when dropped and its datadir is removed.
+#[derive(Debug)]
+pub struct TestNode {
+    /// Launched node, giving direct access to its provider, pool and engine
+    pub node: TestFullNode,
+    /// Client of the HTTP RPC endpoint
+    rpc: HttpClient,
+    /// Chain spec of the generated genesis
+    chain_spec: Arc<ChainSpec>,
+    /// Secret authenticating Engine API requests
+    jwt_secret: JwtSecret,
+    /// ev-reth arguments of the node
+    args: RollkitArgs,
+    /// Byte limit of the transactions served by `txpoolExt_getTxs`
+    max_txpool_bytes: u64,
+    /// Base fee selected transactions must pay, the pool's pending base fee when unset
+    selection_base_fee: Option<u64>,
+    /// Tasks of the node
+    tasks: TaskManager,
+    /// Datadir holding the database, the genesis and the JWT secret
+    datadir: TempDir,
+}
+
+impl TestNode {
+    /// Starts a node on a datadir holding the genesis and the JWT secret
+    async fn start(
+        datadir: TempDir,
+        chain_spec: Arc<ChainSpec>,
+        jwt_secret: JwtSecret,
+        args: RollkitArgs,
+    ) -> Result<Self> {
+        let mut config = NodeConfig::new(chain_spec.clone())
+            .with_datadir_args(DatadirArgs {
+                datadir: datadir.path().to_path_buf().into(),
+                ..Default::default()
+            })
+            .with_rpc(RpcServerArgs::default().with_http())
+            .with_unused_ports();
+        config.rpc.auth_jwtsecret = Some(datadir.path().join(JWT_SECRET_FILE));
+        config.network.discovery.disable_discovery = true;
+        configure_engine(&mut config.engine);
+        // Persist every block right away, so the datadir holds the whole chain on restarts
+        config.engine.persistence_threshold = 0;
+        config.engine.memory_block_buffer_target = 0;
+
+        let chain_config = EvolveChainConfig::from_chain_spec(&chain_spec)?;
+        let selection_base_fee = chain_config.zero_fee.then_some(0);
+        let max_txpool_bytes = RollkitConfig::for_max_block_bytes(
+            args.payload_builder_config(&chain_config)?.max_block_bytes,
+        )
+        .max_txpool_bytes;
+
+        let db = Arc::new(init_db(
+            config.datadir().db(),
+            DatabaseArguments::new(Default::default()),
+        )?);
+        db.create_tables_for::<EvolveTables>()?;
+        let da_inclusions = DaInclusionStore::new(db.clone());
+        let build_attributes = BuildAttributesStore::new(db.clone());
+
+        let tasks = TaskManager::current();
+        // The RPC modules ev-node and the tests rely on, registered as `ev-reth node` does
+        let NodeHandle { node, .. } = NodeBuilder::new(config)
+            .with_database(db)
+            .with_launch_context(tasks.executor())
+            .node(RollkitNode::new(args.clone()))
+            .extend_rpc_modules(move |ctx| {
+                let rollkit_txpool =
+                    RollkitTxpoolApiImpl::new(ctx.pool().clone(), max_txpool_bytes)
+                        .with_base_fee(selection_base_fee);
+                ctx.modules.merge_configured(rollkit_txpool.into_rpc())?;
+
+                let evolve_finality = EvolveFinalityApiImpl::new(ctx.provider().clone());
+                ctx.auth_module
+                    .merge_auth_methods(EvolveFinalityApiServer::into_rpc(evolve_finality))?;
+
+                let evolve_finality_status = EvolveFinalityStatusApiServer::into_rpc(
+                    EvolveFinalityApiImpl::new(ctx.provider().clone()),
+                );
+                ctx.auth_module
+                    .merge_auth_methods(evolve_finality_status.clone())?;
+                ctx.modules.merge_configured(evolve_finality_status)?;
+
+                let evolve_rollback = EvolveRollbackApiImpl::new(
+                    ctx.provider().clone(),
+                    ctx.beacon_engine_handle.clone(),
+                    da_inclusions.clone(),
+                    build_attributes,
+                );
+                ctx.auth_module
+                    .merge_auth_methods(evolve_rollback.into_rpc())?;
+
+                let evolve_da_inclusion_admin =
+                    EvolveDaInclusionApiImpl::new(ctx.provider().clone(), da_inclusions.clone());
+                ctx.auth_module
+                    .merge_auth_methods(EvolveDaInclusionAdminApiServer::into_rpc(
+                        evolve_da_inclusion_admin,
+                    ))?;
+                let evolve_da_inclusion =
+                    EvolveDaInclusionApiImpl::new(ctx.provider().clone(), da_inclusions);
+                ctx.modules
+                    .merge_configured(EvolveDaInclusionApiServer::into_rpc(evolve_da_inclusion))?;
+                Ok(())
+            })
+            .launch()
+            .await?;
+
+        let rpc = node
+            .rpc_server_handle()
+            .http_client()
+            .ok_or_else(|| eyre::eyre!("the HTTP RPC server is not running"))?;
+        Ok(Self {
+            node,
+            rpc,
+            chain_spec,
+            jwt_secret,
+            args,
+            max_txpool_bytes,
+            selection_base_fee,
+            tasks,
+            datadir,
+        })
+    }
+
+    /// Shuts the node down and starts it again on the same datadir, as after a crash or an
+    /// upgrade of ev-reth
+    pub async fn restart(self) -> Result<Self> {
+        self.restart_after(|_| Ok(())).await
+    }
+
+    /// Shuts the node down, runs `offline` on its datadir, as an ev-reth command run on a
+    /// stopped node does, and starts the node again on the datadir
+    pub async fn restart_after(
+        self,
+        offline: impl FnOnce(&Path) -> Result<()> + Send + 'static,
+    ) -> Result<Self> {
+        let Self {
+            node,
+            rpc,
+            chain_spec,
+            jwt_secret,
+            args,
+            tasks,
+            datadir,
+            ..
+        } = self;
+        drop(rpc);
+        drop(node);
+        // The database can only be reopened once every task holding it has exited
+        let stopped = tokio::task::spawn_blocking(move || {
+            tasks.graceful_shutdown_with_timeout(SHUTDOWN_TIMEOUT)
+        })
+        .await?;
+        if !stopped {
+            eyre::bail!("node did not shut down within {SHUTDOWN_TIMEOUT:?}");
+        }
+        let path = datadir.path().to_path_buf();
+        tokio::task::spawn_blocking(move || offline(&path)).await??;
+        Self::start(datadir, chain_spec, jwt_secret, args).await
+    }
+
+    /// Returns the execution service ev-node drives through the Evolve execution API,
+    /// producing blocks with `fee_recipient` as their beneficiary
+    pub fn execution_service(&self, fee_recipient: Address) -> TestExecutionService {
+        EvolveExecutionService::new(
+            self.node.provider.clone(),
+            self.node.pool.clone(),
+            self.node.add_ons_handle.beacon_engine_handle.clone(),
+            self.node.payload_builder_handle.clone(),
+            fee_recipient,
+            self.max_txpool_bytes,
+            self.selection_base_fee,
+        )
+    }
+
+    /// Returns the finality API moving the node's safe and finalized heads
+    pub fn finality_api(&self) -> TestFinalityApi {
+        EvolveFinalityApiImpl::new(self.node.provider.clone())
+    }
+
+    /// Launches a node on the default test genesis
+    pub async fn launch() -> Result<Self> {
+        TestNodeBuilder::default().launch().await
+    }
+
+    /// Returns a builder to customize the genesis and arguments of the node
+    pub fn builder() -> TestNodeBuilder {
+        TestNodeBuilder::default()
+    }
+
+    /// Returns the chain spec of the node
+    pub fn chain_spec(&self) -> &Arc<ChainSpec> {
+        &self.chain_spec
+    }
+
+    /// Returns the hash of the genesis block
+    pub fn genesis_hash(&self) -> B256 {
+        self.chain_spec.genesis_hash()
+    }
+
+    /// Returns the JWT secret of the Engine API endpoint
+    pub const fn jwt_secret(&self) -> &JwtSecret {
+        &self.jwt_secret
+    }
+
+    /// Returns the task manager of the node
+    pub const fn tasks(&self) -> &TaskManager {
+        &self.tasks
+    }
+
+    /// Returns the path of the node's datadir
+    pub fn datadir(&self) -> &Path {
+        self.datadir.path()
+    }
+
+    /// Returns the prefunded account derived from [`TEST_MNEMONIC`] at `index`
+    pub fn signer(&self, index: u32) -> PrivateKeySigner {
+        test_signer(index)
+    }
+
+    /// Sends a request to the HTTP RPC endpoint
+    pub async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<R> {
+        Ok(self.rpc.request(method, params).await?)
+    }
+
+    /// Sends a request to the JWT-authenticated Engine API endpoint
+    pub async fn engine_request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: ArrayParams,
+    ) -> Result<R> {
+        let client = self.node.auth_server_handle().http_client();
+        Ok(client.request(method, params).await?)
+    }
+
+    /// Calls `engine_forkchoiceUpdatedV3`
+    pub async fn fork_choice_updated(
+        &self,
+        state: ForkchoiceState,
+        attributes: Option<RollkitEnginePayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated> {
+        self.engine_request("engine_forkchoiceUpdatedV3", rpc_params![state, attributes])
+            .await
+    }
+
+    /// Calls `engine_getPayloadV3`
+    pub async fn get_payload(&self, payload_id: PayloadId) -> Result<ExecutionPayloadEnvelopeV3> {
+        self.engine_request("engine_getPayloadV3", rpc_params![payload_id])
+            .await
+    }
+
+    /// Calls `engine_newPayloadV3` with a payload without blobs
+    pub async fn new_payload(
+        &self,
+        payload: ExecutionPayloadV3,
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatus> {
+        self.engine_request(
+            "engine_newPayloadV3",
+            rpc_params![payload, Vec::<B256>::new(), parent_beacon_block_root],
+        )
+        .await
+    }
+
+    /// Builds a block with the given transactions on top of `parent` and makes it the
+    /// canonical head, as ev-node's `ExecuteTxs` does over the Engine API
+    pub async fn build_block_on(
+        &self,
+        parent: B256,
+        timestamp: u64,
+        transactions: Vec<Bytes>,
+    ) -> Result<ExecutionPayloadV3> {
+        self.build_block_with(parent, payload_attributes(timestamp, transactions))
+            .await
+    }
+
+    /// Builds a block from the given attributes on top of `parent` and makes it the
+    /// canonical head
+    pub async fn build_block_with(
+        &self,
+        parent: B256,
+        attributes: RollkitEnginePayloadAttributes,
+    ) -> Result<ExecutionPayloadV3> {
+        let parent_beacon_block_root = attributes
+            .inner
+            .parent_beacon_block_root
+            .unwrap_or_default();
+        let updated = self
+            .fork_choice_updated(forkchoice_state(parent), Some(attributes))
+            .await?;
+        if !updated.payload_status.is_valid() {
+            eyre::bail!(
+                "forkchoice update to {parent} rejected: {:?}",
+                updated.payload_status.status
+            );
+        }
+        let payload_id = updated
+            .payload_id
+            .ok_or_else(|| eyre::eyre!("forkchoice update with attributes started no payload"))?;
+        let payload = self.get_payload(payload_id).await?.execution_payload;
+
+        let status = self
+            .new_payload(payload.clone(), parent_beacon_block_root)
+            .await?;
+        if !status.is_valid() {
+            eyre::bail!("payload rejected: {:?}", status.status);
+        }
+        let head = payload.payload_inner.payload_inner.block_hash;
+        let updated = self
+            .fork_choice_updated(forkchoice_state(head), None)
+            .await?;
+        if !updated.payload_status.is_valid() {
+            eyre::bail!(
+                "forkchoice update to {head} rejected: {:?}",
+                updated.payload_status.status
+            );
+        }
+        Ok(payload)
+    }
+
+    /// Builds a block with the given transactions on top of the latest block, one second
+    /// after it
+    pub async fn build_block(&self, transactions: Vec<Bytes>) -> Result<ExecutionPayloadV3> {
+        let head = self
+            .block_by_number(BlockNumberOrTag::Latest, false)
+            .await?
+            .ok_or_else(|| eyre::eyre!("no latest block"))?;
+        self.build_block_on(head.header.hash, head.header.timestamp + 1, transactions)
+            .await
+    }
+
+    /// Calls `txpoolExt_getTxs`
+    pub async fn txpool_get_txs(&self) -> Result<Vec<Bytes>> {
+        self.request("txpoolExt_getTxs", rpc_params![]).await
+    }
+
+    /// Calls `eth_chainId`
+    pub async fn chain_id(&self) -> Result<u64> {
+        Ok(self
+            .request::<U64>("eth_chainId", rpc_params![])
+            .await?
+            .to())
+    }
+
+    /// Calls `eth_blockNumber`
+    pub async fn block_number(&self) -> Result<u64> {
+        Ok(self
+            .request::<U64>("eth_blockNumber", rpc_params![])
+            .await?
+            .to())
+    }
+
+    /// Calls `eth_getBlockByNumber`
+    pub async fn block_by_number(
+        &self,
+        number: BlockNumberOrTag,
+        full: bool,
+    ) -> Result<Option<Block>> {
+        self.request("eth_getBlockByNumber", rpc_params![number, full])
+            .await
+    }
+
+    /// Calls `eth_getBalance` on the latest block
+    pub async fn balance(&self, address: Address) -> Result<U256> {
+        self.request(
+            "eth_getBalance",
+            rpc_params![address, BlockNumberOrTag::Latest],
+        )
+        .await
+    }
+
+    /// Calls `eth_getTransactionCount` on the latest block
+    pub async fn nonce(&self, address: Address) -> Result<u64> {
+        Ok(self
+            .request::<U64>(
+                "eth_getTransactionCount",
+                rpc_params![address, BlockNumberOrTag::Latest],
+            )
+            .await?
+            .to())
+    }
+
+    /// Calls `eth_sendRawTransaction`
+    pub async fn send_raw_transaction(&self, transaction: Bytes) -> Result<B256> {
+        self.request("eth_sendRawTransaction", rpc_params![transaction])
+            .await
+    }
+
+    /// Calls `eth_getTransactionReceipt`
+    pub async fn transaction_receipt(&self, hash: B256) -> Result<Option<TransactionReceipt>> {
+        self.request("eth_getTransactionReceipt", rpc_params![hash])
+            .await
+    }
+}
+
+/// Returns the prefunded account derived from [`TEST_MNEMONIC`] at `index`
+pub fn test_signer(index: u32) -> PrivateKeySigner {
+    MnemonicBuilder::<English>::default()
+        .phrase(TEST_MNEMONIC)
+        .index(index)
+        .and_then(|builder| builder.build())
+        .expect("test mnemonic should derive accounts")
+}
+
+/// Returns a forkchoice state with `head` as its head and no safe or finalized block
+pub fn forkchoice_state(head: B256) -> ForkchoiceState {
+    ForkchoiceState {
+        head_block_hash: head,
+        safe_block_hash: B256::ZERO,
+        finalized_block_hash: B256::ZERO,
+    }
+}
+
+/// Returns the payload attributes ev-node sends to build a block with the given
+/// transactions
+pub fn payload_attributes(
+    timestamp: u64,
+    transactions: Vec<Bytes>,
+) -> RollkitEnginePayloadAttributes {
+    RollkitEnginePayloadAttributes {
+        inner: PayloadAttributes {
+            timestamp,
+            prev_randao: B256::ZERO,
+            suggested_fee_recipient: Address::ZERO,
+            withdrawals: Some(Vec::new()),
+            parent_beacon_block_root: Some(B256::ZERO),
+        },
+        transactions: Some(transactions),
+        gas_limit: Some(TEST_GAS_LIMIT),
+        da_info: None,
+        timestamp_millis: None,
+    }
+}
+
+/// Returns a transfer of `value` wei to `to`, signed by `signer`
+pub fn transfer(
+    signer: &PrivateKeySigner,
+    nonce: u64,
+    to: Address,
+    value: U256,
+) -> Result<TransactionSigned> {
+    sign_transaction(
+        signer,
+        TxEip1559 {
+            chain_id: TEST_CHAIN_ID,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: TEST_MAX_FEE_PER_GAS,
+            max_priority_fee_per_gas: TEST_MAX_PRIORITY_FEE_PER_GAS,
+            to: TxKind::Call(to),
+            value,
+            ..Default::default()
+        },
+    )
+}
+
+/// Signs a transaction with `signer`
+pub fn sign_transaction(signer: &PrivateKeySigner, tx: TxEip1559) -> Result<TransactionSigned> {
+    let signature = signer.sign_hash_sync(&tx.signature_hash())?;
+    Ok(TransactionSigned::new_unhashed(
+        Transaction::Eip1559(tx),
+        signature,
+    ))
+}
+
+/// Returns the EIP-2718 encoding of transactions, as sent in payload attributes
+pub fn encode_transactions<'a>(
+    transactions: impl IntoIterator<Item = &'a TransactionSigned>,
+) -> Vec<Bytes> {
+    transactions
+        .into_iter()
+        .map(|tx| tx.encoded_2718().into())
+        .collect()
+}
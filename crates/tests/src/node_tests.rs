@@ -0,0 +1,135 @@
+//! End-to-end tests driving an in-process node over its RPC endpoints.
+
+use alloy_eips::{eip2718::Encodable2718, BlockNumberOrTag};
+use alloy_primitives::{Address, B256, U256};
+use alloy_rpc_types_engine::PayloadStatusEnum;
+use eyre::Result;
+
+use crate::{
+    common::TEST_CHAIN_ID,
+    node::{encode_transactions, forkchoice_state, payload_attributes, transfer, TestNode},
+};
+
+/// A transaction sent over `eth_sendRawTransaction` is served by `txpoolExt_getTxs`, and the
+/// block built from it over the Engine API is imported and reflected by `eth_*`
+#[tokio::test]
+async fn test_node_executes_txpool_transactions() -> Result<()> {
+    let node = TestNode::launch().await?;
+    assert_eq!(node.chain_id().await?, TEST_CHAIN_ID);
+    assert_eq!(node.block_number().await?, 0);
+
+    let signer = node.signer(0);
+    let to = Address::with_last_byte(1);
+    let tx = transfer(&signer, 0, to, U256::from(1_000))?;
+    let hash = node.send_raw_transaction(tx.encoded_2718().into()).await?;
+    assert_eq!(hash, *tx.tx_hash());
+
+    let txs = node.txpool_get_txs().await?;
+    assert_eq!(txs, encode_transactions([&tx]));
+
+    let payload = node.build_block(txs).await?;
+    assert_eq!(payload.payload_inner.payload_inner.block_number, 1);
+    assert_eq!(payload.payload_inner.payload_inner.transactions.len(), 1);
+    assert_eq!(node.block_number().await?, 1);
+
+    let receipt = node
+        .transaction_receipt(hash)
+        .await?
+        .expect("transaction should be included");
+    assert!(receipt.status());
+    assert_eq!(receipt.block_number, Some(1));
+    assert_eq!(node.nonce(signer.address()).await?, 1);
+    assert_eq!(node.balance(to).await?, U256::from(1_000));
+
+    // Included transactions leave the pool
+    assert!(node.txpool_get_txs().await?.is_empty());
+    Ok(())
+}
+
+/// Blocks built over the Engine API chain up and their state roots hold, as each block is
+/// validated on top of the state the previous one left
+#[tokio::test]
+async fn test_node_builds_chain_of_blocks() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let signer = node.signer(1);
+    let to = Address::with_last_byte(2);
+
+    let mut parent_hash = node.genesis_hash();
+    for nonce in 0..5 {
+        let tx = transfer(&signer, nonce, to, U256::from(1))?;
+        let payload = node.build_block(encode_transactions([&tx])).await?;
+        let block = &payload.payload_inner.payload_inner;
+        assert_eq!(block.parent_hash, parent_hash);
+        assert_eq!(block.block_number, nonce + 1);
+
+        let latest = node
+            .block_by_number(BlockNumberOrTag::Latest, false)
+            .await?
+            .expect("latest block should exist");
+        assert_eq!(latest.header.hash, block.block_hash);
+        assert_eq!(latest.header.state_root, block.state_root);
+        parent_hash = block.block_hash;
+    }
+
+    assert_eq!(node.block_number().await?, 5);
+    assert_eq!(node.nonce(signer.address()).await?, 5);
+    assert_eq!(node.balance(to).await?, U256::from(5));
+    Ok(())
+}
+
+/// A forkchoice update to an unknown head reports the node as syncing and leaves the chain
+/// as it is
+#[tokio::test]
+async fn test_engine_api_fork_choice_update_to_unknown_head() -> Result<()> {
+    let node = TestNode::launch().await?;
+
+    let updated = node
+        .fork_choice_updated(forkchoice_state(B256::repeat_byte(0x42)), None)
+        .await?;
+    assert!(
+        matches!(updated.payload_status.status, PayloadStatusEnum::Syncing),
+        "{updated:?}"
+    );
+    assert!(updated.payload_id.is_none());
+    assert_eq!(node.block_number().await?, 0);
+    Ok(())
+}
+
+/// Transactions passed in the payload attributes of `engine_forkchoiceUpdatedV3` make up the
+/// payload served by `engine_getPayloadV3`, without going through the pool
+#[tokio::test]
+async fn test_engine_api_fork_choice_with_transactions() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let signer = node.signer(2);
+    let to = Address::with_last_byte(3);
+    let transactions = (0..2)
+        .map(|nonce| transfer(&signer, nonce, to, U256::from(1)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let genesis = node
+        .block_by_number(BlockNumberOrTag::Number(0), false)
+        .await?
+        .expect("genesis should exist");
+    let attributes = payload_attributes(
+        genesis.header.timestamp + 1,
+        encode_transactions(&transactions),
+    );
+    let updated = node
+        .fork_choice_updated(forkchoice_state(genesis.header.hash), Some(attributes))
+        .await?;
+    assert!(updated.payload_status.is_valid(), "{updated:?}");
+    let payload_id = updated
+        .payload_id
+        .expect("forkchoice update with attributes should start a payload");
+
+    let payload = node.get_payload(payload_id).await?.execution_payload;
+    let block = &payload.payload_inner.payload_inner;
+    assert_eq!(block.parent_hash, genesis.header.hash);
+    assert_eq!(block.block_number, 1);
+    assert_eq!(block.transactions, encode_transactions(&transactions));
+    assert!(node.txpool_get_txs().await?.is_empty());
+
+    // The payload only becomes canonical through newPayload and a forkchoice update
+    assert_eq!(node.block_number().await?, 0);
+    Ok(())
+}
@@ -8,9 +8,18 @@ use crate::common;
 
 use eyre::Result;
 use std::time::Duration;
-use tokio::time::timeout;
+use tokio::{sync::broadcast::error::TryRecvError, time::timeout};
 
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_signer_local::PrivateKeySigner;
 use common::{create_test_transactions, RollkitTestFixture, TEST_GAS_LIMIT, TEST_TIMESTAMP};
+use ev_node::{RollkitPayloadBuilderConfig, SkipReason};
+use evolve_ev_reth::{
+    BuildAttributesRecorder, DaBlockInfo, EvolveChainConfig, EvolveExtraData,
+    PreconfirmationStatus, Preconfirmations, TimestampRules, BLOCK_OVERHEAD_BYTES,
+};
+use reth_primitives::Header;
 
 /// Tests basic payload building with empty transactions
 #[tokio::test]
@@ -295,3 +304,406 @@ async fn test_gas_limit_scenarios() -> Result<()> {
     println!("✓ Gas limit scenarios test passed");
     Ok(())
 }
+
+/// Tests that DA block info is recorded in the header of the built block
+#[tokio::test]
+async fn test_payload_with_da_info() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+
+    let da_info = DaBlockInfo::new(42, B256::repeat_byte(0xda), TEST_TIMESTAMP - 6);
+    let payload_attrs = fixture
+        .create_payload_attributes(
+            create_test_transactions(2, 0),
+            1,
+            TEST_TIMESTAMP,
+            fixture.genesis_hash,
+            Some(TEST_GAS_LIMIT),
+        )
+        .with_da_info(da_info);
+
+    let sealed_block = fixture.builder.build_payload(payload_attrs).await?;
+    assert_eq!(
+        DaBlockInfo::from_extra_data(&sealed_block.extra_data),
+        Some(da_info),
+        "Header should carry the DA block info"
+    );
+
+    // Blocks built without DA info must not carry the extension
+    let payload_attrs = fixture.create_payload_attributes(
+        vec![],
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+    let sealed_block = fixture.builder.build_payload(payload_attrs).await?;
+    assert_eq!(DaBlockInfo::from_extra_data(&sealed_block.extra_data), None);
+
+    println!("✓ DA info payload test passed");
+    Ok(())
+}
+
+/// Tests that the millisecond timestamp is recorded in the header of the built block
+#[tokio::test]
+async fn test_payload_with_timestamp_millis() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+
+    let timestamp_millis = TEST_TIMESTAMP * 1000 + 250;
+    let payload_attrs = fixture
+        .create_payload_attributes(
+            create_test_transactions(2, 0),
+            1,
+            TEST_TIMESTAMP,
+            fixture.genesis_hash,
+            Some(TEST_GAS_LIMIT),
+        )
+        .with_timestamp_millis(timestamp_millis);
+
+    let sealed_block = fixture.builder.build_payload(payload_attrs).await?;
+    let extension = EvolveExtraData::decode(&sealed_block.extra_data);
+    assert_eq!(
+        extension.and_then(|extension| extension.timestamp_millis),
+        Some(timestamp_millis),
+        "Header should carry the millisecond timestamp"
+    );
+    assert_eq!(
+        EvolveExtraData::timestamp_millis_of(sealed_block.header()),
+        timestamp_millis
+    );
+
+    println!("✓ Millisecond timestamp payload test passed");
+    Ok(())
+}
+
+/// Tests zero-fee chains build blocks with a zero base fee whatever the parent's base fee
+#[tokio::test]
+async fn test_zero_fee_payload() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    fixture.builder.evm_config = fixture.builder.evm_config.clone().with_zero_fee(true);
+
+    let parent_hash = B256::random();
+    fixture.provider.add_header(
+        parent_hash,
+        Header {
+            state_root: fixture.genesis_state_root,
+            number: 1,
+            gas_limit: TEST_GAS_LIMIT,
+            gas_used: TEST_GAS_LIMIT,
+            timestamp: TEST_TIMESTAMP,
+            base_fee_per_gas: Some(1_000_000_000),
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..Default::default()
+        },
+    );
+
+    let payload_attrs = fixture.create_payload_attributes(
+        create_test_transactions(2, 0),
+        2,
+        TEST_TIMESTAMP + 1,
+        parent_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    let sealed_block = fixture.builder.build_payload(payload_attrs).await?;
+    assert_eq!(sealed_block.base_fee_per_gas, Some(0));
+    assert_eq!(sealed_block.body().transactions.len(), 2);
+    // Gas accounting is unaffected by the zero base fee
+    assert_eq!(sealed_block.gas_used, 2 * 21_000);
+    assert_eq!(sealed_block.gas_limit, TEST_GAS_LIMIT);
+
+    println!("✓ Zero-fee payload test passed");
+    Ok(())
+}
+
+/// Tests the minimum gas price and maximum transaction count of the payload builder
+#[tokio::test]
+async fn test_payload_builder_limits() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    fixture.builder.config = RollkitPayloadBuilderConfig::new()
+        .with_max_transactions(2)
+        .with_min_gas_price(10);
+
+    let transactions = vec![
+        fixture.create_funded_transaction(Address::with_last_byte(1), 9),
+        fixture.create_funded_transaction(Address::with_last_byte(2), 10),
+        fixture.create_funded_transaction(Address::with_last_byte(3), 20),
+        fixture.create_funded_transaction(Address::with_last_byte(4), 10),
+    ];
+    let payload_attrs = fixture.create_payload_attributes(
+        transactions.clone(),
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    let built = fixture
+        .builder
+        .build_payload_with_report(payload_attrs)
+        .await?;
+    assert_eq!(built.block.transaction_count(), 2);
+    assert_eq!(built.skipped.len(), 2);
+    assert_eq!(built.skipped[0].hash, *transactions[0].hash());
+    assert_eq!(
+        built.skipped[0].reason,
+        SkipReason::GasPriceTooLow {
+            effective_gas_price: 9,
+            min_gas_price: 10
+        }
+    );
+    assert_eq!(built.skipped[1].hash, *transactions[3].hash());
+    assert_eq!(built.skipped[1].reason, SkipReason::MaxTransactions(2));
+
+    // Zero-fee chains have no minimum gas price by default
+    let zero_fee = EvolveChainConfig {
+        zero_fee: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        RollkitPayloadBuilderConfig::for_chain(&zero_fee).min_gas_price,
+        0
+    );
+    assert_eq!(
+        RollkitPayloadBuilderConfig::for_chain(&EvolveChainConfig::default()),
+        RollkitPayloadBuilderConfig::default()
+    );
+    assert!(RollkitPayloadBuilderConfig::new()
+        .with_max_transactions(0)
+        .validate()
+        .is_err());
+
+    println!("✓ Payload builder limits test passed");
+    Ok(())
+}
+
+/// Tests the payload builder stops including transactions at the maximum block size
+#[tokio::test]
+async fn test_payload_builder_max_block_bytes() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    let transactions: Vec<_> = (1..=4)
+        .map(|to| fixture.create_funded_transaction(Address::with_last_byte(to), 10))
+        .collect();
+    let tx_bytes = transactions[0].network_len() as u64;
+    let max_block_bytes = BLOCK_OVERHEAD_BYTES + 2 * tx_bytes + tx_bytes / 2;
+    fixture.builder.config = fixture
+        .builder
+        .config
+        .with_max_block_bytes(Some(max_block_bytes));
+
+    let payload_attrs = fixture.create_payload_attributes(
+        transactions.clone(),
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    let built = fixture
+        .builder
+        .build_payload_with_report(payload_attrs)
+        .await?;
+    assert_eq!(built.block.transaction_count(), 2);
+    assert!(built.block.rlp_length() as u64 <= max_block_bytes);
+    let skipped: Vec<_> = built.skipped.iter().map(|skipped| skipped.hash).collect();
+    assert_eq!(
+        skipped,
+        vec![*transactions[2].hash(), *transactions[3].hash()]
+    );
+    assert!(built
+        .skipped
+        .iter()
+        .all(|skipped| skipped.reason == SkipReason::MaxBlockBytes(max_block_bytes)));
+
+    println!("✓ Payload builder block size test passed");
+    Ok(())
+}
+
+/// Tests blocks carrying the largest header extension (DA info, sequencer signature,
+/// timestamp run and millisecond timestamp) fill up to the maximum block size without going
+/// over it
+#[tokio::test]
+async fn test_payload_builder_max_block_bytes_with_largest_header() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    fixture.builder.sequencer_signer = Some(PrivateKeySigner::random());
+    // Blocks only record their timestamp run where runs are bounded
+    fixture.builder.evm_config =
+        fixture
+            .builder
+            .evm_config
+            .clone()
+            .with_timestamp_rules(TimestampRules {
+                max_equal_timestamp_run: Some(10),
+                ..Default::default()
+            });
+    let transactions: Vec<_> = (1..=3)
+        .map(|to| fixture.create_funded_transaction(Address::with_last_byte(to), 10))
+        .collect();
+    let tx_bytes = transactions[0].network_len() as u64;
+    let max_block_bytes = BLOCK_OVERHEAD_BYTES + 2 * tx_bytes;
+    fixture.builder.config = fixture
+        .builder
+        .config
+        .with_max_block_bytes(Some(max_block_bytes));
+
+    // Sharing the genesis timestamp gives the block a timestamp run
+    let payload_attrs = fixture
+        .create_payload_attributes(
+            transactions.clone(),
+            1,
+            TEST_TIMESTAMP,
+            fixture.genesis_hash,
+            Some(TEST_GAS_LIMIT),
+        )
+        .with_da_info(DaBlockInfo::new(
+            u64::MAX,
+            B256::repeat_byte(0xda),
+            TEST_TIMESTAMP - 6,
+        ))
+        .with_timestamp_millis(TEST_TIMESTAMP * 1000 + 999);
+
+    let built = fixture
+        .builder
+        .build_payload_with_report(payload_attrs)
+        .await?;
+    let extension = EvolveExtraData::decode(&built.block.extra_data)
+        .expect("header should carry the extension");
+    assert!(extension.da_info.is_some());
+    assert!(extension.signature.is_some());
+    assert!(extension.timestamp_run > 0);
+    assert!(extension.timestamp_millis.is_some());
+
+    assert_eq!(built.block.transaction_count(), 2);
+    assert!(built.block.rlp_length() as u64 <= max_block_bytes);
+    assert_eq!(built.skipped.len(), 1);
+    assert_eq!(built.skipped[0].hash, *transactions[2].hash());
+    assert_eq!(
+        built.skipped[0].reason,
+        SkipReason::MaxBlockBytes(max_block_bytes)
+    );
+
+    println!("✓ Payload builder largest header test passed");
+    Ok(())
+}
+
+/// Tests the payload builder publishes preconfirmations of executed transactions
+#[tokio::test]
+async fn test_payload_builder_preconfirmations() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    let preconfirmations = Preconfirmations::default();
+    fixture.builder.preconfirmations = Some(preconfirmations.clone());
+    let mut subscriber = preconfirmations.subscribe();
+
+    let to = Address::with_last_byte(1);
+    let transaction = fixture.create_funded_transaction(to, 10);
+    let payload_attrs = fixture.create_payload_attributes(
+        vec![transaction.clone()],
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    let sealed_block = fixture.builder.build_payload(payload_attrs).await?;
+    assert_eq!(sealed_block.transaction_count(), 1);
+
+    let preconfirmation = subscriber.try_recv()?;
+    assert_eq!(preconfirmation.status, PreconfirmationStatus::Preconfirmed);
+    assert_eq!(preconfirmation.transaction_hash, *transaction.hash());
+    assert_eq!(preconfirmation.transaction_index, 0);
+    assert_eq!(preconfirmation.block_number, 1);
+    assert_eq!(preconfirmation.parent_hash, fixture.genesis_hash);
+    assert_eq!(preconfirmation.to, Some(to));
+    assert!(preconfirmation.success);
+    assert_eq!(preconfirmation.gas_used, 21_000);
+    assert_eq!(preconfirmation.cumulative_gas_used, sealed_block.gas_used);
+    assert_eq!(
+        preconfirmations.get(transaction.hash()),
+        Some(preconfirmation)
+    );
+
+    println!("✓ Preconfirmation test passed");
+    Ok(())
+}
+
+/// Tests rebuilding a payload does not publish its preconfirmations again
+#[tokio::test]
+async fn test_payload_builder_preconfirmations_on_rebuild() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    let preconfirmations = Preconfirmations::default();
+    fixture.builder.preconfirmations = Some(preconfirmations.clone());
+    let mut subscriber = preconfirmations.subscribe();
+
+    let transactions = vec![
+        fixture.create_funded_transaction(Address::with_last_byte(1), 10),
+        fixture.create_funded_transaction(Address::with_last_byte(2), 10),
+    ];
+    let payload_attrs = fixture.create_payload_attributes(
+        transactions.clone(),
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    // The payload job rebuilds the block with the same attributes until it is resolved
+    let first = fixture.builder.build_payload(payload_attrs.clone()).await?;
+    let second = fixture.builder.build_payload(payload_attrs).await?;
+    assert_eq!(first.hash(), second.hash());
+
+    for transaction in &transactions {
+        assert_eq!(subscriber.try_recv()?.transaction_hash, *transaction.hash());
+    }
+    assert!(matches!(subscriber.try_recv(), Err(TryRecvError::Empty)));
+
+    // Sealing the block drops its preconfirmations, receipts take over
+    preconfirmations.seal_block(1);
+    for transaction in &transactions {
+        assert_eq!(preconfirmations.get(transaction.hash()), None);
+    }
+
+    println!("✓ Preconfirmation rebuild test passed");
+    Ok(())
+}
+
+/// Tests recording the attributes of built blocks along with the transactions left out
+#[tokio::test]
+async fn test_payload_builder_build_attributes() -> Result<()> {
+    let mut fixture = RollkitTestFixture::new().await?;
+    let (recorder, mut records) = BuildAttributesRecorder::channel();
+    fixture.builder.build_attributes = Some(recorder);
+
+    // The replayed transaction reuses a spent nonce and is left out
+    let transaction = fixture.create_funded_transaction(Address::with_last_byte(1), 10);
+    let payload_attrs = fixture.create_payload_attributes(
+        vec![transaction.clone(), transaction.clone()],
+        1,
+        TEST_TIMESTAMP,
+        fixture.genesis_hash,
+        Some(TEST_GAS_LIMIT),
+    );
+
+    let sealed_block = fixture.builder.build_payload(payload_attrs.clone()).await?;
+    assert_eq!(sealed_block.transaction_count(), 1);
+
+    let record = records.try_recv()?;
+    assert_eq!(record.block_number, 1);
+    assert_eq!(record.block_hash, sealed_block.hash());
+    assert_eq!(record.parent_hash, fixture.genesis_hash);
+    let raw = Bytes::from(transaction.encoded_2718());
+    assert_eq!(record.transactions, vec![raw.clone(), raw]);
+    assert_eq!(record.gas_limit, Some(TEST_GAS_LIMIT));
+    assert_eq!(record.fee_recipient, payload_attrs.suggested_fee_recipient);
+    let [excluded] = record.excluded.as_slice() else {
+        panic!(
+            "expected one excluded transaction, got {:?}",
+            record.excluded
+        );
+    };
+    assert_eq!(excluded.hash, *transaction.hash());
+    assert!(!excluded.reason.is_empty());
+
+    println!("✓ Build attributes test passed");
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Tests for replaying blocks through the payload builder and diffing their results.
+
+use crate::common::{RollkitTestFixture, TEST_GAS_LIMIT, TEST_TIMESTAMP};
+
+use alloy_consensus::transaction::SignerRecoverable;
+use alloy_primitives::{Address, B256};
+use ev_node::{attributes_from_block, compare_headers, diff_state, HeaderMismatch};
+use evolve_ev_reth::DaBlockInfo;
+use eyre::Result;
+use reth_primitives::Header;
+use std::collections::BTreeMap;
+
+#[tokio::test]
+async fn test_replay_block() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+    let transaction = fixture.create_funded_transaction(Address::with_last_byte(1), 10);
+    let sender = transaction.recover_signer()?;
+    let da_info = DaBlockInfo::new(42, B256::repeat_byte(0xda), TEST_TIMESTAMP - 6);
+    let attributes = fixture
+        .create_payload_attributes(
+            vec![transaction],
+            1,
+            TEST_TIMESTAMP + 1,
+            fixture.genesis_hash,
+            Some(TEST_GAS_LIMIT),
+        )
+        .with_da_info(da_info)
+        .with_timestamp_millis((TEST_TIMESTAMP + 1) * 1000 + 250);
+    let block = fixture.builder.build_payload(attributes.clone()).await?;
+
+    // The block implies the attributes it was built from
+    let replayed_attributes = attributes_from_block(&block);
+    assert_eq!(replayed_attributes.transactions, attributes.transactions);
+    assert_eq!(replayed_attributes.gas_limit, attributes.gas_limit);
+    assert_eq!(replayed_attributes.timestamp, attributes.timestamp);
+    assert_eq!(replayed_attributes.prev_randao, attributes.prev_randao);
+    assert_eq!(
+        replayed_attributes.suggested_fee_recipient,
+        attributes.suggested_fee_recipient
+    );
+    assert_eq!(replayed_attributes.parent_hash, fixture.genesis_hash);
+    assert_eq!(replayed_attributes.block_number, 1);
+    assert_eq!(replayed_attributes.da_info, Some(da_info));
+    assert_eq!(
+        replayed_attributes.timestamp_millis,
+        attributes.timestamp_millis
+    );
+
+    let replayed = fixture
+        .builder
+        .build_payload_with_report(replayed_attributes)
+        .await?;
+    assert!(compare_headers(block.header(), replayed.block.header()).is_empty());
+
+    let diverged = Header {
+        state_root: B256::repeat_byte(0x01),
+        gas_used: 42_000,
+        ..block.header().clone()
+    };
+    assert_eq!(
+        compare_headers(&diverged, replayed.block.header()),
+        vec![
+            HeaderMismatch::StateRoot {
+                expected: B256::repeat_byte(0x01),
+                actual: block.state_root,
+            },
+            HeaderMismatch::GasUsed {
+                expected: 42_000,
+                actual: 21_000,
+            },
+        ]
+    );
+
+    // Against a state the block was never applied to, the sender's nonce is off by one
+    let diffs = diff_state(
+        &replayed.bundle_state,
+        &fixture.provider,
+        &fixture.provider,
+        &BTreeMap::new(),
+    )?;
+    let sender_diff = diffs
+        .iter()
+        .find(|diff| diff.address == sender)
+        .expect("sender should differ");
+    assert_eq!(sender_diff.expected.map(|account| account.nonce), Some(0));
+    assert_eq!(sender_diff.actual.map(|account| account.nonce), Some(1));
+    assert!(sender_diff.storage.is_empty());
+
+    // Accounts the expected block changed are checked too, unchanged ones are not reported
+    let untouched = Address::with_last_byte(0x42);
+    let expected_changes = BTreeMap::from([(untouched, [B256::ZERO].into())]);
+    let diffs = diff_state(
+        &replayed.bundle_state,
+        &fixture.provider,
+        &fixture.provider,
+        &expected_changes,
+    )?;
+    assert!(diffs.iter().any(|diff| diff.address == sender));
+    assert!(diffs.iter().all(|diff| diff.address != untouched));
+    Ok(())
+}
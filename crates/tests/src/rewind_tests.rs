@@ -0,0 +1,220 @@
+//! End-to-end tests for forkchoice updates rewinding the chain to an ancestor of its head.
+//!
+//! The node runs in-process, driven through the engine handle the way Evolve drives it over
+//! the Engine API.
+
+use alloy_primitives::{Address, B256, U256};
+use ev_reth::RollkitEngineTypes;
+use eyre::Result;
+use reth_ethereum_primitives::TransactionSigned;
+use reth_node_api::{
+    BeaconConsensusEngineHandle, EngineApiMessageVersion, PayloadKind, PayloadTypes,
+};
+use reth_payload_builder::PayloadBuilderHandle;
+use reth_primitives::SealedBlock;
+use reth_provider::{AccountReader, BlockHashReader, BlockNumReader, StateProviderFactory};
+
+use crate::{
+    common::TEST_TIMESTAMP,
+    node::{encode_transactions, forkchoice_state, payload_attributes, transfer, TestNode},
+};
+
+/// Sends transactions and payload attributes to an in-process node's engine
+struct EngineDriver {
+    engine: BeaconConsensusEngineHandle<RollkitEngineTypes>,
+    payload_builder: PayloadBuilderHandle<RollkitEngineTypes>,
+}
+
+impl EngineDriver {
+    /// Sends a forkchoice update to `head`, returning the started payload job's block if
+    /// attributes are given
+    async fn forkchoice_updated(
+        &self,
+        head: B256,
+        transactions: Option<Vec<TransactionSigned>>,
+        timestamp: u64,
+    ) -> Result<Option<SealedBlock>> {
+        let attributes = transactions
+            .map(|transactions| payload_attributes(timestamp, encode_transactions(&transactions)));
+        let state = forkchoice_state(head);
+        let updated = self
+            .engine
+            .fork_choice_updated(state, attributes, EngineApiMessageVersion::V3)
+            .await?;
+        assert!(
+            updated.payload_status.is_valid(),
+            "forkchoice update to {head} rejected: {:?}",
+            updated.payload_status.status
+        );
+
+        let Some(payload_id) = updated.payload_id else {
+            return Ok(None);
+        };
+        let built = self
+            .payload_builder
+            .resolve_kind(payload_id, PayloadKind::WaitForPending)
+            .await
+            .expect("payload job should exist")?;
+        Ok(Some(SealedBlock::clone(built.block())))
+    }
+
+    /// Imports a block and makes it the canonical head
+    async fn import(&self, block: SealedBlock) -> Result<()> {
+        let hash = block.hash();
+        let status = self
+            .engine
+            .new_payload(RollkitEngineTypes::block_to_payload(block))
+            .await?;
+        assert!(status.is_valid(), "payload {hash} rejected: {status:?}");
+        self.forkchoice_updated(hash, None, 0).await?;
+        Ok(())
+    }
+
+    /// Builds a block with the given transactions on `parent` and makes it canonical
+    async fn build_block(
+        &self,
+        parent: B256,
+        transactions: Vec<TransactionSigned>,
+        timestamp: u64,
+    ) -> Result<SealedBlock> {
+        let block = self
+            .forkchoice_updated(parent, Some(transactions), timestamp)
+            .await?
+            .expect("attributes should start a payload job");
+        self.import(block.clone()).await?;
+        Ok(block)
+    }
+}
+
+/// A forkchoice update with attributes on an ancestor builds on the ancestor's state, and
+/// importing the new block discards the blocks it replaces
+#[tokio::test]
+async fn test_forkchoice_rewind_builds_on_ancestor() -> Result<()> {
+    let test_node = TestNode::launch().await?;
+    let node = &test_node.node;
+    let signer = test_node.signer(0);
+    let sender = signer.address();
+    let genesis_hash = test_node.genesis_hash();
+    let driver = EngineDriver {
+        engine: node.add_ons_handle.beacon_engine_handle.clone(),
+        payload_builder: node.payload_builder_handle.clone(),
+    };
+
+    let first = Address::with_last_byte(1);
+    let mut parent = genesis_hash;
+    let mut blocks = Vec::new();
+    for nonce in 0..3 {
+        let block = driver
+            .build_block(
+                parent,
+                vec![transfer(&signer, nonce, first, U256::from(1))?],
+                TEST_TIMESTAMP + 1 + nonce,
+            )
+            .await?;
+        parent = block.hash();
+        blocks.push(block);
+    }
+    assert_eq!(node.provider.best_block_number()?, 3);
+
+    // Evolve rewinds to block 1 after a failed DA submission and sends new attributes. Built
+    // on the latest state, the sender's nonce would be 3 and the transaction left out.
+    let second = Address::with_last_byte(2);
+    let rebuilt = driver
+        .forkchoice_updated(
+            blocks[0].hash(),
+            Some(vec![transfer(&signer, 1, second, U256::from(1))?]),
+            TEST_TIMESTAMP + 5,
+        )
+        .await?
+        .expect("attributes should start a payload job");
+    assert_eq!(rebuilt.number, 2);
+    assert_eq!(rebuilt.parent_hash, blocks[0].hash());
+    assert_eq!(rebuilt.body().transactions.len(), 1);
+    assert_ne!(rebuilt.state_root, blocks[1].state_root);
+
+    driver.import(rebuilt.clone()).await?;
+    assert_eq!(node.provider.best_block_number()?, 2);
+    assert_eq!(node.provider.block_hash(2)?, Some(rebuilt.hash()));
+    assert_eq!(node.provider.block_hash(3)?, None);
+
+    let state = node.provider.latest()?;
+    assert_eq!(
+        state.basic_account(&sender)?.map(|account| account.nonce),
+        Some(2)
+    );
+    assert_eq!(
+        state.basic_account(&second)?.map(|account| account.balance),
+        Some(U256::from(1))
+    );
+    assert_eq!(
+        state.basic_account(&first)?.map(|account| account.balance),
+        Some(U256::from(1))
+    );
+
+    // The chain continues from the new head
+    let next = driver
+        .build_block(
+            rebuilt.hash(),
+            vec![transfer(&signer, 2, first, U256::from(1))?],
+            TEST_TIMESTAMP + 6,
+        )
+        .await?;
+    assert_eq!(next.number, 3);
+    assert_eq!(node.provider.best_block_number()?, 3);
+    Ok(())
+}
+
+/// A forkchoice update without attributes to an ancestor makes the ancestor the canonical
+/// head, with its state
+#[tokio::test]
+async fn test_forkchoice_rewind_unwinds_head() -> Result<()> {
+    let test_node = TestNode::launch().await?;
+    let node = &test_node.node;
+    let signer = test_node.signer(0);
+    let sender = signer.address();
+    let genesis_hash = test_node.genesis_hash();
+    let driver = EngineDriver {
+        engine: node.add_ons_handle.beacon_engine_handle.clone(),
+        payload_builder: node.payload_builder_handle.clone(),
+    };
+
+    let to = Address::with_last_byte(1);
+    let mut parent = genesis_hash;
+    let mut blocks = Vec::new();
+    for nonce in 0..3 {
+        let block = driver
+            .build_block(
+                parent,
+                vec![transfer(&signer, nonce, to, U256::from(1))?],
+                TEST_TIMESTAMP + 1 + nonce,
+            )
+            .await?;
+        parent = block.hash();
+        blocks.push(block);
+    }
+
+    assert!(driver
+        .forkchoice_updated(blocks[0].hash(), None, 0)
+        .await?
+        .is_none());
+    assert_eq!(node.provider.best_block_number()?, 1);
+    assert_eq!(node.provider.block_hash(2)?, None);
+    let state = node.provider.latest()?;
+    assert_eq!(
+        state.basic_account(&sender)?.map(|account| account.nonce),
+        Some(1)
+    );
+
+    // Block 2 is rebuilt from the same transaction on the rewound head
+    let rebuilt = driver
+        .build_block(
+            blocks[0].hash(),
+            vec![transfer(&signer, 1, to, U256::from(1))?],
+            TEST_TIMESTAMP + 2,
+        )
+        .await?;
+    assert_eq!(rebuilt.number, 2);
+    assert_eq!(rebuilt.state_root, blocks[1].state_root);
+    assert_eq!(node.provider.best_block_number()?, 2);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! End-to-end tests for `ev-reth rollback`, run on the datadir of a stopped in-process node.
+
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::{Address, U256};
+use clap::Parser;
+use ev_reth::commands::rollback::RollbackCommand;
+use eyre::Result;
+
+use crate::node::{encode_transactions, transfer, TestNode};
+
+/// After rolling a stopped node back, its datadir reopens at the target height, with the
+/// target's state, and the node builds on it again
+#[tokio::test]
+async fn test_rollback_command_reopens_datadir() -> Result<()> {
+    let node = TestNode::launch().await?;
+    let signer = node.signer(0);
+    let to = Address::with_last_byte(1);
+    let mut blocks = Vec::new();
+    for nonce in 0..3 {
+        let tx = transfer(&signer, nonce, to, U256::from(1))?;
+        blocks.push(node.build_block(encode_transactions([&tx])).await?);
+    }
+    assert_eq!(node.block_number().await?, 3);
+
+    let node = node
+        .restart_after(|datadir| {
+            RollbackCommand::try_parse_from([
+                "rollback",
+                "--chain",
+                datadir.join("genesis.json").to_str().unwrap(),
+                "--datadir",
+                datadir.to_str().unwrap(),
+                "--to",
+                "1",
+            ])?
+            .execute()
+        })
+        .await?;
+
+    assert_eq!(node.block_number().await?, 1);
+    assert!(node
+        .block_by_number(BlockNumberOrTag::Number(2), false)
+        .await?
+        .is_none());
+    assert_eq!(node.nonce(signer.address()).await?, 1);
+    assert_eq!(node.balance(to).await?, U256::from(1));
+
+    // The static files were unwound along with the database, so block 2 is written again
+    let tx = transfer(&signer, 1, to, U256::from(1))?;
+    let rebuilt = node.build_block(encode_transactions([&tx])).await?;
+    let block = &rebuilt.payload_inner.payload_inner;
+    assert_eq!(block.block_number, 2);
+    assert_eq!(
+        block.parent_hash,
+        blocks[0].payload_inner.payload_inner.block_hash
+    );
+    assert_eq!(node.block_number().await?, 2);
+    Ok(())
+}
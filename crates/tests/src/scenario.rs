@@ -0,0 +1,746 @@
+//! Conformance scenarios replaying ev-node's block lifecycle against an in-process node.
+//!
+//! A [`Scenario`] is a YAML or JSON list of steps: the execution API calls ev-node makes
+//! (`InitChain`, `GetTxs`, `ExecuteTxs`, `SetFinal`), the events around them (transactions
+//! reaching the pool, node restarts, a full node syncing the chain) and assertions on heads,
+//! receipts, state roots, accounts and txpool contents. Running a scenario returns a
+//! [`ScenarioVector`] recording every call with its concrete inputs and outputs, which the
+//! Go implementation can replay to check it produces the same blocks.
+
+use alloy_eips::{eip2718::Encodable2718, BlockNumberOrTag};
+use alloy_genesis::Genesis;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use evolve_ev_reth::EvolveExtraData;
+use eyre::{Result, WrapErr};
+use reth_ethereum_primitives::TransactionSigned;
+use reth_provider::HeaderProvider;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::node::{transfer, TestExecutionService, TestNode, TestNodeBuilder};
+
+/// Block time ev-node produces blocks at when a step does not give a timestamp
+pub const DEFAULT_BLOCK_TIME_MILLIS: u64 = 1_000;
+
+/// Time the txpool is given to reflect imported blocks
+const TXPOOL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the directory holding the scenarios of this crate
+pub fn scenarios_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios")
+}
+
+/// Conformance scenario, as written in a YAML or JSON test case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Scenario {
+    /// Name of the scenario, also the file name of its vector
+    pub name: String,
+    /// What the scenario checks
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Evolve parameters of the genesis, stored under `config.evolve`
+    #[serde(default)]
+    pub evolve: Option<serde_json::Value>,
+    /// Beneficiary of the blocks ev-node executes
+    #[serde(default)]
+    pub fee_recipient: Address,
+    /// Steps played in order
+    pub steps: Vec<Step>,
+}
+
+/// Step of a scenario
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Step {
+    /// `InitChain`, checking the genesis state root and gas limit are returned
+    InitChain {
+        /// Initial height ev-node starts at
+        #[serde(default = "default_initial_height")]
+        initial_height: u64,
+    },
+    /// A transfer reaching the txpool over `eth_sendRawTransaction`
+    SubmitTx {
+        /// Name later steps refer to the transaction by
+        id: String,
+        /// Index of the prefunded sender
+        from: u32,
+        /// Recipient
+        to: AccountRef,
+        /// Value in wei
+        #[serde(default)]
+        value: u128,
+        /// Nonce, the sender's next one by default
+        #[serde(default)]
+        nonce: Option<u64>,
+    },
+    /// `GetTxs`, whose result is what the next `executeTxs` executes by default
+    GetTxs {
+        /// Transactions expected, in order
+        #[serde(default)]
+        expect: Option<Vec<String>>,
+    },
+    /// `ExecuteTxs`
+    ExecuteTxs {
+        /// Height of the block, one above the head by default
+        #[serde(default)]
+        height: Option<u64>,
+        /// Timestamp of the block in milliseconds, a block time after its parent by default
+        #[serde(default)]
+        timestamp_millis: Option<u64>,
+        /// Transactions to execute, those of the last `getTxs` by default
+        #[serde(default)]
+        txs: Option<Vec<String>>,
+        /// Expected outcome
+        #[serde(default)]
+        expect: ExecuteExpect,
+    },
+    /// `SetFinal`
+    SetFinal {
+        /// Height of the block ev-node finalizes
+        height: u64,
+    },
+    /// Node restart on the same datadir, checking the heads survive it
+    Restart,
+    /// A new node syncing the chain from genesis the way an ev-node full node does, by
+    /// executing every block's transactions, and checking it produces the same blocks
+    Sync,
+    /// Assertions on the head blocks
+    AssertHeads {
+        /// Expected latest block number
+        #[serde(default)]
+        latest: Option<u64>,
+        /// Expected safe block number
+        #[serde(default)]
+        safe: Option<u64>,
+        /// Expected finalized block number
+        #[serde(default)]
+        finalized: Option<u64>,
+    },
+    /// Assertion on the transactions `txpoolExt_getTxs` serves, in order
+    AssertTxpool {
+        /// Expected transactions
+        txs: Vec<String>,
+    },
+    /// Assertions on an account in the latest state
+    AssertAccount {
+        /// Account
+        account: AccountRef,
+        /// Expected balance in wei
+        #[serde(default)]
+        balance: Option<u128>,
+        /// Expected nonce
+        #[serde(default)]
+        nonce: Option<u64>,
+    },
+}
+
+const fn default_initial_height() -> u64 {
+    1
+}
+
+impl Step {
+    /// Returns the name of the step's operation
+    pub const fn op(&self) -> &'static str {
+        match self {
+            Self::InitChain { .. } => "initChain",
+            Self::SubmitTx { .. } => "submitTx",
+            Self::GetTxs { .. } => "getTxs",
+            Self::ExecuteTxs { .. } => "executeTxs",
+            Self::SetFinal { .. } => "setFinal",
+            Self::Restart => "restart",
+            Self::Sync => "sync",
+            Self::AssertHeads { .. } => "assertHeads",
+            Self::AssertTxpool { .. } => "assertTxpool",
+            Self::AssertAccount { .. } => "assertAccount",
+        }
+    }
+}
+
+/// Account referred to by the index of a prefunded account or by address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AccountRef {
+    /// Prefunded account derived from the test mnemonic
+    Index(u32),
+    /// Any account
+    Address(Address),
+}
+
+/// Expected outcome of an `executeTxs` step
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExecuteExpect {
+    /// Transactions the block includes, in order
+    #[serde(default)]
+    pub included: Option<Vec<String>>,
+    /// Included transactions that revert, all others must succeed
+    #[serde(default)]
+    pub reverted: Vec<String>,
+    /// Gas used by the block
+    #[serde(default)]
+    pub gas_used: Option<u64>,
+    /// State root after the block
+    #[serde(default)]
+    pub state_root: Option<B256>,
+    /// Hash of the block
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+}
+
+/// Execution API calls of a scenario run with their inputs and outputs, for the Go
+/// implementation to replay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioVector {
+    /// Name of the scenario
+    pub name: String,
+    /// Genesis the chain starts from
+    pub genesis: Genesis,
+    /// Beneficiary of the executed blocks
+    pub fee_recipient: Address,
+    /// Calls in the order they were made
+    pub steps: Vec<VectorStep>,
+}
+
+/// Call recorded in a [`ScenarioVector`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum VectorStep {
+    /// `InitChain` and its response
+    InitChain {
+        /// Initial height
+        initial_height: u64,
+        /// Genesis state root
+        state_root: B256,
+        /// Genesis gas limit, returned as `max_bytes`
+        max_bytes: u64,
+    },
+    /// Transaction submitted to the txpool
+    SubmitTx {
+        /// EIP-2718 encoded transaction
+        tx: Bytes,
+    },
+    /// `GetTxs` and its response
+    GetTxs {
+        /// EIP-2718 encoded transactions returned
+        txs: Vec<Bytes>,
+    },
+    /// `ExecuteTxs` and the block it produced
+    ExecuteTxs {
+        /// Height of the block
+        height: u64,
+        /// Timestamp of the block in milliseconds
+        timestamp_millis: u64,
+        /// State root of the parent
+        prev_state_root: B256,
+        /// EIP-2718 encoded transactions executed
+        txs: Vec<Bytes>,
+        /// Hash of the block
+        block_hash: B256,
+        /// State root after the block
+        state_root: B256,
+        /// Gas used by the block
+        gas_used: u64,
+        /// Receipts of the included transactions
+        receipts: Vec<VectorReceipt>,
+    },
+    /// `SetFinal`
+    SetFinal {
+        /// Finalized height
+        height: u64,
+    },
+    /// Node restart
+    Restart,
+}
+
+/// Receipt of a transaction included by an `ExecuteTxs` call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorReceipt {
+    /// Hash of the transaction
+    pub tx_hash: B256,
+    /// Whether the transaction succeeded
+    pub status: bool,
+    /// Gas used by the transaction
+    pub gas_used: u64,
+}
+
+impl Scenario {
+    /// Reads a scenario from a `.json` file, or from a YAML file otherwise
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let scenario = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&contents)?
+        } else {
+            serde_yaml::from_str(&contents)?
+        };
+        Ok(scenario)
+    }
+
+    /// Reads the scenarios of a directory, sorted by file name
+    pub fn from_dir(dir: &Path) -> Result<Vec<(PathBuf, Self)>> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        paths.retain(|path| {
+            path.extension().is_some_and(|extension| {
+                extension == "yaml" || extension == "yml" || extension == "json"
+            })
+        });
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let scenario =
+                    Self::from_file(&path).wrap_err_with(|| format!("{}", path.display()))?;
+                Ok((path, scenario))
+            })
+            .collect()
+    }
+
+    /// Returns the builder of the nodes the scenario runs on
+    fn node_builder(&self) -> TestNodeBuilder {
+        let builder = TestNode::builder();
+        match &self.evolve {
+            Some(evolve) => builder.with_evolve_config(evolve.clone()),
+            None => builder,
+        }
+    }
+
+    /// Plays the scenario on a new node, failing at the first step whose assertions do not
+    /// hold
+    pub async fn run(&self) -> Result<ScenarioVector> {
+        let node = self.node_builder().launch().await?;
+        let service = node.execution_service(self.fee_recipient);
+        let mut runner = ScenarioRunner {
+            vector: ScenarioVector {
+                name: self.name.clone(),
+                genesis: node.chain_spec().genesis().clone(),
+                fee_recipient: self.fee_recipient,
+                steps: Vec::new(),
+            },
+            scenario: self,
+            node: Some(node),
+            service,
+            txs: HashMap::new(),
+            nonces: HashMap::new(),
+            fetched: Vec::new(),
+            blocks: BTreeMap::new(),
+        };
+        for (index, step) in self.steps.iter().enumerate() {
+            runner
+                .step(step)
+                .await
+                .wrap_err_with(|| format!("{}: step {} ({})", self.name, index + 1, step.op()))?;
+        }
+        Ok(runner.vector)
+    }
+}
+
+/// Block executed by a scenario, replayed when syncing
+#[derive(Debug, Clone)]
+struct ExecutedTxs {
+    timestamp_millis: u64,
+    prev_state_root: B256,
+    txs: Vec<Bytes>,
+    block_hash: B256,
+    state_root: B256,
+}
+
+/// State of a scenario being played
+#[derive(Debug)]
+struct ScenarioRunner<'a> {
+    scenario: &'a Scenario,
+    /// Node, only missing while it restarts
+    node: Option<TestNode>,
+    service: TestExecutionService,
+    /// Submitted transactions by id
+    txs: HashMap<String, TransactionSigned>,
+    /// Next nonce of the prefunded accounts that sent transactions
+    nonces: HashMap<u32, u64>,
+    /// Transactions returned by the last `getTxs`
+    fetched: Vec<Bytes>,
+    /// Executed blocks by height
+    blocks: BTreeMap<u64, ExecutedTxs>,
+    vector: ScenarioVector,
+}
+
+impl ScenarioRunner<'_> {
+    fn node(&self) -> &TestNode {
+        self.node.as_ref().expect("node is running between steps")
+    }
+
+    /// Returns the submitted transaction named `id`
+    fn tx(&self, id: &str) -> Result<&TransactionSigned> {
+        self.txs
+            .get(id)
+            .ok_or_else(|| eyre::eyre!("unknown transaction {id}"))
+    }
+
+    /// Returns the encoding of the submitted transactions named by `ids`
+    fn encoded(&self, ids: &[String]) -> Result<Vec<Bytes>> {
+        ids.iter()
+            .map(|id| Ok(self.tx(id)?.encoded_2718().into()))
+            .collect()
+    }
+
+    fn address(&self, account: AccountRef) -> Address {
+        match account {
+            AccountRef::Index(index) => self.node().signer(index).address(),
+            AccountRef::Address(address) => address,
+        }
+    }
+
+    async fn step(&mut self, step: &Step) -> Result<()> {
+        match step {
+            Step::InitChain { initial_height } => {
+                let chain_spec = self.node().chain_spec();
+                let genesis = chain_spec.genesis_header().clone();
+                let (state_root, max_bytes) = self.service.init_chain(
+                    *initial_height,
+                    &chain_spec.chain().id().to_string(),
+                    Some(genesis.timestamp),
+                )?;
+                eyre::ensure!(
+                    state_root == genesis.state_root,
+                    "state root {state_root} is not the genesis state root {}",
+                    genesis.state_root
+                );
+                eyre::ensure!(
+                    max_bytes == genesis.gas_limit,
+                    "max bytes {max_bytes} is not the genesis gas limit {}",
+                    genesis.gas_limit
+                );
+                self.vector.steps.push(VectorStep::InitChain {
+                    initial_height: *initial_height,
+                    state_root,
+                    max_bytes,
+                });
+            }
+            Step::SubmitTx {
+                id,
+                from,
+                to,
+                value,
+                nonce,
+            } => {
+                eyre::ensure!(!self.txs.contains_key(id), "duplicate transaction {id}");
+                let signer = self.node().signer(*from);
+                let nonce = match nonce {
+                    Some(nonce) => *nonce,
+                    None => match self.nonces.get(from) {
+                        Some(nonce) => *nonce,
+                        None => self.node().nonce(signer.address()).await?,
+                    },
+                };
+                let tx = transfer(&signer, nonce, self.address(*to), U256::from(*value))?;
+                let encoded: Bytes = tx.encoded_2718().into();
+                let hash = self.node().send_raw_transaction(encoded.clone()).await?;
+                eyre::ensure!(hash == *tx.tx_hash(), "txpool returned hash {hash}");
+                self.nonces.insert(*from, nonce + 1);
+                self.txs.insert(id.clone(), tx);
+                self.vector.steps.push(VectorStep::SubmitTx { tx: encoded });
+            }
+            Step::GetTxs { expect } => {
+                let expected = expect.as_deref().map(|ids| self.encoded(ids)).transpose()?;
+                // The pool drops included transactions shortly after the block is imported
+                let service = &self.service;
+                let txs = eventually(
+                    || async { Ok(service.get_txs()) },
+                    |txs| expected.as_ref().is_none_or(|expected| txs == expected),
+                )
+                .await?;
+                if let Some(expected) = expected {
+                    eyre::ensure!(txs == expected, "got {} transactions: {txs:?}", txs.len());
+                }
+                self.fetched = txs.clone();
+                self.vector.steps.push(VectorStep::GetTxs { txs });
+            }
+            Step::ExecuteTxs {
+                height,
+                timestamp_millis,
+                txs,
+                expect,
+            } => {
+                self.execute_txs(*height, *timestamp_millis, txs.as_deref(), expect)
+                    .await?
+            }
+            Step::SetFinal { height } => {
+                self.service.set_final(*height).await?;
+                self.vector
+                    .steps
+                    .push(VectorStep::SetFinal { height: *height });
+            }
+            Step::Restart => {
+                let node = self.node.take().expect("node is running between steps");
+                let before = heads(&node).await;
+                let node = node.restart().await?;
+                let after = heads(&node).await;
+                eyre::ensure!(
+                    before == after,
+                    "heads changed over the restart from {before:?} to {after:?}"
+                );
+                self.service = node.execution_service(self.scenario.fee_recipient);
+                self.node = Some(node);
+                self.vector.steps.push(VectorStep::Restart);
+            }
+            Step::Sync => self.sync().await?,
+            Step::AssertHeads {
+                latest,
+                safe,
+                finalized,
+            } => {
+                for (tag, expected) in [
+                    (BlockNumberOrTag::Latest, latest),
+                    (BlockNumberOrTag::Safe, safe),
+                    (BlockNumberOrTag::Finalized, finalized),
+                ] {
+                    let Some(expected) = expected else { continue };
+                    let block = self
+                        .node()
+                        .block_by_number(tag, false)
+                        .await?
+                        .ok_or_else(|| eyre::eyre!("no {tag} block"))?;
+                    eyre::ensure!(
+                        block.header.number == *expected,
+                        "{tag} block is {}, expected {expected}",
+                        block.header.number
+                    );
+                }
+            }
+            Step::AssertTxpool { txs } => {
+                let expected = self.encoded(txs)?;
+                let node = self.node();
+                let actual = eventually(|| node.txpool_get_txs(), |txs| *txs == expected).await?;
+                eyre::ensure!(
+                    actual == expected,
+                    "txpool serves {} transactions: {actual:?}",
+                    actual.len()
+                );
+            }
+            Step::AssertAccount {
+                account,
+                balance,
+                nonce,
+            } => {
+                let address = self.address(*account);
+                if let Some(expected) = balance {
+                    let actual = self.node().balance(address).await?;
+                    eyre::ensure!(
+                        actual == U256::from(*expected),
+                        "balance of {address} is {actual}, expected {expected}"
+                    );
+                }
+                if let Some(expected) = nonce {
+                    let actual = self.node().nonce(address).await?;
+                    eyre::ensure!(
+                        actual == *expected,
+                        "nonce of {address} is {actual}, expected {expected}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute_txs(
+        &mut self,
+        height: Option<u64>,
+        timestamp_millis: Option<u64>,
+        ids: Option<&[String]>,
+        expect: &ExecuteExpect,
+    ) -> Result<()> {
+        let node = self.node();
+        let height = match height {
+            Some(height) => height,
+            None => node.block_number().await? + 1,
+        };
+        let parent = node
+            .node
+            .provider
+            .sealed_header(height - 1)?
+            .ok_or_else(|| eyre::eyre!("parent of block {height} is unknown"))?;
+        let timestamp_millis = timestamp_millis.unwrap_or_else(|| {
+            EvolveExtraData::timestamp_millis_of(parent.header()) + DEFAULT_BLOCK_TIME_MILLIS
+        });
+        let txs = match ids {
+            Some(ids) => self.encoded(ids)?,
+            // ev-node fetches transactions anew for every block
+            None => std::mem::take(&mut self.fetched),
+        };
+
+        let executed = self
+            .service
+            .execute_txs(txs.clone(), height, timestamp_millis, parent.state_root)
+            .await?;
+
+        let block = node
+            .block_by_number(BlockNumberOrTag::Number(height), false)
+            .await?
+            .ok_or_else(|| eyre::eyre!("block {height} is not served over RPC"))?;
+        eyre::ensure!(
+            block.header.hash == executed.hash && block.header.state_root == executed.state_root,
+            "block {height} served over RPC differs from the executed one"
+        );
+        if let Some(expected) = expect.state_root {
+            eyre::ensure!(
+                executed.state_root == expected,
+                "state root is {}, expected {expected}",
+                executed.state_root
+            );
+        }
+        if let Some(expected) = expect.block_hash {
+            eyre::ensure!(
+                executed.hash == expected,
+                "block hash is {}, expected {expected}",
+                executed.hash
+            );
+        }
+        if let Some(expected) = expect.gas_used {
+            eyre::ensure!(
+                executed.gas_used == expected,
+                "gas used is {}, expected {expected}",
+                executed.gas_used
+            );
+        }
+
+        let hashes: Vec<B256> = block.transactions.hashes().collect();
+        if let Some(included) = &expect.included {
+            let expected = included
+                .iter()
+                .map(|id| Ok(*self.tx(id)?.tx_hash()))
+                .collect::<Result<Vec<_>>>()?;
+            eyre::ensure!(
+                hashes == expected,
+                "block includes {hashes:?}, expected {expected:?}"
+            );
+        }
+        let reverted = expect
+            .reverted
+            .iter()
+            .map(|id| Ok(*self.tx(id)?.tx_hash()))
+            .collect::<Result<Vec<_>>>()?;
+        let mut receipts = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let receipt = node
+                .transaction_receipt(hash)
+                .await?
+                .ok_or_else(|| eyre::eyre!("no receipt for {hash}"))?;
+            eyre::ensure!(
+                receipt.block_number == Some(height),
+                "receipt of {hash} is not in block {height}"
+            );
+            eyre::ensure!(
+                receipt.status() != reverted.contains(&hash),
+                "transaction {hash} {}",
+                if receipt.status() {
+                    "succeeded"
+                } else {
+                    "reverted"
+                }
+            );
+            receipts.push(VectorReceipt {
+                tx_hash: hash,
+                status: receipt.status(),
+                gas_used: receipt.gas_used,
+            });
+        }
+
+        self.blocks.insert(
+            height,
+            ExecutedTxs {
+                timestamp_millis,
+                prev_state_root: parent.state_root,
+                txs: txs.clone(),
+                block_hash: executed.hash,
+                state_root: executed.state_root,
+            },
+        );
+        self.vector.steps.push(VectorStep::ExecuteTxs {
+            height,
+            timestamp_millis,
+            prev_state_root: parent.state_root,
+            txs,
+            block_hash: executed.hash,
+            state_root: executed.state_root,
+            gas_used: executed.gas_used,
+            receipts,
+        });
+        Ok(())
+    }
+
+    /// Syncs a new node by executing the blocks of the chain and compares the result
+    async fn sync(&self) -> Result<()> {
+        let follower = self.scenario.node_builder().launch().await?;
+        let service = follower.execution_service(self.scenario.fee_recipient);
+        service.init_chain(
+            default_initial_height(),
+            &follower.chain_spec().chain().id().to_string(),
+            None,
+        )?;
+        for (height, block) in &self.blocks {
+            let executed = service
+                .execute_txs(
+                    block.txs.clone(),
+                    *height,
+                    block.timestamp_millis,
+                    block.prev_state_root,
+                )
+                .await
+                .wrap_err_with(|| format!("syncing block {height}"))?;
+            eyre::ensure!(
+                executed.hash == block.block_hash && executed.state_root == block.state_root,
+                "synced block {height} is {} with state root {}, expected {} with state root {}",
+                executed.hash,
+                executed.state_root,
+                block.block_hash,
+                block.state_root
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Returns the hashes of the latest, safe and finalized blocks
+async fn heads(node: &TestNode) -> [Option<B256>; 3] {
+    let mut heads = [None; 3];
+    for (head, tag) in heads.iter_mut().zip([
+        BlockNumberOrTag::Latest,
+        BlockNumberOrTag::Safe,
+        BlockNumberOrTag::Finalized,
+    ]) {
+        // Nodes answer with an error rather than `null` before a safe block is set
+        *head = node
+            .block_by_number(tag, false)
+            .await
+            .ok()
+            .flatten()
+            .map(|block| block.header.hash);
+    }
+    heads
+}
+
+/// Polls `poll` until `done` holds or the txpool timeout elapses, returning the last value
+async fn eventually<T, F, Fut>(mut poll: F, done: impl Fn(&T) -> bool) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let deadline = tokio::time::Instant::now() + TXPOOL_TIMEOUT;
+    loop {
+        let value = poll().await?;
+        if done(&value) || tokio::time::Instant::now() >= deadline {
+            return Ok(value);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
@@ -0,0 +1,84 @@
+//! Runs the conformance scenarios of `crates/tests/scenarios`.
+//!
+//! Set `EV_RETH_SCENARIO_VECTORS` to a directory to write the vector of every scenario there.
+
+use eyre::{Result, WrapErr};
+use std::{env, fs, path::PathBuf};
+
+use crate::scenario::{scenarios_dir, AccountRef, Scenario, Step};
+
+/// Directory the scenario vectors are written to, if set
+const VECTORS_DIR_ENV: &str = "EV_RETH_SCENARIO_VECTORS";
+
+#[tokio::test]
+async fn test_scenarios() -> Result<()> {
+    let scenarios = Scenario::from_dir(&scenarios_dir())?;
+    assert!(!scenarios.is_empty(), "no scenarios found");
+
+    let vectors_dir = env::var_os(VECTORS_DIR_ENV).map(PathBuf::from);
+    if let Some(dir) = &vectors_dir {
+        fs::create_dir_all(dir)?;
+    }
+    for (path, scenario) in scenarios {
+        let vector = scenario
+            .run()
+            .await
+            .wrap_err_with(|| format!("{}", path.display()))?;
+        if let Some(dir) = &vectors_dir {
+            fs::write(
+                dir.join(format!("{}.json", scenario.name)),
+                serde_json::to_string_pretty(&vector)?,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_scenario_formats() -> Result<()> {
+    let yaml: Scenario = serde_yaml::from_str(
+        r#"
+name: formats
+steps:
+  - op: initChain
+  - op: submitTx
+    id: tx
+    from: 0
+    to: "0x00000000000000000000000000000000000000c0"
+  - op: executeTxs
+    timestampMillis: 1000
+    txs: [tx]
+  - op: restart
+"#,
+    )?;
+    let json: Scenario = serde_json::from_str(
+        r#"{
+            "name": "formats",
+            "steps": [
+                { "op": "initChain", "initialHeight": 1 },
+                {
+                    "op": "submitTx",
+                    "id": "tx",
+                    "from": 0,
+                    "to": "0x00000000000000000000000000000000000000c0",
+                    "value": 0
+                },
+                { "op": "executeTxs", "timestampMillis": 1000, "txs": ["tx"] },
+                { "op": "restart" }
+            ]
+        }"#,
+    )?;
+    assert_eq!(yaml, json);
+    assert!(matches!(
+        yaml.steps[1],
+        Step::SubmitTx {
+            to: AccountRef::Address(_),
+            nonce: None,
+            ..
+        }
+    ));
+
+    // Unknown fields are mistakes in the test case, not options to ignore
+    assert!(serde_yaml::from_str::<Scenario>("name: typo\nstep: []\n").is_err());
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Tests for the `evolve_simulateBatch` RPC dry-running batches through the payload builder.
+
+use crate::common::{RollkitTestFixture, TEST_GAS_LIMIT, TEST_TIMESTAMP};
+
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{Address, B256};
+use ev_node::{
+    rpc::{EvolveSimulateApiServer, SimulatedTransactionStatus},
+    EvolveSimulateApiImpl, RollkitPayloadBuilder, SimulateBatchRequest,
+};
+use evolve_ev_reth::{DaBlockInfo, EvolveExtraData};
+use eyre::Result;
+use jsonrpsee::types::error::INVALID_PARAMS_CODE;
+use reth_provider::test_utils::MockEthProvider;
+use std::sync::Arc;
+
+fn simulate_api(fixture: &RollkitTestFixture) -> EvolveSimulateApiImpl<MockEthProvider> {
+    let builder = RollkitPayloadBuilder::new(
+        Arc::new(fixture.provider.clone()),
+        fixture.builder.evm_config.clone(),
+    )
+    .with_config(fixture.builder.config);
+    EvolveSimulateApiImpl::new(Arc::new(builder))
+}
+
+#[tokio::test]
+async fn test_simulate_batch() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+    let api = simulate_api(&fixture);
+
+    let transaction = fixture.create_funded_transaction(Address::with_last_byte(1), 10);
+    let request = SimulateBatchRequest {
+        // The replayed transaction reuses a spent nonce
+        transactions: vec![
+            transaction.encoded_2718().into(),
+            transaction.encoded_2718().into(),
+        ],
+        parent_hash: fixture.genesis_hash,
+        timestamp: TEST_TIMESTAMP + 1,
+        timestamp_millis: None,
+        gas_limit: Some(TEST_GAS_LIMIT),
+        fee_recipient: Address::ZERO,
+        da_info: None,
+    };
+
+    let simulated = api.simulate_batch(request.clone()).await?;
+    assert_eq!(simulated.header.number, 1);
+    assert_eq!(simulated.header.parent_hash, fixture.genesis_hash);
+    assert_eq!(simulated.header.gas_used, 21_000);
+    assert_eq!(simulated.block_hash, simulated.header.hash_slow());
+
+    let [first, second] = simulated.transactions.as_slice() else {
+        panic!("expected two outcomes, got {:?}", simulated.transactions);
+    };
+    assert_eq!(first.hash, *transaction.hash());
+    assert_eq!(first.status, SimulatedTransactionStatus::Success);
+    assert_eq!(first.gas_used, 21_000);
+    assert_eq!(second.status, SimulatedTransactionStatus::Invalid);
+    assert_eq!(second.gas_used, 0);
+    assert!(second.error.is_some());
+
+    // Nothing was inserted, so the batch simulates the same way again
+    assert_eq!(api.simulate_batch(request.clone()).await?, simulated);
+
+    let unknown_parent = SimulateBatchRequest {
+        parent_hash: B256::repeat_byte(0x42),
+        ..request
+    };
+    assert!(api.simulate_batch(unknown_parent).await.is_err());
+    Ok(())
+}
+
+/// The gas limit defaults to the parent's and may not exceed it
+#[tokio::test]
+async fn test_simulate_batch_gas_limit() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+    let api = simulate_api(&fixture);
+    let request = SimulateBatchRequest {
+        transactions: Vec::new(),
+        parent_hash: fixture.genesis_hash,
+        timestamp: TEST_TIMESTAMP + 1,
+        timestamp_millis: None,
+        gas_limit: None,
+        fee_recipient: Address::ZERO,
+        da_info: None,
+    };
+
+    let simulated = api.simulate_batch(request.clone()).await?;
+    assert_eq!(simulated.header.gas_limit, TEST_GAS_LIMIT);
+
+    let lower = SimulateBatchRequest {
+        gas_limit: Some(TEST_GAS_LIMIT - 1),
+        ..request.clone()
+    };
+    assert_eq!(
+        api.simulate_batch(lower).await?.header.gas_limit,
+        TEST_GAS_LIMIT - 1
+    );
+
+    for gas_limit in [TEST_GAS_LIMIT + 1, u64::MAX] {
+        let err = api
+            .simulate_batch(SimulateBatchRequest {
+                gas_limit: Some(gas_limit),
+                ..request.clone()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+    }
+    Ok(())
+}
+
+/// DA block info and the millisecond timestamp are applied to the simulated block as they
+/// would be to a built one
+#[tokio::test]
+async fn test_simulate_batch_with_da_info_and_timestamp_millis() -> Result<()> {
+    let fixture = RollkitTestFixture::new().await?;
+    let api = simulate_api(&fixture);
+    let da_info = DaBlockInfo::new(7, B256::repeat_byte(0x07), TEST_TIMESTAMP);
+    let timestamp_millis = (TEST_TIMESTAMP + 1) * 1000 + 250;
+    let request = SimulateBatchRequest {
+        transactions: Vec::new(),
+        parent_hash: fixture.genesis_hash,
+        timestamp: TEST_TIMESTAMP + 1,
+        timestamp_millis: Some(timestamp_millis),
+        gas_limit: None,
+        fee_recipient: Address::ZERO,
+        da_info: Some(da_info),
+    };
+
+    let simulated = api.simulate_batch(request.clone()).await?;
+    let extension = EvolveExtraData::decode(&simulated.header.extra_data)
+        .expect("header should carry the Evolve extension");
+    assert_eq!(extension.da_info, Some(da_info));
+    assert_eq!(extension.timestamp_millis, Some(timestamp_millis));
+
+    // A millisecond timestamp outside of the block's second is rejected
+    let err = api
+        .simulate_batch(SimulateBatchRequest {
+            timestamp_millis: Some(timestamp_millis + 1000),
+            ..request
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), INVALID_PARAMS_CODE);
+    Ok(())
+}
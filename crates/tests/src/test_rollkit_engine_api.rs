@@ -1,363 +0,0 @@
-//! Engine API integration tests for Rollkit.
-//!
-//! This test suite tests the Engine API integration by:
-//! 1. Attempting to connect to a real Rollkit node
-//! 2. Falling back to mock mode if no node is available
-//! 3. Testing `engine_forkchoiceUpdatedV3` with transactions
-//! 4. Verifying payload building and retrieval
-
-use crate::common;
-
-use alloy_eips::eip2718::Encodable2718;
-use alloy_primitives::{Address, Bytes, B256};
-use alloy_rpc_types::engine::{ForkchoiceState, PayloadId};
-use eyre::Result;
-use reth_primitives::TransactionSigned;
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::str::FromStr;
-
-use common::{create_test_transactions, TEST_TO_ADDRESS};
-
-/// Rollkit Engine API payload attributes
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub(crate) struct RollkitEnginePayloadAttributes {
-    /// Standard Ethereum payload attributes
-    #[serde(flatten)]
-    pub inner: alloy_rpc_types::engine::PayloadAttributes,
-    /// Transactions to include in the payload
-    pub transactions: Option<Vec<Bytes>>,
-    /// Gas limit for the payload
-    pub gas_limit: Option<u64>,
-}
-
-/// Test configuration constants
-const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545";
-
-/// Test node manager for Engine API testing
-#[derive(Debug)]
-pub(crate) struct EngineApiTestNode {
-    pub rpc_url: String,
-    pub client: reqwest::Client,
-    pub mock_mode: bool,
-}
-
-impl EngineApiTestNode {
-    /// Creates a new test node and determines if it should use mock mode
-    pub(crate) async fn new() -> Result<Self> {
-        let client = reqwest::Client::new();
-        let rpc_url = DEFAULT_RPC_URL.to_string();
-
-        let mut node = Self {
-            rpc_url,
-            client,
-            mock_mode: false,
-        };
-
-        // Check if real node is available
-        if node.check_node_availability().await {
-            println!("✓ Connected to real node at {}", node.rpc_url);
-        } else {
-            println!(
-                "⚠ No real node available at {}, using mock mode",
-                node.rpc_url
-            );
-            node.mock_mode = true;
-        }
-
-        Ok(node)
-    }
-
-    /// Checks if a real node is available
-    async fn check_node_availability(&self) -> bool {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "method": "web3_clientVersion",
-            "params": [],
-            "id": 1
-        });
-
-        self.client
-            .post(&self.rpc_url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .timeout(std::time::Duration::from_millis(500))
-            .send()
-            .await
-            .is_ok()
-    }
-
-    /// Makes an Engine API RPC call
-    pub(crate) async fn engine_rpc_call(
-        &self,
-        method: &str,
-        params: serde_json::Value,
-    ) -> Result<serde_json::Value> {
-        if self.mock_mode {
-            return self.get_mock_response(method);
-        }
-
-        let request = json!({
-            "jsonrpc": "2.0",
-            "method": method,
-            "params": params,
-            "id": 1
-        });
-
-        let response = self
-            .client
-            .post(&self.rpc_url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await?;
-
-        let response_json: serde_json::Value = response.json().await?;
-
-        if let Some(error) = response_json.get("error") {
-            return Err(eyre::eyre!("RPC Error: {}", error));
-        }
-
-        Ok(response_json["result"].clone())
-    }
-
-    /// Returns mock responses for testing without a real node
-    fn get_mock_response(&self, method: &str) -> Result<serde_json::Value> {
-        match method {
-            "engine_forkchoiceUpdatedV3" => Ok(json!({
-                "payloadStatus": {
-                    "status": "VALID",
-                    "latestValidHash": format!("0x{:064x}", rand::random::<u64>()),
-                    "validationError": null
-                },
-                "payloadId": format!("0x{:016x}", rand::random::<u64>())
-            })),
-            "engine_getPayloadV3" => Ok(json!({
-                "executionPayload": {
-                    "parentHash": format!("0x{:064x}", rand::random::<u64>()),
-                    "feeRecipient": TEST_TO_ADDRESS,
-                    "stateRoot": format!("0x{:064x}", rand::random::<u64>()),
-                    "receiptsRoot": format!("0x{:064x}", rand::random::<u64>()),
-                    "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
-                    "prevRandao": format!("0x{:064x}", rand::random::<u64>()),
-                    "blockNumber": "0x1",
-                    "gasLimit": "0x1c9c380",
-                    "gasUsed": "0xa410",
-                    "timestamp": format!("0x{:x}", chrono::Utc::now().timestamp()),
-                    "extraData": "0x",
-                    "baseFeePerGas": "0x7",
-                    "blockHash": format!("0x{:064x}", rand::random::<u64>()),
-                    "transactions": [
-                        "0xf86c808504a817c800825208941234567890123456789012345678901234567890880de0b6b3a764000080820a95a01b6b6d1c7b6f6b5a7b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6ba01b6b6d1c7b6f6b5a7b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b",
-                        "0xf86c018504a817c800825208941234567890123456789012345678901234567890880de0b6b3a764000080820a96a01b6b6d1c7b6f6b5a7b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6ba01b6b6d1c7b6f6b5a7b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b6b"
-                    ]
-                },
-                "blockValue": "0x0",
-                "blobsBundle": {
-                    "commitments": [],
-                    "proofs": [],
-                    "blobs": []
-                }
-            })),
-            _ => Ok(json!({"result": "mock_response"})),
-        }
-    }
-
-    /// Calls `engine_forkchoiceUpdatedV3` with payload attributes
-    pub(crate) async fn fork_choice_updated_v3(
-        &self,
-        forkchoice_state: ForkchoiceState,
-        payload_attributes: Option<RollkitEnginePayloadAttributes>,
-    ) -> Result<serde_json::Value> {
-        let params = if let Some(attrs) = payload_attributes {
-            json!([forkchoice_state, attrs])
-        } else {
-            json!([forkchoice_state])
-        };
-
-        self.engine_rpc_call("engine_forkchoiceUpdatedV3", params)
-            .await
-    }
-
-    /// Gets a payload by ID
-    pub(crate) async fn get_payload_v3(&self, payload_id: PayloadId) -> Result<serde_json::Value> {
-        let params = json!([payload_id]);
-        self.engine_rpc_call("engine_getPayloadV3", params).await
-    }
-}
-
-/// Encodes a transaction to bytes for Engine API
-fn encode_transaction(tx: &TransactionSigned) -> Result<Bytes> {
-    let mut buf = Vec::new();
-    tx.encode_2718(&mut buf);
-    Ok(Bytes::from(buf))
-}
-
-/// Test 1: Basic fork choice update
-async fn test_basic_fork_choice_update(node: &EngineApiTestNode) -> Result<()> {
-    println!("Testing basic fork choice update...");
-
-    let forkchoice_state = ForkchoiceState {
-        head_block_hash: B256::random(),
-        safe_block_hash: B256::random(),
-        finalized_block_hash: B256::random(),
-    };
-
-    let result = node.fork_choice_updated_v3(forkchoice_state, None).await;
-
-    match result {
-        Ok(response) => {
-            println!("✓ Basic fork choice update succeeded: {response:?}");
-        }
-        Err(e) => {
-            // This is expected to fail with unknown hash in real node
-            println!("⚠ Fork choice update failed (expected for unknown hash): {e}");
-        }
-    }
-
-    Ok(())
-}
-
-/// Test 2: Fork choice update with transactions
-async fn test_fork_choice_with_transactions(node: &EngineApiTestNode) -> Result<()> {
-    println!("Testing fork choice update with transactions...");
-
-    // Create test transactions using common utilities
-    let transactions = create_test_transactions(2, 0);
-
-    // Encode transactions
-    let tx_bytes: Result<Vec<Bytes>> = transactions.iter().map(encode_transaction).collect();
-    let tx_bytes = tx_bytes?;
-
-    // Create payload attributes with transactions
-    let payload_attributes = RollkitEnginePayloadAttributes {
-        inner: alloy_rpc_types::engine::PayloadAttributes {
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            prev_randao: B256::random(),
-            suggested_fee_recipient: Address::from_str(TEST_TO_ADDRESS)?,
-            withdrawals: Some(vec![]),
-            parent_beacon_block_root: Some(B256::random()),
-        },
-        transactions: Some(tx_bytes),
-        gas_limit: Some(50_000), // Enough for both transactions
-    };
-
-    let forkchoice_state = ForkchoiceState {
-        head_block_hash: B256::random(),
-        safe_block_hash: B256::random(),
-        finalized_block_hash: B256::random(),
-    };
-
-    // Make the Engine API call
-    let result = node
-        .fork_choice_updated_v3(forkchoice_state, Some(payload_attributes))
-        .await?;
-
-    println!("✓ Fork choice update with transactions succeeded");
-
-    // Try to get the payload if we received a payload ID
-    if let Some(payload_id_value) = result.get("payloadId") {
-        if !payload_id_value.is_null() {
-            let payload_id: PayloadId = serde_json::from_value(payload_id_value.clone())?;
-
-            match node.get_payload_v3(payload_id).await {
-                Ok(payload) => {
-                    println!("✓ Successfully retrieved payload");
-
-                    // Verify transactions are included
-                    if let Some(execution_payload) = payload.get("executionPayload") {
-                        if let Some(transactions) = execution_payload.get("transactions") {
-                            let tx_count =
-                                transactions.as_array().map(|arr| arr.len()).unwrap_or(0);
-                            println!("✓ Payload contains {tx_count} transactions");
-
-                            if tx_count >= 2 {
-                                println!("✓ Expected number of transactions found in payload");
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("⚠ Failed to retrieve payload: {e}");
-                }
-            }
-        }
-    }
-
-    Ok(())
-}
-
-/// Test: Basic fork choice update
-#[tokio::test]
-async fn test_engine_api_basic_fork_choice_update() -> Result<()> {
-    let node = EngineApiTestNode::new().await?;
-    test_basic_fork_choice_update(&node).await
-}
-
-/// Test: Fork choice update with transactions
-#[tokio::test]
-async fn test_engine_api_fork_choice_with_transactions() -> Result<()> {
-    let node = EngineApiTestNode::new().await?;
-    test_fork_choice_with_transactions(&node).await
-}
-
-/// Integration test that runs all Engine API tests
-#[tokio::test]
-async fn test_rollkit_engine_api_integration() -> Result<()> {
-    println!("🚀 Rollkit Engine API Integration Test");
-    println!("=====================================");
-    println!("This test verifies Engine API compatibility with transaction passthrough");
-
-    // Initialize test node
-    let node = EngineApiTestNode::new().await?;
-
-    let mut passed = 0;
-    let mut failed = 0;
-
-    // Run tests
-    println!("\n=== Test 1: Basic Fork Choice Update ===");
-    match test_basic_fork_choice_update(&node).await {
-        Ok(_) => {
-            println!("✅ Test 1 PASSED");
-            passed += 1;
-        }
-        Err(e) => {
-            println!("❌ Test 1 FAILED: {e}");
-            failed += 1;
-        }
-    }
-
-    println!("\n=== Test 2: Fork Choice with Transactions ===");
-    match test_fork_choice_with_transactions(&node).await {
-        Ok(_) => {
-            println!("✅ Test 2 PASSED");
-            passed += 1;
-        }
-        Err(e) => {
-            println!("❌ Test 2 FAILED: {e}");
-            failed += 1;
-        }
-    }
-
-    // Results
-    println!("\n=== 📊 Test Results ===");
-    println!("✅ Passed: {passed}");
-    println!("❌ Failed: {failed}");
-    println!("📈 Total:  {}", passed + failed);
-
-    if failed == 0 {
-        println!("🎉 All Engine API tests passed!");
-        println!(
-            "The Rollkit payload builder successfully handles Engine API calls with transactions."
-        );
-    } else {
-        println!("⚠️ Some tests failed - this may be expected if no real node is running");
-        println!("Mock mode tests should still pass to verify the basic functionality");
-    }
-
-    // At least one test should pass to consider this successful
-    assert!(passed > 0, "At least one Engine API test should pass");
-
-    Ok(())
-}